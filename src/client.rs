@@ -15,12 +15,15 @@ mod config;
 mod connection;
 mod db;
 mod discover;
+mod executor;
 mod node;
 mod postman;
 mod proto;
 mod protocol;
+mod pubsub;
 mod runtime;
 mod until;
+mod watch;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
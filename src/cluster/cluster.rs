@@ -3,7 +3,7 @@ use crate::config::Config;
 use crate::connection::manager::ConnectionManager;
 use crate::db::dbvalue::DBValue;
 use crate::node::{NodeManager, ProposalAddNode};
-use crate::postman::{AsAny, LetterMessage};
+use crate::postman::LetterMessage;
 use crate::proto::RaftCmd;
 use crate::runtime::Runtime;
 use anyhow::anyhow;
@@ -168,6 +168,8 @@ impl ClusterNode {
                 EntryType::EntryNormal => {
                     let data = entry.get_data();
                     let command: Command = data.into();
+                    // raft日志索引在各副本上一致，用于生成单调递增的令牌
+                    let command = command.with_index(entry.index);
                     let cmd = command.inner_ref();
                     if cmd.is_valid() && cmd.is_write_type() && !cmd.is_raft_cmd() {
                         // 写数据库操作命令
@@ -206,6 +208,7 @@ impl ClusterNode {
 
         // tick
         self.tick();
+        app.set_leader(self.raft_group.raft.state == StateRole::Leader);
 
         // proposal
         loop {
@@ -369,3 +372,74 @@ pub fn start_cluster(
     });
     Ok(handler)
 }
+
+#[cfg(test)]
+mod test {
+    use super::ClusterNode;
+    use crate::command::{Command, ProposalCommand};
+    use crate::config::Config;
+    use crate::connection::manager::ConnectionManager;
+    use crate::db::database::Database;
+    use crate::db::dbvalue::DBValue;
+    use crate::node::{NodeTable, ShareNodeTable};
+    use crate::postman::{Channel, LetterMessage};
+    use crate::proto::ActiveExpireCmd;
+    use crate::runtime::Runtime;
+    use raft::prelude::ConfState;
+    use raft::storage::MemStorage;
+    use raft::RawNode;
+    use std::sync::Arc;
+    use tokio::sync::mpsc;
+
+    type Mailbox = mpsc::Sender<Box<dyn LetterMessage>>;
+
+    // 只有本节点一个投票成员的集群节点，返回节点、消息和提案的发送器
+    fn single_node(cfg: &Config) -> (ClusterNode, Mailbox, Mailbox) {
+        let storage = MemStorage::new_with_conf_state(ConfState::from((vec![cfg.node_id], vec![])));
+        let (mailbox_tx, mailbox) = mpsc::channel(8);
+        let (proposal_tx, proposal_mailbox) = mpsc::channel(8);
+        let node_table = ShareNodeTable::new(NodeTable::new(Arc::new(Config::default())));
+        let node = ClusterNode {
+            conn_manager: ConnectionManager::new(node_table),
+            raft_group: RawNode::with_default_logger(&cfg.raft_config, storage).unwrap(),
+            mailbox,
+            proposal_mailbox,
+        };
+        (node, mailbox_tx, proposal_tx)
+    }
+
+    #[tokio::test]
+    async fn propose_and_apply_test() {
+        let cfg = Config::default();
+        let app = Runtime::new(Arc::new(cfg));
+        let mut db_recv = app.postman.new_channel(Channel::DbCmdReq, 8).await.unwrap();
+        let (mut node, _mailbox_tx, proposal_tx) = single_node(&app.cfg);
+        node.raft_group.campaign().unwrap();
+
+        let mut db = Database::new();
+        db.set(String::from("session"), DBValue::String(String::from("token")));
+        db.expire_at("session", 1_500);
+        let sweep = Command::new(Box::new(ActiveExpireCmd { limit: 10 }), None).with_ts(2_000);
+        proposal_tx
+            .send(Box::new(ProposalCommand(sweep)))
+            .await
+            .unwrap();
+
+        // 提案提交后发送到本地数据库的执行队列
+        let mut applied = None;
+        for _ in 0..10 {
+            node.poll(&app).await.unwrap();
+            if let Ok(letter) = db_recv.try_recv() {
+                applied = letter.as_any().downcast_ref::<Command>().map(|c| c.try_clone());
+                break;
+            }
+        }
+        let command = applied.expect("proposal should be applied").unwrap();
+        assert!(app.is_leader());
+        assert!(command.index() > 0);
+        assert_eq!(command.ts(), 2_000);
+        db.set_clock(command.ts());
+        command.execute(Some(&app), Some(&mut db)).await.unwrap();
+        assert!(!db.contains_key("session"));
+    }
+}
@@ -9,10 +9,18 @@ use tokio::{
     task::JoinHandle,
 };
 use tokio_context::context::{Context, RefContext};
+use uuid::Uuid;
 
+use crate::proto::{
+    PSubscribeCmd, PUnsubscribeCmd, PushMessage, SelectCmd, SubscribeCmd, UnsubscribeCmd,
+    UnwatchCmd, WatchCmd,
+};
+use crate::watch::WatchFilter;
+use prost::Message;
 use crate::protocol::frame;
 use crate::protocol::{Segment, CURRENT_VERSION};
 use crate::runtime::Runtime;
+use crate::until;
 use crate::{
     command::Command,
     config::Config,
@@ -74,46 +82,137 @@ pub async fn connection(
     app: Option<&Runtime>,
     ctx: RefContext,
     stream: TcpStream,
-    mut send_msg_box: Option<mpsc::Receiver<Vec<Frame>>>,
+    send_msg_box: Option<mpsc::Receiver<Vec<Frame>>>,
 ) {
     let (mut ctx, _handler) = Context::with_parent(&ctx, None);
     let conn = Connection::new(stream);
+    // 未指定发送通道时使用会话的推送通道，用于推送订阅消息
+    let (push_tx, push_rx) = mpsc::channel(64);
+    let mut send_msg_box = send_msg_box.unwrap_or(push_rx);
+    let mut session = Session::new(push_tx);
     loop {
-        if send_msg_box.is_some() {
-            select! {
-                _ = ctx.done() => {
+        select! {
+            _ = ctx.done() => {
+                break;
+            },
+            frames_cnt = send_message(&conn, &mut send_msg_box) => {
+                if post_send_message(frames_cnt) {
                     break;
-                },
-                frames_cnt = send_message(&conn, send_msg_box.as_mut().unwrap()) => {
-                    if post_send_message(frames_cnt) {
-                        break;
-                    }
-                },
-                message = read_message(&conn) => {
-                    if post_read_message(&conn, message, app).await {
-                        break;
-                    }
                 }
-            }
-        } else {
-            select! {
-                _ = ctx.done() => {
+            },
+            message = read_message(&conn) => {
+                if post_read_message(&conn, message, app, &mut session).await {
                     break;
-                },
-                message = read_message(&conn) => {
-                    if post_read_message(&conn, message, app).await {
-                        break;
-                    }
                 }
             }
         }
     }
+    if let Some(app) = app {
+        if let Some(id) = session.subscriber {
+            app.pubsub.remove(id).await;
+        }
+        for id in session.watches.drain(..) {
+            app.watch.unwatch(id).await;
+        }
+    }
+}
+
+// 连接级别的状态
+pub struct Session {
+    // 会话ID，随连接建立生成，连接上的所有命令都使用该会话
+    id: String,
+    // 连接当前选择的命名空间，命令未指定命名空间时使用
+    namespace: String,
+    // 推送数据帧到连接的发送器
+    push: mpsc::Sender<Vec<Frame>>,
+    // 发布订阅的订阅者ID，第一次订阅时注册
+    subscriber: Option<u64>,
+    // 连接上注册的监听ID
+    watches: Vec<u64>,
+}
+
+impl Session {
+    fn new(push: mpsc::Sender<Vec<Frame>>) -> Self {
+        Session {
+            id: Uuid::new_v4().to_string(),
+            namespace: String::new(),
+            push,
+            subscriber: None,
+            watches: Vec::new(),
+        }
+    }
+
+    async fn subscriber_id(&mut self, app: &Runtime) -> u64 {
+        match self.subscriber {
+            Some(id) => id,
+            None => {
+                let id = app.pubsub.register(self.push.clone()).await;
+                self.subscriber = Some(id);
+                id
+            }
+        }
+    }
+}
+
+// 处理作用于连接本身的命令，返回是否已处理
+async fn handle_session_command(
+    command: &Command,
+    app: Option<&Runtime>,
+    session: &mut Session,
+) -> anyhow::Result<bool> {
+    let cmd = command.inner_ref().as_any();
+    // 切换连接的命名空间
+    if let Some(select) = cmd.downcast_ref::<SelectCmd>() {
+        session.namespace = select.namespace.clone();
+        return Ok(true);
+    }
+    let Some(app) = app else {
+        return Ok(false);
+    };
+    if let Some(subscribe) = cmd.downcast_ref::<SubscribeCmd>() {
+        let id = session.subscriber_id(app).await;
+        app.pubsub.subscribe(id, &subscribe.channels, false).await;
+    } else if let Some(subscribe) = cmd.downcast_ref::<PSubscribeCmd>() {
+        let id = session.subscriber_id(app).await;
+        app.pubsub.subscribe(id, &subscribe.patterns, true).await;
+    } else if let Some(unsubscribe) = cmd.downcast_ref::<UnsubscribeCmd>() {
+        if let Some(id) = session.subscriber {
+            app.pubsub.unsubscribe(id, &unsubscribe.channels, false).await;
+        }
+    } else if let Some(unsubscribe) = cmd.downcast_ref::<PUnsubscribeCmd>() {
+        if let Some(id) = session.subscriber {
+            app.pubsub.unsubscribe(id, &unsubscribe.patterns, true).await;
+        }
+    } else if let Some(watch) = cmd.downcast_ref::<WatchCmd>() {
+        if !app.cfg.watch_enabled {
+            return Err(anyhow::anyhow!("Watch is disabled, enable watch_enabled first"));
+        }
+        let filter = WatchFilter::from_cmd(watch)?;
+        let namespace = if command.namespace().is_empty() {
+            &session.namespace
+        } else {
+            command.namespace()
+        };
+        let id = app
+            .watch
+            .watch(namespace, filter, watch.start_revision, session.push.clone())
+            .await?;
+        session.watches.push(id);
+    } else if cmd.is::<UnwatchCmd>() {
+        for id in session.watches.drain(..) {
+            app.watch.unwatch(id).await;
+        }
+    } else {
+        return Ok(false);
+    }
+    Ok(true)
 }
 
 pub enum CmdServerMessage {
     PING,
     PONG,
     CMD(Command),
+    PUSH(PushMessage),
     ERROR(String),
 }
 
@@ -174,6 +273,13 @@ async fn read_message(conn: &Connection) -> anyhow::Result<Option<CmdServerMessa
                         }
                         frames.push(frame);
                     }
+                    Kind::PUSH => {
+                        if frame.is_last() {
+                            frames.push(frame);
+                            return Ok(Some(CmdServerMessage::PUSH(parse_push_message(&frames)?)));
+                        }
+                        frames.push(frame);
+                    }
                     Kind::ERROR => {
                         if frame.is_last() {
                             frames.push(frame);
@@ -203,13 +309,14 @@ async fn post_read_message(
     conn: &Connection,
     message: anyhow::Result<Option<CmdServerMessage>>,
     app: Option<&Runtime>,
+    session: &mut Session,
 ) -> bool {
     match message {
         Ok(message_opt) => {
             match message_opt {
                 Some(message) => {
                     // 处理消息
-                    if let Err(err) = handle_cmd_server_message(&conn, message, app).await {
+                    if let Err(err) = handle_cmd_server_message(&conn, message, app, session).await {
                         error!("处理命令错误: {:?}", err);
                         if let Err(reply_err) = try_reply_error(&conn, err).await {
                             error!("回复客户端错误: {:?}", reply_err);
@@ -261,6 +368,15 @@ async fn try_reply_error(conn: &Connection, error: anyhow::Error) -> anyhow::Res
     Ok(())
 }
 
+fn parse_push_message(frames: &[Frame]) -> anyhow::Result<PushMessage> {
+    let capacity: usize = frames.iter().map(|i| i.length.inner_value() as usize).sum();
+    let mut payload = BytesMut::with_capacity(capacity);
+    for frame in frames {
+        payload.extend_from_slice(&frame.payload);
+    }
+    Ok(PushMessage::decode(&payload[..])?)
+}
+
 fn parse_error_message(frames: &[Frame]) -> String {
     let capacity: usize = frames.iter().map(|i| i.length.inner_value() as usize).sum();
     let mut payload = BytesMut::with_capacity(capacity);
@@ -274,6 +390,7 @@ async fn handle_cmd_server_message(
     conn: &Connection,
     msg: CmdServerMessage,
     app: Option<&Runtime>,
+    session: &mut Session,
 ) -> anyhow::Result<()> {
     // debug!("receive new command: {}", cmd);
     match msg {
@@ -288,12 +405,23 @@ async fn handle_cmd_server_message(
         CmdServerMessage::PONG => {
             debug!("收到PONG帧, from={}", conn.get_peer_addr());
         }
-        CmdServerMessage::CMD(command) => {
+        CmdServerMessage::CMD(mut command) => {
             info!(
                 "收到命令: from={}, command={}",
                 conn.get_peer_addr(),
                 command
             );
+            if handle_session_command(&command, app, session).await? {
+                return Ok(());
+            }
+            if command.namespace().is_empty() {
+                command = command.with_namespace(session.namespace.clone());
+            }
+            // 客户端不能冒用其它会话，一律使用连接的会话
+            command = command.with_session(session.id.clone());
+            // 客户端提供的时间戳不可信，使用本节点时间覆盖，
+            // 只有经raft复制的命令才使用提案时写入的时间戳
+            command = command.with_ts(until::now_ts()?);
             if command.inner_ref().is_raft_cmd() {
                 if let Some(app) = app {
                     if let Err(err) = app.postman.send(Box::new(command)).await {
@@ -305,6 +433,9 @@ async fn handle_cmd_server_message(
                 command.execute(app, None).await?;
             }
         }
+        CmdServerMessage::PUSH(push) => {
+            info!("收到推送: from={}, push={:?}", conn.get_peer_addr(), push);
+        }
         CmdServerMessage::ERROR(err_msg) => {
             warn!(
                 "收到错误响应: from={}, error={}",
@@ -0,0 +1,244 @@
+use super::register_info::parse_proto_command;
+use super::{BlockedError, CommandType, ExecutableCommand};
+use crate::db::{database::Database, dbvalue::DBValue};
+use crate::proto::command_message::Cmd;
+use crate::proto::BatchCmd;
+use crate::runtime::Runtime;
+use anyhow::anyhow;
+use async_trait::async_trait;
+use std::any::Any;
+use std::fmt::Display;
+
+impl BatchCmd {
+    // 解析批量命令中的所有命令，不允许嵌套批量命令和raft命令，
+    // 命名空间、会话和时间戳由批量命令统一提供，子命令不能单独设置
+    fn commands(&self) -> anyhow::Result<Vec<Box<dyn ExecutableCommand>>> {
+        self.cmds
+            .iter()
+            .enumerate()
+            .map(|(i, message)| {
+                if !message.namespace.is_empty()
+                    || !message.session.is_empty()
+                    || message.ts.is_some()
+                {
+                    return Err(anyhow!(
+                        "Batch command #{} can not set namespace, session or ts",
+                        i
+                    ));
+                }
+                match &message.cmd {
+                    Some(Cmd::Batch(_) | Cmd::Raft(_)) => {
+                        Err(anyhow!("Batch command #{} can not be nested", i))
+                    }
+                    Some(cmd) => parse_proto_command(cmd.clone()),
+                    None => Err(anyhow!("Batch command #{} is empty", i)),
+                }
+            })
+            .collect()
+    }
+}
+
+// 解析后的批量命令，子命令只在构造时解析一次
+pub struct Batch {
+    cmd: BatchCmd,
+    // 解析失败时保存错误信息，执行时返回
+    commands: Result<Vec<Box<dyn ExecutableCommand>>, String>,
+}
+
+impl From<BatchCmd> for Batch {
+    fn from(cmd: BatchCmd) -> Self {
+        let commands = cmd.commands().map_err(|err| err.to_string());
+        Batch { cmd, commands }
+    }
+}
+
+fn entry(ok: bool, value: DBValue) -> DBValue {
+    DBValue::List([DBValue::Boolean(ok), value].into())
+}
+
+// 包含写命令的批量命令整体作为一条raft日志复制，只包含读命令时在本地执行
+#[async_trait]
+impl ExecutableCommand for Batch {
+    fn cmd_type(&self) -> CommandType {
+        // 解析失败的批量命令按读命令处理，执行时直接返回错误，不写入raft日志
+        match &self.commands {
+            Ok(commands) if commands.iter().any(|cmd| cmd.is_write_type()) => CommandType::WRITE,
+            _ => CommandType::READ,
+        }
+    }
+
+    fn may_grow_memory(&self) -> bool {
+        match &self.commands {
+            Ok(commands) => commands.iter().any(|cmd| cmd.may_grow_memory()),
+            Err(_) => false,
+        }
+    }
+
+    async fn execute(
+        &self,
+        app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        let commands = self.commands.as_ref().map_err(|err| anyhow!("{}", err))?;
+        if let Some(db) = db {
+            // 按顺序执行，每个命令的结果为[true, 返回值]或者[false, 错误信息]。
+            // 遇到错误时停止，之前的命令已经生效不回滚，之后的命令不执行也没有结果
+            let mut results = Vec::with_capacity(commands.len());
+            for (i, cmd) in commands.iter().enumerate() {
+                match cmd.execute(app, Some(&mut *db)).await {
+                    Ok(result) => results.push(entry(true, result.unwrap_or(DBValue::None))),
+                    Err(err) => {
+                        let err = if err.is::<BlockedError>() {
+                            format!("Batch command #{} {} can not block", i, cmd)
+                        } else {
+                            format!("Batch command #{} {} failed: {}", i, cmd, err)
+                        };
+                        results.push(entry(false, DBValue::String(err)));
+                        break;
+                    }
+                }
+            }
+            return Ok(Some(DBValue::List(results.into())));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::Batch(self.cmd.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for Batch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Batch ({} commands)", self.cmd.cmds.len())
+    }
+}
+
+impl TryFrom<Cmd> for BatchCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::Batch(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{entry, Batch};
+    use crate::command::{Command, ExecutableCommand};
+    use crate::db::database::Database;
+    use crate::db::dbvalue::DBValue;
+    use crate::proto::command_message::Cmd;
+    use crate::proto::{BatchCmd, BlPopCmd, CommandMessage, HashGetCmd, HashPutCmd, LPushCmd};
+    use std::collections::VecDeque;
+
+    fn message(cmd: Cmd) -> CommandMessage {
+        CommandMessage {
+            cmd: Some(cmd),
+            ..Default::default()
+        }
+    }
+
+    fn batch(cmds: Vec<CommandMessage>) -> Batch {
+        Batch::from(BatchCmd { cmds })
+    }
+
+    fn hash_put(user: u32) -> CommandMessage {
+        message(Cmd::HashPut(HashPutCmd {
+            key: format!("user:{}", user),
+            member_key: String::from("name"),
+            member_value: Some(DBValue::String(format!("name-{}", user)).into()),
+            ephemeral: false,
+        }))
+    }
+
+    fn hash_get(user: u32) -> CommandMessage {
+        message(Cmd::HashGet(HashGetCmd {
+            key: format!("user:{}", user),
+            member_key: String::from("name"),
+        }))
+    }
+
+    #[tokio::test]
+    async fn batch_test() {
+        let mut db = Database::new();
+        let puts = batch((0..3).map(hash_put).collect());
+        assert!(puts.is_write_type());
+        // 整个批量命令编码为一条日志
+        let payload = Command::new(Box::new(puts), None)
+            .encode_to_payload()
+            .unwrap();
+        let command: Command = (&payload[..]).into();
+        command.execute(None, Some(&mut db)).await.unwrap();
+
+        let gets = batch((0..4).map(hash_get).collect());
+        assert!(gets.is_read_type());
+        let result = gets.execute(None, Some(&mut db)).await.unwrap();
+        let expected = vec![
+            entry(true, DBValue::String(String::from("name-0"))),
+            entry(true, DBValue::String(String::from("name-1"))),
+            entry(true, DBValue::String(String::from("name-2"))),
+            entry(true, DBValue::None),
+        ];
+        assert!(result == Some(DBValue::List(expected.into())));
+
+        let nested = batch(vec![message(Cmd::Batch(gets.cmd.clone()))]);
+        assert!(nested.execute(None, Some(&mut db)).await.is_err());
+        // 子命令不能单独设置命名空间、会话和时间戳
+        let mut scoped = hash_get(0);
+        scoped.namespace = String::from("other");
+        assert!(batch(vec![scoped])
+            .execute(None, Some(&mut db))
+            .await
+            .is_err());
+        let mut scoped = hash_get(0);
+        scoped.session = String::from("other");
+        assert!(batch(vec![scoped])
+            .execute(None, Some(&mut db))
+            .await
+            .is_err());
+        let mut scoped = hash_put(0);
+        scoped.ts = Some(Default::default());
+        let scoped = batch(vec![scoped]);
+        assert!(scoped.is_read_type());
+        assert!(scoped.execute(None, Some(&mut db)).await.is_err());
+        let blocking = batch(vec![message(Cmd::BlPop(BlPopCmd {
+            keys: vec![String::from("queue")],
+            timeout_ms: 0,
+        }))]);
+        let result = blocking.execute(None, Some(&mut db)).await.unwrap();
+        assert!(matches!(result, Some(DBValue::List(results)) if failed_at(&results) == Some(0)));
+
+        // 出错的命令之前的结果仍然返回，之后的命令不执行
+        let mixed = batch(vec![
+            hash_put(4),
+            message(Cmd::LPush(LPushCmd {
+                key: String::from("queue"),
+                values: vec![],
+            })),
+            hash_put(5),
+        ]);
+        let Some(DBValue::List(results)) = mixed.execute(None, Some(&mut db)).await.unwrap() else {
+            panic!("batch should reply a list");
+        };
+        assert_eq!(results.len(), 2);
+        assert_eq!(failed_at(&results), Some(1));
+        assert!(db.contains_key("user:4"));
+        assert!(!db.contains_key("user:5"));
+    }
+
+    // 第一个失败的命令的位置
+    fn failed_at(results: &VecDeque<DBValue>) -> Option<usize> {
+        results.iter().position(
+            |result| matches!(result, DBValue::List(entry) if entry[0] == DBValue::Boolean(false)),
+        )
+    }
+}
@@ -0,0 +1,512 @@
+use super::{CommandType, ExecutableCommand};
+use crate::db::bitmap::{self, BitOp, FieldType, Overflow};
+use crate::db::{database::Database, dbvalue::DBValue};
+use crate::proto::command_message::Cmd;
+use crate::proto::{
+    BitCountCmd, BitFieldCmd, BitFieldOpKind, BitFieldOverflow, BitOpCmd, BitOperation, BitPosCmd,
+    GetBitCmd, SetBitCmd,
+};
+use crate::runtime::Runtime;
+use anyhow::anyhow;
+use async_trait::async_trait;
+use std::any::Any;
+use std::fmt::Display;
+
+// 位图读取命令接受String或者Bytes类型的值，key不存在时视为空值
+fn get_bitmap<'a>(db: &'a Database, key: &str) -> anyhow::Result<&'a [u8]> {
+    match db.get(key) {
+        Some(DBValue::Bytes(b)) => Ok(&b[..]),
+        Some(DBValue::String(s)) => Ok(s.as_bytes()),
+        Some(value) => Err(anyhow!(
+            "Mismatch DBValue type, required String or Bytes but got {}",
+            value
+        )),
+        None => Ok(&[]),
+    }
+}
+
+// 位图修改命令只接受Bytes类型的值，key不存在时创建
+fn get_bitmap_mut<'a>(db: &'a mut Database, key: &str) -> anyhow::Result<&'a mut Vec<u8>> {
+    if !db.contains_key(key) {
+        db.set(String::from(key), DBValue::Bytes(Vec::new()));
+    }
+    match db.get_mut(key) {
+        Some(DBValue::Bytes(b)) => Ok(b),
+        Some(value) => Err(anyhow!(
+            "Mismatch DBValue type, required Bytes but got {}",
+            value
+        )),
+        None => Err(anyhow!("Key {} does not exist", key)),
+    }
+}
+
+impl From<BitOperation> for BitOp {
+    fn from(value: BitOperation) -> Self {
+        match value {
+            BitOperation::And => BitOp::And,
+            BitOperation::Or => BitOp::Or,
+            BitOperation::Xor => BitOp::Xor,
+            BitOperation::Not => BitOp::Not,
+        }
+    }
+}
+
+impl From<BitFieldOverflow> for Overflow {
+    fn from(value: BitFieldOverflow) -> Self {
+        match value {
+            BitFieldOverflow::Wrap => Overflow::Wrap,
+            BitFieldOverflow::Sat => Overflow::Sat,
+            BitFieldOverflow::Fail => Overflow::Fail,
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for SetBitCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            bitmap::check_offset(self.offset)?;
+            let buf = get_bitmap_mut(db, &self.key)?;
+            let old = bitmap::set_bit(buf, self.offset, self.value);
+            return Ok(Some(DBValue::Int64(old as i64)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::SetBit(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for SetBitCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "SetBit {} {} {}",
+            &self.key, self.offset, self.value as u8
+        )
+    }
+}
+
+impl TryFrom<Cmd> for SetBitCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::SetBit(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for GetBitCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::READ
+    }
+
+    fn may_grow_memory(&self) -> bool {
+        false
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let buf = get_bitmap(db, &self.key)?;
+            return Ok(Some(DBValue::Int64(
+                bitmap::get_bit(buf, self.offset) as i64
+            )));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::GetBit(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for GetBitCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "GetBit {} {}", &self.key, self.offset)
+    }
+}
+
+impl TryFrom<Cmd> for GetBitCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::GetBit(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for BitCountCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::READ
+    }
+
+    fn may_grow_memory(&self) -> bool {
+        false
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let buf = get_bitmap(db, &self.key)?;
+            let count = match &self.range {
+                Some(range) => {
+                    match bitmap::bit_range(buf.len(), range.start, range.end, range.bit_unit) {
+                        Some(range) => bitmap::bit_count(buf, Some(range)),
+                        None => 0,
+                    }
+                }
+                None => bitmap::bit_count(buf, None),
+            };
+            return Ok(Some(DBValue::Int64(count as i64)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::BitCount(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for BitCountCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "BitCount {}", &self.key)
+    }
+}
+
+impl TryFrom<Cmd> for BitCountCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::BitCount(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for BitPosCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::READ
+    }
+
+    fn may_grow_memory(&self) -> bool {
+        false
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let buf = get_bitmap(db, &self.key)?;
+            let range = match &self.range {
+                Some(range) => bitmap::bit_range(buf.len(), range.start, range.end, range.bit_unit),
+                None => bitmap::bit_range(buf.len(), 0, -1, false),
+            };
+            let pos = match range {
+                Some((start, end)) => match bitmap::bit_pos(buf, self.bit, start, end) {
+                    Some(pos) => pos as i64,
+                    // 未指定区间时查找0，值的末尾视为用0补齐
+                    None if !self.bit && self.range.is_none() => end as i64 + 1,
+                    None => -1,
+                },
+                None if !self.bit && self.range.is_none() => 0,
+                None => -1,
+            };
+            return Ok(Some(DBValue::Int64(pos)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::BitPos(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for BitPosCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "BitPos {} {}", &self.key, self.bit as u8)
+    }
+}
+
+impl TryFrom<Cmd> for BitPosCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::BitPos(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for BitOpCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let op = BitOp::from(
+                BitOperation::try_from(self.op)
+                    .map_err(|_| anyhow!("Invalid bit operation {}", self.op))?,
+            );
+            let mut sources = Vec::with_capacity(self.keys.len());
+            for key in self.keys.iter() {
+                sources.push(get_bitmap(db, key)?);
+            }
+            let result = bitmap::bit_op(op, &sources)?;
+            let len = result.len();
+            if result.is_empty() {
+                db.remove(&self.destination);
+            } else {
+                db.set(self.destination.clone(), DBValue::Bytes(result));
+            }
+            return Ok(Some(DBValue::Int64(len as i64)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::BitOp(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for BitOpCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "BitOp {:?} {} {}",
+            self.op(),
+            &self.destination,
+            self.keys.join(" ")
+        )
+    }
+}
+
+impl TryFrom<Cmd> for BitOpCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::BitOp(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for BitFieldCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            // 在副本上修改，全部操作完成后再写回，只读的操作不会创建key
+            let mut buf = get_bitmap(db, &self.key)?.to_vec();
+            let mut modified = false;
+            let mut results = Vec::with_capacity(self.ops.len());
+            for op in self.ops.iter() {
+                let ty = FieldType::parse(&op.r#type)?;
+                let offset = ty.parse_offset(&op.offset)?;
+                let overflow = Overflow::from(op.overflow());
+                let current = bitmap::get_field(&buf, offset, ty);
+                let result = match op.kind() {
+                    BitFieldOpKind::Get => Some(current),
+                    BitFieldOpKind::Set => {
+                        bitmap::incr_field(0, op.value, ty, overflow).map(|value| {
+                            bitmap::set_field(&mut buf, offset, ty, value);
+                            current
+                        })
+                    }
+                    BitFieldOpKind::IncrBy => bitmap::incr_field(current, op.value, ty, overflow)
+                        .inspect(|value| {
+                            bitmap::set_field(&mut buf, offset, ty, *value);
+                        }),
+                };
+                if op.kind() != BitFieldOpKind::Get && result.is_some() {
+                    modified = true;
+                }
+                results.push(result.map_or(DBValue::None, DBValue::Int64));
+            }
+            if modified {
+                let bitmap = get_bitmap_mut(db, &self.key)?;
+                *bitmap = buf;
+            }
+            return Ok(Some(DBValue::List(results.into())));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::BitField(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for BitFieldCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "BitField {} ({} ops)", &self.key, self.ops.len())
+    }
+}
+
+impl TryFrom<Cmd> for BitFieldCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::BitField(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::command::ExecutableCommand;
+    use crate::db::database::Database;
+    use crate::db::dbvalue::DBValue;
+    use crate::proto::{
+        BitCountCmd, BitFieldCmd, BitFieldOp, BitFieldOpKind, BitFieldOverflow, BitOpCmd,
+        BitOperation, BitPosCmd, SetBitCmd,
+    };
+
+    fn set_bit(key: &str, offset: u64) -> SetBitCmd {
+        SetBitCmd {
+            key: String::from(key),
+            offset,
+            value: true,
+        }
+    }
+
+    #[tokio::test]
+    async fn daily_active_test() {
+        let mut db = Database::new();
+        // 用户ID作为位偏移
+        for (key, user) in [
+            ("active:1", 3),
+            ("active:1", 10),
+            ("active:2", 10),
+            ("active:2", 12),
+        ] {
+            set_bit(key, user)
+                .execute(None, Some(&mut db))
+                .await
+                .unwrap();
+        }
+        let result = set_bit("active:1", 3)
+            .execute(None, Some(&mut db))
+            .await
+            .unwrap();
+        assert!(result == Some(DBValue::Int64(1)));
+
+        let both = BitOpCmd {
+            op: BitOperation::And as i32,
+            destination: String::from("active:both"),
+            keys: vec![String::from("active:1"), String::from("active:2")],
+        };
+        both.execute(None, Some(&mut db)).await.unwrap();
+        let count = BitCountCmd {
+            key: String::from("active:both"),
+            range: None,
+        };
+        let result = count.execute(None, Some(&mut db)).await.unwrap();
+        assert!(result == Some(DBValue::Int64(1)));
+        let pos = BitPosCmd {
+            key: String::from("active:both"),
+            bit: true,
+            range: None,
+        };
+        let result = pos.execute(None, Some(&mut db)).await.unwrap();
+        assert!(result == Some(DBValue::Int64(10)));
+
+        let op = |kind: BitFieldOpKind, offset: &str, value: i64| BitFieldOp {
+            kind: kind as i32,
+            r#type: String::from("u4"),
+            offset: String::from(offset),
+            value,
+            overflow: BitFieldOverflow::Fail as i32,
+        };
+        let field = BitFieldCmd {
+            key: String::from("counters"),
+            ops: vec![
+                op(BitFieldOpKind::Set, "#1", 14),
+                op(BitFieldOpKind::IncrBy, "#1", 1),
+                op(BitFieldOpKind::IncrBy, "#1", 1),
+                op(BitFieldOpKind::Get, "4", 0),
+            ],
+        };
+        let result = field.execute(None, Some(&mut db)).await.unwrap();
+        let expected = vec![
+            DBValue::Int64(0),
+            DBValue::Int64(15),
+            DBValue::None,
+            DBValue::Int64(15),
+        ];
+        assert!(result == Some(DBValue::List(expected.into())));
+        assert!(db.get("counters") == Some(&DBValue::Bytes(vec![0x0f])));
+    }
+}
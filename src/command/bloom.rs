@@ -0,0 +1,247 @@
+use super::{CommandType, ExecutableCommand};
+use crate::db::bloom::{BloomFilter, DEFAULT_CAPACITY, DEFAULT_ERROR_RATE, DEFAULT_EXPANSION};
+use crate::db::{database::Database, dbvalue::DBValue};
+use crate::proto::command_message::Cmd;
+use crate::proto::{BfAddCmd, BfExistsCmd, BfReserveCmd};
+use crate::runtime::Runtime;
+use anyhow::anyhow;
+use async_trait::async_trait;
+use std::any::Any;
+use std::fmt::Display;
+
+fn get_bloom<'a>(db: &'a Database, key: &str) -> anyhow::Result<Option<&'a BloomFilter>> {
+    match db.get(key) {
+        Some(DBValue::BloomFilter(bloom)) => Ok(Some(bloom)),
+        Some(value) => Err(anyhow!(
+            "Mismatch DBValue type, required BloomFilter but got {}",
+            value
+        )),
+        None => Ok(None),
+    }
+}
+
+fn get_bloom_mut<'a>(
+    db: &'a mut Database,
+    key: &str,
+) -> anyhow::Result<Option<&'a mut BloomFilter>> {
+    match db.get_mut(key) {
+        Some(DBValue::BloomFilter(bloom)) => Ok(Some(bloom)),
+        Some(value) => Err(anyhow!(
+            "Mismatch DBValue type, required BloomFilter but got {}",
+            value
+        )),
+        None => Ok(None),
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for BfReserveCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            if db.contains_key(&self.key) {
+                return Err(anyhow!("Key {} already exists", &self.key));
+            }
+            let expansion = match (self.non_scaling, self.expansion) {
+                (true, _) => 0,
+                (false, 0) => DEFAULT_EXPANSION,
+                (false, expansion) => expansion,
+            };
+            let bloom = BloomFilter::new(self.error_rate, self.capacity, expansion)?;
+            db.set(self.key.clone(), DBValue::BloomFilter(bloom));
+            return Ok(Some(DBValue::Boolean(true)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::BfReserve(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for BfReserveCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "BfReserve {} {} {}",
+            &self.key, self.error_rate, self.capacity
+        )
+    }
+}
+
+impl TryFrom<Cmd> for BfReserveCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::BfReserve(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for BfAddCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            // key不存在时使用默认参数创建
+            let added = match get_bloom_mut(db, &self.key)? {
+                Some(bloom) => bloom.add(self.item.as_bytes())?,
+                None => {
+                    let mut bloom =
+                        BloomFilter::new(DEFAULT_ERROR_RATE, DEFAULT_CAPACITY, DEFAULT_EXPANSION)?;
+                    bloom.add(self.item.as_bytes())?;
+                    db.set(self.key.clone(), DBValue::BloomFilter(bloom));
+                    true
+                }
+            };
+            return Ok(Some(DBValue::Boolean(added)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::BfAdd(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for BfAddCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "BfAdd {} {}", &self.key, &self.item)
+    }
+}
+
+impl TryFrom<Cmd> for BfAddCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::BfAdd(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for BfExistsCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::READ
+    }
+
+    fn may_grow_memory(&self) -> bool {
+        false
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let exists =
+                get_bloom(db, &self.key)?.is_some_and(|bloom| bloom.contains(self.item.as_bytes()));
+            return Ok(Some(DBValue::Boolean(exists)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::BfExists(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for BfExistsCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "BfExists {} {}", &self.key, &self.item)
+    }
+}
+
+impl TryFrom<Cmd> for BfExistsCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::BfExists(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::command::ExecutableCommand;
+    use crate::db::database::Database;
+    use crate::db::dbvalue::DBValue;
+    use crate::proto::{BfAddCmd, BfExistsCmd, BfReserveCmd};
+
+    fn add(key: &str, item: &str) -> BfAddCmd {
+        BfAddCmd {
+            key: String::from(key),
+            item: String::from(item),
+        }
+    }
+
+    #[tokio::test]
+    async fn bloom_test() {
+        let mut db = Database::new();
+        let reserve = BfReserveCmd {
+            key: String::from("seen"),
+            error_rate: 0.001,
+            capacity: 2,
+            expansion: 0,
+            non_scaling: true,
+        };
+        reserve.execute(None, Some(&mut db)).await.unwrap();
+        assert!(reserve.execute(None, Some(&mut db)).await.is_err());
+        let result = add("seen", "a").execute(None, Some(&mut db)).await.unwrap();
+        assert!(result == Some(DBValue::Boolean(true)));
+        let result = add("seen", "a").execute(None, Some(&mut db)).await.unwrap();
+        assert!(result == Some(DBValue::Boolean(false)));
+        add("seen", "b").execute(None, Some(&mut db)).await.unwrap();
+        // 不扩容的过滤器写满后拒绝添加
+        assert!(add("seen", "c").execute(None, Some(&mut db)).await.is_err());
+
+        add("other", "x")
+            .execute(None, Some(&mut db))
+            .await
+            .unwrap();
+        let exists = BfExistsCmd {
+            key: String::from("other"),
+            item: String::from("x"),
+        };
+        let result = exists.execute(None, Some(&mut db)).await.unwrap();
+        assert!(result == Some(DBValue::Boolean(true)));
+        let value = db.get("other").unwrap().clone();
+        assert!(DBValue::from(value.to_protobuf()) == value);
+    }
+}
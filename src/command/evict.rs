@@ -0,0 +1,64 @@
+use super::{CommandType, ExecutableCommand};
+use crate::db::{database::Database, dbvalue::DBValue};
+use crate::proto::command_message::Cmd;
+use crate::proto::EvictCmd;
+use crate::runtime::Runtime;
+use anyhow::anyhow;
+use async_trait::async_trait;
+use std::any::Any;
+use std::fmt::Display;
+
+#[async_trait]
+impl ExecutableCommand for EvictCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    fn may_grow_memory(&self) -> bool {
+        false
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            // 淘汰的key由提案节点选出，副本只负责删除
+            let evicted = self
+                .keys
+                .iter()
+                .filter(|key| db.evict(key).is_some())
+                .count();
+            db.keyspace_mut().stats.evicted_keys += evicted as u64;
+            return Ok(Some(DBValue::Int64(evicted as i64)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::Evict(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for EvictCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Evict [{}]", self.keys.join(","))
+    }
+}
+
+impl TryFrom<Cmd> for EvictCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::Evict(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
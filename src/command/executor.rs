@@ -0,0 +1,234 @@
+use super::{CommandType, ExecutableCommand};
+use crate::db::{database::Database, dbvalue::DBValue};
+use crate::executor::{RemoteTask, TaskTarget};
+use crate::proto::command_message::Cmd;
+use crate::proto::{ExecuteTaskCmd, SubmitTaskCmd, TaskResultCmd};
+use crate::runtime::Runtime;
+use ahash::AHashMap;
+use anyhow::anyhow;
+use async_trait::async_trait;
+use std::any::Any;
+use std::fmt::Display;
+
+impl SubmitTaskCmd {
+    pub fn target(&self) -> TaskTarget {
+        if self.all {
+            TaskTarget::All
+        } else if self.node != 0 {
+            TaskTarget::Node(self.node)
+        } else if !self.key.is_empty() {
+            TaskTarget::KeyOwner(self.key.clone())
+        } else {
+            TaskTarget::Local
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for SubmitTaskCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::READ
+    }
+
+    fn may_grow_memory(&self) -> bool {
+        false
+    }
+
+    async fn execute(
+        &self,
+        app: Option<&Runtime>,
+        _db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(app) = app {
+            // 等待所有目标节点执行完成，在所有节点上执行时返回节点ID到结果的hash
+            let args = self.args.iter().cloned().map(DBValue::from).collect();
+            let target = self.target();
+            let futures = app
+                .executor
+                .submit(&app.postman, target.clone(), &self.task, args)
+                .await?;
+            if target != TaskTarget::All {
+                return match futures.into_iter().next() {
+                    Some(future) => Ok(Some(future.await?)),
+                    None => Err(anyhow!("No node to run task {}", &self.task)),
+                };
+            }
+            let mut results = AHashMap::new();
+            for future in futures {
+                let node = future.node.to_string();
+                results.insert(node, future.await?);
+            }
+            return Ok(Some(DBValue::Hash(results)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::SubmitTask(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for SubmitTaskCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "SubmitTask {} {:?} ({} args)",
+            &self.task,
+            self.target(),
+            self.args.len()
+        )
+    }
+}
+
+impl TryFrom<Cmd> for SubmitTaskCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::SubmitTask(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for ExecuteTaskCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::READ
+    }
+
+    fn may_grow_memory(&self) -> bool {
+        false
+    }
+
+    async fn execute(
+        &self,
+        app: Option<&Runtime>,
+        _db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(app) = app {
+            // 任务交给执行服务在后台执行，避免阻塞节点之间的连接
+            app.postman.send(Box::new(RemoteTask(self.clone()))).await?;
+            return Ok(None);
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::ExecuteTask(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for ExecuteTaskCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "ExecuteTask {} from {} ({} args)",
+            &self.task,
+            self.origin,
+            self.args.len()
+        )
+    }
+}
+
+impl TryFrom<Cmd> for ExecuteTaskCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::ExecuteTask(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for TaskResultCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::READ
+    }
+
+    fn may_grow_memory(&self) -> bool {
+        false
+    }
+
+    async fn execute(
+        &self,
+        app: Option<&Runtime>,
+        _db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(app) = app {
+            let result = if self.error.is_empty() {
+                Ok(self.value.clone().map_or(DBValue::None, DBValue::from))
+            } else {
+                Err(anyhow!("{}", &self.error))
+            };
+            app.executor.complete(self.id, result).await;
+            return Ok(None);
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::TaskResult(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for TaskResultCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "TaskResult {}", self.id)
+    }
+}
+
+impl TryFrom<Cmd> for TaskResultCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::TaskResult(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::command::ExecutableCommand;
+    use crate::db::dbvalue::DBValue;
+    use crate::proto::SubmitTaskCmd;
+    use crate::runtime::Runtime;
+
+    #[tokio::test]
+    async fn submit_task_test() {
+        let app = Runtime::new_with_default_config();
+        let cmd = SubmitTaskCmd {
+            task: String::from("echo"),
+            args: vec![DBValue::Int64(1).into()],
+            node: app.executor.node_id(),
+            key: String::new(),
+            all: false,
+        };
+        let result = cmd.execute(Some(&app), None).await.unwrap();
+        assert!(result == Some(DBValue::List(vec![DBValue::Int64(1)].into())));
+        let missing = SubmitTaskCmd {
+            task: String::from("missing"),
+            ..cmd
+        };
+        assert!(missing.execute(Some(&app), None).await.is_err());
+    }
+}
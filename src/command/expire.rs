@@ -0,0 +1,514 @@
+use super::{CommandType, ExecutableCommand};
+use crate::db::{database::Database, dbvalue::DBValue};
+use crate::proto::command_message::Cmd;
+use crate::proto::{
+    ActiveExpireCmd, BlockTimeoutCmd, ExpireAtCmd, ExpireCmd, PExpireCmd, PTtlCmd, PersistCmd,
+    TtlCmd,
+};
+use crate::runtime::Runtime;
+use anyhow::anyhow;
+use async_trait::async_trait;
+use std::any::Any;
+use std::fmt::Display;
+
+// 以毫秒为单位设置过期时间，key存在返回1，否则返回0
+fn expire_at_ms(db: &mut Database, key: &str, deadline_ms: i128) -> DBValue {
+    let deadline = deadline_ms.max(0) as u128;
+    DBValue::Int64(db.expire_at(key, deadline) as i64)
+}
+
+// 剩余生存时间，key不存在返回-2，没有过期时间返回-1
+fn ttl_reply(db: &Database, key: &str, unit_ms: u128) -> DBValue {
+    if !db.contains_key(key) {
+        return DBValue::Int64(-2);
+    }
+    match db.expire_time(key) {
+        Some(deadline) => {
+            let remaining = deadline.saturating_sub(db.now());
+            DBValue::Int64(((remaining + unit_ms / 2) / unit_ms) as i64)
+        }
+        None => DBValue::Int64(-1),
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for ExpireCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    fn may_grow_memory(&self) -> bool {
+        false
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let deadline = db.now() as i128 + self.seconds as i128 * 1000;
+            return Ok(Some(expire_at_ms(db, &self.key, deadline)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::Expire(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for ExpireCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Expire {} {}", &self.key, self.seconds)
+    }
+}
+
+impl TryFrom<Cmd> for ExpireCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::Expire(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for PExpireCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    fn may_grow_memory(&self) -> bool {
+        false
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let deadline = db.now() as i128 + self.milliseconds as i128;
+            return Ok(Some(expire_at_ms(db, &self.key, deadline)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::PExpire(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for PExpireCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "PExpire {} {}", &self.key, self.milliseconds)
+    }
+}
+
+impl TryFrom<Cmd> for PExpireCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::PExpire(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for ExpireAtCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    fn may_grow_memory(&self) -> bool {
+        false
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let deadline = self.timestamp as i128 * 1000;
+            return Ok(Some(expire_at_ms(db, &self.key, deadline)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::ExpireAt(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for ExpireAtCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ExpireAt {} {}", &self.key, self.timestamp)
+    }
+}
+
+impl TryFrom<Cmd> for ExpireAtCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::ExpireAt(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for TtlCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::READ
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            return Ok(Some(ttl_reply(db, &self.key, 1000)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::Ttl(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for TtlCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Ttl {}", &self.key)
+    }
+}
+
+impl TryFrom<Cmd> for TtlCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::Ttl(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for PTtlCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::READ
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            return Ok(Some(ttl_reply(db, &self.key, 1)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::PTtl(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for PTtlCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "PTtl {}", &self.key)
+    }
+}
+
+impl TryFrom<Cmd> for PTtlCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::PTtl(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for PersistCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    fn may_grow_memory(&self) -> bool {
+        false
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let persisted = db.persist(&self.key);
+            return Ok(Some(DBValue::Int64(persisted as i64)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::Persist(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for PersistCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Persist {}", &self.key)
+    }
+}
+
+impl TryFrom<Cmd> for PersistCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::Persist(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for ActiveExpireCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    fn may_grow_memory(&self) -> bool {
+        false
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            // 以提案时间戳为准删除过期key，各副本删除结果一致
+            let removed = db.active_expire(self.limit as usize);
+            return Ok(Some(DBValue::Int64(removed as i64)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::ActiveExpire(*self))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for ActiveExpireCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ActiveExpire {}", self.limit)
+    }
+}
+
+impl TryFrom<Cmd> for ActiveExpireCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::ActiveExpire(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for BlockTimeoutCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    fn may_grow_memory(&self) -> bool {
+        false
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            // 各副本在相同的日志位置移除超时的客户端，只有客户端所在的节点会收到空结果
+            let clients = db.blocked.take_indexes(&self.indexes);
+            for client in clients.iter() {
+                // 客户端已经断开时忽略
+                let _ = client.command.send(Ok(None)).await;
+            }
+            return Ok(Some(DBValue::Int64(clients.len() as i64)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::BlockTimeout(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for BlockTimeoutCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "BlockTimeout {} clients", self.indexes.len())
+    }
+}
+
+impl TryFrom<Cmd> for BlockTimeoutCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::BlockTimeout(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::ExecutableCommand;
+    use crate::db::{database::Database, dbvalue::DBValue};
+    use crate::proto::{ActiveExpireCmd, ExpireCmd, GetCmd, PersistCmd, SetCmd, TtlCmd};
+
+    fn set_cmd(key: &str, expire_ms: u64) -> SetCmd {
+        SetCmd {
+            key: String::from(key),
+            value: Some(DBValue::String(String::from("online")).into()),
+            expire_ms,
+            ephemeral: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn ttl_and_lazy_expire_test() {
+        let mut db = Database::new();
+        db.set_clock(1_000);
+        set_cmd("user:1", 0)
+            .execute(None, Some(&mut db))
+            .await
+            .unwrap();
+        let ttl = TtlCmd {
+            key: String::from("user:1"),
+        };
+        assert!(matches!(
+            ttl.execute(None, Some(&mut db)).await.unwrap(),
+            Some(DBValue::Int64(-1))
+        ));
+
+        let expire = ExpireCmd {
+            key: String::from("user:1"),
+            seconds: 10,
+        };
+        expire.execute(None, Some(&mut db)).await.unwrap();
+        db.set_clock(5_000);
+        assert!(matches!(
+            ttl.execute(None, Some(&mut db)).await.unwrap(),
+            Some(DBValue::Int64(6))
+        ));
+
+        // 到达过期时间后读取不到，写入会清除过期时间
+        db.set_clock(11_000);
+        let get = GetCmd {
+            key: String::from("user:1"),
+        };
+        assert!(get.execute(None, Some(&mut db)).await.unwrap().is_none());
+        assert!(matches!(
+            ttl.execute(None, Some(&mut db)).await.unwrap(),
+            Some(DBValue::Int64(-2))
+        ));
+
+        set_cmd("user:1", 500)
+            .execute(None, Some(&mut db))
+            .await
+            .unwrap();
+        let persist = PersistCmd {
+            key: String::from("user:1"),
+        };
+        assert!(matches!(
+            persist.execute(None, Some(&mut db)).await.unwrap(),
+            Some(DBValue::Int64(1))
+        ));
+        db.set_clock(20_000);
+        assert!(get.execute(None, Some(&mut db)).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn active_expire_test() {
+        let mut db = Database::new();
+        db.set_clock(1_000);
+        for key in ["a", "b", "c"] {
+            set_cmd(key, 100)
+                .execute(None, Some(&mut db))
+                .await
+                .unwrap();
+        }
+        set_cmd("d", 0).execute(None, Some(&mut db)).await.unwrap();
+        assert!(!db.has_expired_keys(1_050));
+        assert!(db.has_expired_keys(1_100));
+
+        db.set_clock(2_000);
+        let sweep = ActiveExpireCmd { limit: 2 };
+        let removed = sweep.execute(None, Some(&mut db)).await.unwrap();
+        assert!(matches!(removed, Some(DBValue::Int64(2))));
+        // 按key排序删除，剩余的过期key在下一轮删除
+        assert!(db.keyspace().db.contains_key("c"));
+        sweep.execute(None, Some(&mut db)).await.unwrap();
+        assert_eq!(db.dbsize(), 1);
+        assert!(db.contains_key("d"));
+    }
+}
@@ -0,0 +1,223 @@
+use super::{CommandType, ExecutableCommand};
+use crate::db::{database::Database, dbvalue::DBValue};
+use crate::proto::command_message::Cmd;
+use crate::proto::{DelCmd, ExistsCmd, TypeCmd};
+use crate::runtime::Runtime;
+use anyhow::anyhow;
+use async_trait::async_trait;
+use std::any::Any;
+use std::fmt::Display;
+
+// 通用命令，适用于任意类型的DBValue
+
+#[async_trait]
+impl ExecutableCommand for DelCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    fn may_grow_memory(&self) -> bool {
+        false
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let removed = self
+                .keys
+                .iter()
+                .filter(|key| db.remove(key).is_some())
+                .count();
+            return Ok(Some(DBValue::Int64(removed as i64)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::Del(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for DelCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Del {}", self.keys.join(" "))
+    }
+}
+
+impl TryFrom<Cmd> for DelCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::Del(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for ExistsCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::READ
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            // 与Redis一致，重复的key会被重复计数
+            let exists = self.keys.iter().filter(|key| db.contains_key(key)).count();
+            return Ok(Some(DBValue::Int64(exists as i64)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::Exists(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for ExistsCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Exists {}", self.keys.join(" "))
+    }
+}
+
+impl TryFrom<Cmd> for ExistsCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::Exists(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for TypeCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::READ
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let type_name = db.get(&self.key).map_or("none", |v| v.type_name());
+            return Ok(Some(DBValue::String(String::from(type_name))));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::Type(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for TypeCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Type {}", &self.key)
+    }
+}
+
+impl TryFrom<Cmd> for TypeCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::Type(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::command::ExecutableCommand;
+    use crate::db::sorted_set::SortedSet;
+    use crate::db::{database::Database, dbvalue::DBValue};
+    use crate::proto::{DelCmd, ExistsCmd, TypeCmd};
+
+    fn keys(keys: &[&str]) -> Vec<String> {
+        keys.iter().map(|key| String::from(*key)).collect()
+    }
+
+    #[tokio::test]
+    async fn del_and_exists_test() {
+        let mut db = Database::new();
+        db.set(String::from("a"), DBValue::String(String::from("1")));
+        db.set(String::from("b"), DBValue::List(Default::default()));
+        db.set(String::from("c"), DBValue::Int64(3));
+
+        // 重复的key重复计数，不存在的key不计数
+        let exists = ExistsCmd {
+            keys: keys(&["a", "a", "b", "missing"]),
+        };
+        let result = exists.execute(None, Some(&mut db)).await.unwrap();
+        assert!(result == Some(DBValue::Int64(3)));
+
+        // 返回实际删除的key数量
+        let del = DelCmd {
+            keys: keys(&["a", "b", "a", "missing"]),
+        };
+        let result = del.execute(None, Some(&mut db)).await.unwrap();
+        assert!(result == Some(DBValue::Int64(2)));
+        assert!(!db.contains_key("a"));
+        assert!(!db.contains_key("b"));
+        assert!(db.contains_key("c"));
+
+        let result = exists.execute(None, Some(&mut db)).await.unwrap();
+        assert!(result == Some(DBValue::Int64(0)));
+    }
+
+    #[tokio::test]
+    async fn type_test() {
+        let mut db = Database::new();
+        let values = [
+            ("string", DBValue::String(String::from("v"))),
+            ("bytes", DBValue::Bytes(vec![1])),
+            ("list", DBValue::List(Default::default())),
+            ("hash", DBValue::Hash(Default::default())),
+            ("set", DBValue::Set(Default::default())),
+            ("zset", DBValue::SortedSet(SortedSet::new())),
+            ("int64", DBValue::Int64(1)),
+            ("float64", DBValue::Float64(1.5)),
+        ];
+        for (name, value) in values {
+            db.set(String::from(name), value);
+            let type_cmd = TypeCmd {
+                key: String::from(name),
+            };
+            let result = type_cmd.execute(None, Some(&mut db)).await.unwrap();
+            assert!(result == Some(DBValue::String(String::from(name))));
+        }
+        let missing = TypeCmd {
+            key: String::from("missing"),
+        };
+        let result = missing.execute(None, Some(&mut db)).await.unwrap();
+        assert!(result == Some(DBValue::String(String::from("none"))));
+    }
+}
@@ -0,0 +1,319 @@
+use super::{CommandType, ExecutableCommand};
+use crate::db::{database::Database, dbvalue::DBValue};
+use crate::proto::command_message::Cmd;
+use crate::proto::{HashExpireCmd, HashPersistCmd, HashTtlCmd};
+use crate::runtime::Runtime;
+use anyhow::anyhow;
+use async_trait::async_trait;
+use std::any::Any;
+use std::collections::VecDeque;
+use std::fmt::Display;
+
+// hash成员不存在
+const NO_MEMBER: i64 = -2;
+// hash成员没有设置过期时间
+const NO_EXPIRE: i64 = -1;
+
+// 成员是否存在(未过期)，key不存在时返回None
+fn members_exist(
+    db: &Database,
+    key: &str,
+    members: &[String],
+) -> anyhow::Result<Option<Vec<bool>>> {
+    match db.get(key) {
+        Some(DBValue::Hash(hash)) => Ok(Some(
+            members
+                .iter()
+                .map(|m| hash.contains_key(m) && !db.is_field_expired(key, m))
+                .collect(),
+        )),
+        Some(value) => Err(anyhow!(
+            "Mismatch DBValue type, required Hash but got {}",
+            value
+        )),
+        None => Ok(None),
+    }
+}
+
+fn int_list(values: Vec<i64>) -> DBValue {
+    DBValue::List(
+        values
+            .into_iter()
+            .map(DBValue::Int64)
+            .collect::<VecDeque<_>>(),
+    )
+}
+
+#[async_trait]
+impl ExecutableCommand for HashExpireCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    fn may_grow_memory(&self) -> bool {
+        false
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let Some(exists) = members_exist(db, &self.key, &self.member_keys)? else {
+                return Ok(Some(int_list(vec![NO_MEMBER; self.member_keys.len()])));
+            };
+            let deadline = (db.now() as i128 + self.seconds as i128 * 1000).max(0) as u128;
+            let mut replies = Vec::with_capacity(self.member_keys.len());
+            for (member, exist) in self.member_keys.iter().zip(exists) {
+                if !exist {
+                    replies.push(NO_MEMBER);
+                } else if deadline <= db.now() {
+                    // 过期时间已过，直接删除成员
+                    db.set_field_expire(&self.key, member, deadline);
+                    db.expire_fields_if_needed(&self.key);
+                    replies.push(2);
+                } else {
+                    db.set_field_expire(&self.key, member, deadline);
+                    replies.push(1);
+                }
+            }
+            return Ok(Some(int_list(replies)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::HashExpire(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for HashExpireCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "HashExpire {} {} [{}]",
+            &self.key,
+            self.seconds,
+            self.member_keys.join(",")
+        )
+    }
+}
+
+impl TryFrom<Cmd> for HashExpireCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::HashExpire(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for HashTtlCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::READ
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let Some(exists) = members_exist(db, &self.key, &self.member_keys)? else {
+                return Ok(Some(int_list(vec![NO_MEMBER; self.member_keys.len()])));
+            };
+            let now = db.now();
+            let replies = self
+                .member_keys
+                .iter()
+                .zip(exists)
+                .map(|(member, exist)| {
+                    if !exist {
+                        return NO_MEMBER;
+                    }
+                    match db.field_expire_time(&self.key, member) {
+                        Some(deadline) => ((deadline.saturating_sub(now) + 500) / 1000) as i64,
+                        None => NO_EXPIRE,
+                    }
+                })
+                .collect();
+            return Ok(Some(int_list(replies)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::HashTtl(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for HashTtlCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "HashTtl {} [{}]", &self.key, self.member_keys.join(","))
+    }
+}
+
+impl TryFrom<Cmd> for HashTtlCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::HashTtl(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for HashPersistCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    fn may_grow_memory(&self) -> bool {
+        false
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let Some(exists) = members_exist(db, &self.key, &self.member_keys)? else {
+                return Ok(Some(int_list(vec![NO_MEMBER; self.member_keys.len()])));
+            };
+            let mut replies = Vec::with_capacity(self.member_keys.len());
+            for (member, exist) in self.member_keys.iter().zip(exists) {
+                if !exist {
+                    replies.push(NO_MEMBER);
+                } else if db.persist_field(&self.key, member) {
+                    replies.push(1);
+                } else {
+                    replies.push(NO_EXPIRE);
+                }
+            }
+            return Ok(Some(int_list(replies)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::HashPersist(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for HashPersistCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "HashPersist {} [{}]",
+            &self.key,
+            self.member_keys.join(",")
+        )
+    }
+}
+
+impl TryFrom<Cmd> for HashPersistCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::HashPersist(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::ExecutableCommand;
+    use crate::db::{database::Database, dbvalue::DBValue};
+    use crate::proto::{HashExpireCmd, HashGetCmd, HashPutCmd, HashTtlCmd};
+
+    async fn put(db: &mut Database, member: &str) {
+        let put = HashPutCmd {
+            key: String::from("presence"),
+            member_key: String::from(member),
+            member_value: Some(DBValue::Boolean(true).into()),
+            ephemeral: false,
+        };
+        put.execute(None, Some(db)).await.unwrap();
+    }
+
+    fn members(members: &[&str]) -> Vec<String> {
+        members.iter().map(|m| String::from(*m)).collect()
+    }
+
+    #[tokio::test]
+    async fn hash_member_expire_test() {
+        let mut db = Database::new();
+        db.set_clock(1_000);
+        put(&mut db, "alice").await;
+        put(&mut db, "bob").await;
+        let expire = HashExpireCmd {
+            key: String::from("presence"),
+            seconds: 5,
+            member_keys: members(&["alice", "carol"]),
+        };
+        let reply = expire.execute(None, Some(&mut db)).await.unwrap();
+        assert!(
+            reply
+                == Some(DBValue::List(
+                    vec![DBValue::Int64(1), DBValue::Int64(-2)].into()
+                ))
+        );
+
+        let ttl = HashTtlCmd {
+            key: String::from("presence"),
+            member_keys: members(&["alice", "bob"]),
+        };
+        let reply = ttl.execute(None, Some(&mut db)).await.unwrap();
+        assert!(
+            reply
+                == Some(DBValue::List(
+                    vec![DBValue::Int64(5), DBValue::Int64(-1)].into()
+                ))
+        );
+
+        // 过期后读取不到，主动过期删除成员
+        db.set_clock(6_000);
+        let get = HashGetCmd {
+            key: String::from("presence"),
+            member_key: String::from("alice"),
+        };
+        assert!(get.execute(None, Some(&mut db)).await.unwrap().is_none());
+        assert!(db.has_expired_keys(6_000));
+        assert_eq!(db.active_expire(20), 1);
+        assert!(!db.has_expired_keys(6_000));
+
+        // 最后一个成员过期后删除hash本身
+        let expire = HashExpireCmd {
+            key: String::from("presence"),
+            seconds: 0,
+            member_keys: members(&["bob"]),
+        };
+        let reply = expire.execute(None, Some(&mut db)).await.unwrap();
+        assert!(reply == Some(DBValue::List(vec![DBValue::Int64(2)].into())));
+        assert!(!db.contains_key("presence"));
+    }
+}
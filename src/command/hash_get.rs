@@ -26,6 +26,9 @@ impl ExecutableCommand for HashGetCmd {
         db: Option<&mut Database>,
     ) -> anyhow::Result<Option<DBValue>> {
         if let Some(db) = db {
+            if db.is_field_expired(&self.key, &self.member_key) {
+                return Ok(None);
+            }
             if let Some(value) = db.get(&self.key) {
                 return match value {
                     DBValue::Hash(hash) => Ok(hash.get(&self.member_key).map(|x| x.clone())),
@@ -31,7 +31,10 @@ impl ExecutableCommand for HashPutCmd {
     ) -> anyhow::Result<Option<DBValue>> {
         if let Some(db) = db {
             if let Some(member_value) = &self.member_value {
-                return match db.get_mut(&self.key) {
+                if self.ephemeral {
+                    db.require_session()?;
+                }
+                let result = match db.get_mut(&self.key) {
                     Some(value) => match value {
                         DBValue::Hash(ref mut hash) => {
                             let old = hash.insert(self.member_key.clone(), member_value.clone().into());
@@ -46,6 +49,12 @@ impl ExecutableCommand for HashPutCmd {
                         Ok(None)
                     }
                 };
+                // 重新写入的成员不再过期，所属会话以最后一次写入为准
+                db.persist_field(&self.key, &self.member_key);
+                if result.is_ok() {
+                    db.mark_ephemeral_field(&self.key, &self.member_key, self.ephemeral);
+                }
+                return result;
             }
         }
         Ok(None)
@@ -0,0 +1,269 @@
+use super::{CommandType, ExecutableCommand};
+use crate::db::hyperloglog::HyperLogLog;
+use crate::db::{database::Database, dbvalue::DBValue};
+use crate::proto::command_message::Cmd;
+use crate::proto::{PfAddCmd, PfCountCmd, PfMergeCmd};
+use crate::runtime::Runtime;
+use anyhow::anyhow;
+use async_trait::async_trait;
+use std::any::Any;
+use std::fmt::Display;
+
+fn get_hll<'a>(db: &'a Database, key: &str) -> anyhow::Result<Option<&'a HyperLogLog>> {
+    match db.get(key) {
+        Some(DBValue::HyperLogLog(hll)) => Ok(Some(hll)),
+        Some(value) => Err(anyhow!(
+            "Mismatch DBValue type, required HyperLogLog but got {}",
+            value
+        )),
+        None => Ok(None),
+    }
+}
+
+fn get_hll_mut<'a>(db: &'a mut Database, key: &str) -> anyhow::Result<Option<&'a mut HyperLogLog>> {
+    match db.get_mut(key) {
+        Some(DBValue::HyperLogLog(hll)) => Ok(Some(hll)),
+        Some(value) => Err(anyhow!(
+            "Mismatch DBValue type, required HyperLogLog but got {}",
+            value
+        )),
+        None => Ok(None),
+    }
+}
+
+// 合并多个key，不存在的key被忽略
+fn union(db: &Database, keys: &[String]) -> anyhow::Result<HyperLogLog> {
+    let mut result = HyperLogLog::new();
+    for key in keys {
+        if let Some(hll) = get_hll(db, key)? {
+            result.merge(hll);
+        }
+    }
+    Ok(result)
+}
+
+#[async_trait]
+impl ExecutableCommand for PfAddCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            // key不存在时即使没有元素也创建
+            let changed = match get_hll_mut(db, &self.key)? {
+                Some(hll) => {
+                    let mut changed = false;
+                    for element in self.elements.iter() {
+                        changed |= hll.add(element.as_bytes());
+                    }
+                    changed
+                }
+                None => {
+                    let mut hll = HyperLogLog::new();
+                    for element in self.elements.iter() {
+                        hll.add(element.as_bytes());
+                    }
+                    db.set(self.key.clone(), DBValue::HyperLogLog(hll));
+                    true
+                }
+            };
+            return Ok(Some(DBValue::Int64(changed as i64)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::PfAdd(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for PfAddCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "PfAdd {} ({} elements)", &self.key, self.elements.len())
+    }
+}
+
+impl TryFrom<Cmd> for PfAddCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::PfAdd(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for PfCountCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::READ
+    }
+
+    fn may_grow_memory(&self) -> bool {
+        false
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let count = match self.keys.as_slice() {
+                [key] => get_hll(db, key)?.map_or(0, |hll| hll.count()),
+                keys => union(db, keys)?.count(),
+            };
+            return Ok(Some(DBValue::Int64(count as i64)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::PfCount(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for PfCountCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "PfCount {}", self.keys.join(" "))
+    }
+}
+
+impl TryFrom<Cmd> for PfCountCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::PfCount(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for PfMergeCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let merged = union(db, &self.sources)?;
+            match get_hll_mut(db, &self.destination)? {
+                Some(hll) => hll.merge(&merged),
+                None => {
+                    db.set(self.destination.clone(), DBValue::HyperLogLog(merged));
+                }
+            }
+            return Ok(Some(DBValue::Boolean(true)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::PfMerge(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for PfMergeCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "PfMerge {} {}",
+            &self.destination,
+            self.sources.join(" ")
+        )
+    }
+}
+
+impl TryFrom<Cmd> for PfMergeCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::PfMerge(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::command::ExecutableCommand;
+    use crate::db::database::Database;
+    use crate::db::dbvalue::DBValue;
+    use crate::proto::{PfAddCmd, PfCountCmd, PfMergeCmd};
+
+    fn pfadd(key: &str, elements: &[&str]) -> PfAddCmd {
+        PfAddCmd {
+            key: String::from(key),
+            elements: elements.iter().map(|e| String::from(*e)).collect(),
+        }
+    }
+
+    #[tokio::test]
+    async fn pf_test() {
+        let mut db = Database::new();
+        let result = pfadd("day:1", &["a", "b", "c"])
+            .execute(None, Some(&mut db))
+            .await;
+        assert!(result.unwrap() == Some(DBValue::Int64(1)));
+        let result = pfadd("day:1", &["a"]).execute(None, Some(&mut db)).await;
+        assert!(result.unwrap() == Some(DBValue::Int64(0)));
+        pfadd("day:2", &["c", "d"])
+            .execute(None, Some(&mut db))
+            .await
+            .unwrap();
+
+        let count = PfCountCmd {
+            keys: vec![String::from("day:1"), String::from("day:2")],
+        };
+        let result = count.execute(None, Some(&mut db)).await.unwrap();
+        assert!(result == Some(DBValue::Int64(4)));
+
+        let merge = PfMergeCmd {
+            destination: String::from("week"),
+            sources: vec![String::from("day:1"), String::from("day:2")],
+        };
+        merge.execute(None, Some(&mut db)).await.unwrap();
+        let week = PfCountCmd {
+            keys: vec![String::from("week")],
+        };
+        let result = week.execute(None, Some(&mut db)).await.unwrap();
+        assert!(result == Some(DBValue::Int64(4)));
+
+        // 经过protobuf编码后保持一致
+        let value = db.get("week").unwrap().clone();
+        assert!(DBValue::from(value.to_protobuf()) == value);
+        db.set(String::from("name"), DBValue::String(String::from("x")));
+        assert!(pfadd("name", &["a"])
+            .execute(None, Some(&mut db))
+            .await
+            .is_err());
+    }
+}
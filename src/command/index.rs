@@ -0,0 +1,326 @@
+use super::{CommandType, ExecutableCommand};
+use crate::db::index::{IndexDef, IndexValue};
+use crate::db::{database::Database, dbvalue::DBValue};
+use crate::proto::command_message::Cmd;
+use crate::proto::{IndexCreateCmd, IndexDropCmd, IndexMatchCmd, IndexRangeCmd};
+use crate::runtime::Runtime;
+use anyhow::anyhow;
+use async_trait::async_trait;
+use std::any::Any;
+use std::fmt::Display;
+use std::ops::Bound;
+
+fn index_value(value: &crate::proto::DbValue) -> anyhow::Result<IndexValue> {
+    let value = DBValue::from(value.clone());
+    IndexValue::from_value(&value)
+        .ok_or_else(|| anyhow!("Only numbers and strings can be indexed, got {}", value))
+}
+
+fn bound(
+    value: &Option<crate::proto::DbValue>,
+    exclusive: bool,
+) -> anyhow::Result<Bound<IndexValue>> {
+    Ok(match value {
+        None => Bound::Unbounded,
+        Some(value) if exclusive => Bound::Excluded(index_value(value)?),
+        Some(value) => Bound::Included(index_value(value)?),
+    })
+}
+
+fn keys_reply(keys: Vec<String>) -> Option<DBValue> {
+    Some(DBValue::List(
+        keys.into_iter().map(DBValue::String).collect(),
+    ))
+}
+
+#[async_trait]
+impl ExecutableCommand for IndexCreateCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            if self.name.is_empty() || self.field.is_empty() {
+                return Err(anyhow!("Index name and field must not be empty"));
+            }
+            let def = IndexDef {
+                prefix: self.prefix.clone(),
+                field: self.field.clone(),
+            };
+            let indexed = db.create_index(&self.name, def)?;
+            return Ok(Some(DBValue::Int64(indexed as i64)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::IndexCreate(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for IndexCreateCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "IndexCreate {} prefix={} field={}",
+            &self.name, &self.prefix, &self.field
+        )
+    }
+}
+
+impl TryFrom<Cmd> for IndexCreateCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::IndexCreate(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for IndexDropCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    fn may_grow_memory(&self) -> bool {
+        false
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            return Ok(Some(DBValue::Boolean(db.drop_index(&self.name))));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::IndexDrop(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for IndexDropCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "IndexDrop {}", &self.name)
+    }
+}
+
+impl TryFrom<Cmd> for IndexDropCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::IndexDrop(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for IndexMatchCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::READ
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let value = self
+                .value
+                .as_ref()
+                .ok_or_else(|| anyhow!("IndexMatch requires a value"))?;
+            let value = index_value(value)?;
+            let keys = db.query_index(
+                &self.name,
+                Bound::Included(value.clone()),
+                Bound::Included(value),
+                false,
+                self.limit as usize,
+            )?;
+            return Ok(keys_reply(keys));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::IndexMatch(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for IndexMatchCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "IndexMatch {} limit={}", &self.name, self.limit)
+    }
+}
+
+impl TryFrom<Cmd> for IndexMatchCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::IndexMatch(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for IndexRangeCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::READ
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let min = bound(&self.min, self.min_exclusive)?;
+            let max = bound(&self.max, self.max_exclusive)?;
+            let keys = db.query_index(&self.name, min, max, self.rev, self.limit as usize)?;
+            return Ok(keys_reply(keys));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::IndexRange(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for IndexRangeCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "IndexRange {} rev={} limit={}",
+            &self.name, self.rev, self.limit
+        )
+    }
+}
+
+impl TryFrom<Cmd> for IndexRangeCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::IndexRange(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::command::ExecutableCommand;
+    use crate::db::database::Database;
+    use crate::db::dbvalue::DBValue;
+    use crate::proto::{HashPutCmd, IndexCreateCmd, IndexMatchCmd, IndexRangeCmd};
+
+    async fn put(db: &mut Database, key: &str, member: &str, value: DBValue) {
+        let put = HashPutCmd {
+            key: String::from(key),
+            member_key: String::from(member),
+            member_value: Some(value.into()),
+            ephemeral: false,
+        };
+        put.execute(None, Some(db)).await.unwrap();
+    }
+
+    fn keys(value: Option<DBValue>) -> Vec<String> {
+        match value {
+            Some(DBValue::List(list)) => list
+                .into_iter()
+                .map(|key| match key {
+                    DBValue::String(key) => key,
+                    _ => panic!("index query should reply keys"),
+                })
+                .collect(),
+            _ => panic!("index query should reply list"),
+        }
+    }
+
+    #[tokio::test]
+    async fn index_query_test() {
+        let mut db = Database::new();
+        let eu = || DBValue::String(String::from("eu"));
+        put(&mut db, "user:1", "region", eu()).await;
+        put(&mut db, "order:1", "region", eu()).await;
+
+        // 创建索引时根据已有数据生成索引
+        let create = IndexCreateCmd {
+            name: String::from("by_region"),
+            prefix: String::from("user:"),
+            field: String::from("region"),
+        };
+        let indexed = create.execute(None, Some(&mut db)).await.unwrap();
+        assert!(indexed == Some(DBValue::Int64(1)));
+        assert!(create.execute(None, Some(&mut db)).await.is_err());
+        let create = IndexCreateCmd {
+            name: String::from("by_age"),
+            field: String::from("age"),
+            ..create
+        };
+        create.execute(None, Some(&mut db)).await.unwrap();
+
+        put(&mut db, "user:2", "region", eu()).await;
+        put(
+            &mut db,
+            "user:1",
+            "region",
+            DBValue::String(String::from("us")),
+        )
+        .await;
+        put(&mut db, "user:1", "age", DBValue::Int64(30)).await;
+        put(&mut db, "user:2", "age", DBValue::Int64(20)).await;
+        db.remove("user:2");
+
+        let eq = IndexMatchCmd {
+            name: String::from("by_region"),
+            value: Some(eu().into()),
+            limit: 0,
+        };
+        assert!(keys(eq.execute(None, Some(&mut db)).await.unwrap()).is_empty());
+        let range = IndexRangeCmd {
+            name: String::from("by_age"),
+            min: Some(DBValue::Int64(25).into()),
+            ..Default::default()
+        };
+        let found = keys(range.execute(None, Some(&mut db)).await.unwrap());
+        assert_eq!(found, vec![String::from("user:1")]);
+    }
+}
@@ -0,0 +1,398 @@
+use super::{CommandType, ExecutableCommand};
+use crate::db::json::{self, Segment};
+use crate::db::{database::Database, dbvalue::DBValue};
+use crate::proto::command_message::Cmd;
+use crate::proto::{JsonArrAppendCmd, JsonDelCmd, JsonGetCmd, JsonNumIncrByCmd, JsonSetCmd};
+use crate::runtime::Runtime;
+use anyhow::anyhow;
+use async_trait::async_trait;
+use serde_json::{Map, Value};
+use std::any::Any;
+use std::fmt::Display;
+
+// 路径为空时表示根路径
+fn parse_path(path: &str) -> anyhow::Result<Vec<Segment>> {
+    json::parse_path(if path.is_empty() { "$" } else { path })
+}
+
+fn get_document_mut<'a>(db: &'a mut Database, key: &str) -> anyhow::Result<&'a mut DBValue> {
+    db.get_mut(key)
+        .ok_or_else(|| anyhow!("No such key {}", key))
+}
+
+// 回复JSON文本
+fn json_reply(value: Value) -> anyhow::Result<Option<DBValue>> {
+    Ok(Some(DBValue::String(serde_json::to_string(&value)?)))
+}
+
+#[async_trait]
+impl ExecutableCommand for JsonGetCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::READ
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let Some(doc) = db.get(&self.key) else {
+                return Ok(None);
+            };
+            // 单个路径回复匹配值的数组，多个路径回复以路径为字段的对象
+            let reply = match self.paths.as_slice() {
+                [] => json::to_json_array(json::select(doc, &[]).into_iter())?,
+                [path] => json::to_json_array(json::select(doc, &parse_path(path)?).into_iter())?,
+                paths => {
+                    let mut object = Map::new();
+                    for path in paths {
+                        let values = json::select(doc, &parse_path(path)?);
+                        object.insert(path.clone(), json::to_json_array(values.into_iter())?);
+                    }
+                    Value::Object(object)
+                }
+            };
+            return json_reply(reply);
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::JsonGet(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for JsonGetCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "JsonGet {} [{}]", &self.key, self.paths.join(","))
+    }
+}
+
+impl TryFrom<Cmd> for JsonGetCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::JsonGet(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for JsonSetCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            if self.nx && self.xx {
+                return Err(anyhow!(
+                    "NX and XX options at the same time are not compatible"
+                ));
+            }
+            let path = parse_path(&self.path)?;
+            let value = json::parse_json(&self.value)?;
+            let exists = db.get(&self.key).is_some();
+            // 根路径整体写入key，新的key只能在根路径创建
+            if path.is_empty() {
+                if (exists && self.nx) || (!exists && self.xx) {
+                    return Ok(Some(DBValue::Boolean(false)));
+                }
+                db.set(self.key.clone(), value);
+                return Ok(Some(DBValue::Boolean(true)));
+            }
+            if !exists {
+                return Err(anyhow!("New documents must be created at the root path"));
+            }
+            let doc = get_document_mut(db, &self.key)?;
+            let count = json::set(doc, &path, &value, self.nx, self.xx)?;
+            return Ok(Some(DBValue::Boolean(count > 0)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::JsonSet(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for JsonSetCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "JsonSet {} {} nx={} xx={}",
+            &self.key, &self.path, self.nx, self.xx
+        )
+    }
+}
+
+impl TryFrom<Cmd> for JsonSetCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::JsonSet(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for JsonDelCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    fn may_grow_memory(&self) -> bool {
+        false
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let path = parse_path(&self.path)?;
+            if db.get(&self.key).is_none() {
+                return Ok(Some(DBValue::Int64(0)));
+            }
+            if path.is_empty() {
+                db.remove(&self.key);
+                return Ok(Some(DBValue::Int64(1)));
+            }
+            let doc = get_document_mut(db, &self.key)?;
+            let count = json::delete(doc, &path)?;
+            return Ok(Some(DBValue::Int64(count as i64)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::JsonDel(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for JsonDelCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "JsonDel {} {}", &self.key, &self.path)
+    }
+}
+
+impl TryFrom<Cmd> for JsonDelCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::JsonDel(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for JsonArrAppendCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            if self.values.is_empty() {
+                return Err(anyhow!("JSON.ARRAPPEND requires at least one value"));
+            }
+            let path = parse_path(&self.path)?;
+            let values = self
+                .values
+                .iter()
+                .map(|value| json::parse_json(value))
+                .collect::<anyhow::Result<Vec<DBValue>>>()?;
+            let doc = get_document_mut(db, &self.key)?;
+            // 每个匹配值回复追加后的长度，匹配值不是数组时回复None
+            let lens = json::arr_append(doc, &path, &values)?
+                .into_iter()
+                .map(|len| len.map_or(DBValue::None, |len| DBValue::Int64(len as i64)))
+                .collect();
+            return Ok(Some(DBValue::List(lens)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::JsonArrAppend(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for JsonArrAppendCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "JsonArrAppend {} {} ({} values)",
+            &self.key,
+            &self.path,
+            self.values.len()
+        )
+    }
+}
+
+impl TryFrom<Cmd> for JsonArrAppendCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::JsonArrAppend(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for JsonNumIncrByCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    fn may_grow_memory(&self) -> bool {
+        false
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let path = parse_path(&self.path)?;
+            let delta = match json::parse_json(&self.value)? {
+                delta @ (DBValue::Int64(_) | DBValue::Float64(_)) => delta,
+                _ => return Err(anyhow!("JSON.NUMINCRBY requires a number")),
+            };
+            let doc = get_document_mut(db, &self.key)?;
+            // 回复增加后的值组成的数组，匹配值不是数值时为null
+            let results: Vec<DBValue> = json::num_incr_by(doc, &path, &delta)?
+                .into_iter()
+                .map(|value| value.unwrap_or(DBValue::None))
+                .collect();
+            return json_reply(json::to_json_array(results.iter())?);
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::JsonNumIncrBy(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for JsonNumIncrByCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "JsonNumIncrBy {} {} {}",
+            &self.key, &self.path, &self.value
+        )
+    }
+}
+
+impl TryFrom<Cmd> for JsonNumIncrByCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::JsonNumIncrBy(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::command::ExecutableCommand;
+    use crate::db::database::Database;
+    use crate::db::dbvalue::DBValue;
+    use crate::proto::{JsonGetCmd, JsonNumIncrByCmd, JsonSetCmd};
+
+    fn json_set(path: &str, value: &str) -> JsonSetCmd {
+        JsonSetCmd {
+            key: String::from("user:1"),
+            path: String::from(path),
+            value: String::from(value),
+            ..Default::default()
+        }
+    }
+
+    fn text(value: Option<DBValue>) -> String {
+        match value {
+            Some(DBValue::String(text)) => text,
+            _ => panic!("json command should reply JSON text"),
+        }
+    }
+
+    #[tokio::test]
+    async fn nested_document_test() {
+        let mut db = Database::new();
+        // 新的key只能在根路径创建
+        let cmd = json_set("$.name", r#""alice""#);
+        assert!(cmd.execute(None, Some(&mut db)).await.is_err());
+        let doc = r#"{"name":"alice","address":{"city":"paris","zip":75001}}"#;
+        json_set("$", doc)
+            .execute(None, Some(&mut db))
+            .await
+            .unwrap();
+        let cmd = json_set("$.address.city", r#""lyon""#);
+        cmd.execute(None, Some(&mut db)).await.unwrap();
+
+        let incr = JsonNumIncrByCmd {
+            key: String::from("user:1"),
+            path: String::from("$.address.zip"),
+            value: String::from("-6000"),
+        };
+        assert_eq!(
+            text(incr.execute(None, Some(&mut db)).await.unwrap()),
+            "[69001]"
+        );
+        let get = JsonGetCmd {
+            key: String::from("user:1"),
+            paths: vec![String::from("$.address")],
+        };
+        assert_eq!(
+            text(get.execute(None, Some(&mut db)).await.unwrap()),
+            r#"[{"city":"lyon","zip":69001}]"#
+        );
+    }
+}
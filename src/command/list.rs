@@ -0,0 +1,916 @@
+use super::{BlockedError, CommandType, ExecutableCommand};
+use crate::db::{database::Database, dbvalue::DBValue};
+use crate::proto::command_message::Cmd;
+use crate::proto::{
+    BlPopCmd, BrPopCmd, LIndexCmd, LLenCmd, LPopCmd, LPushCmd, LRangeCmd, LRemCmd, LSetCmd,
+    LTrimCmd, RPopCmd, RPushCmd,
+};
+use crate::runtime::Runtime;
+use crate::until;
+use anyhow::anyhow;
+use async_trait::async_trait;
+use std::any::Any;
+use std::collections::VecDeque;
+use std::fmt::Display;
+
+fn get_list<'a>(db: &'a Database, key: &str) -> anyhow::Result<Option<&'a VecDeque<DBValue>>> {
+    match db.get(key) {
+        Some(DBValue::List(list)) => Ok(Some(list)),
+        Some(value) => Err(anyhow!(
+            "Mismatch DBValue type, required List but got {}",
+            value
+        )),
+        None => Ok(None),
+    }
+}
+
+fn get_list_mut<'a>(
+    db: &'a mut Database,
+    key: &str,
+) -> anyhow::Result<Option<&'a mut VecDeque<DBValue>>> {
+    match db.get_mut(key) {
+        Some(DBValue::List(list)) => Ok(Some(list)),
+        Some(value) => Err(anyhow!(
+            "Mismatch DBValue type, required List but got {}",
+            value
+        )),
+        None => Ok(None),
+    }
+}
+
+// 列表为空时删除key
+fn remove_if_empty(db: &mut Database, key: &str) {
+    if let Some(DBValue::List(list)) = db.get(key) {
+        if list.is_empty() {
+            db.remove(key);
+        }
+    }
+}
+
+// 根据Redis的下标语义计算列表下标
+fn list_index(index: i64, len: usize) -> Option<usize> {
+    let index = if index < 0 { len as i64 + index } else { index };
+    if index < 0 || index >= len as i64 {
+        None
+    } else {
+        Some(index as usize)
+    }
+}
+
+fn push(db: &mut Database, key: &str, values: &[crate::proto::DbValue], left: bool) -> anyhow::Result<usize> {
+    // 空的push不创建空列表
+    if values.is_empty() {
+        return Err(anyhow!("Push requires at least one element"));
+    }
+    let len = match get_list_mut(db, key)? {
+        Some(list) => {
+            for value in values {
+                if left {
+                    list.push_front(value.clone().into());
+                } else {
+                    list.push_back(value.clone().into());
+                }
+            }
+            list.len()
+        }
+        None => {
+            let mut list = VecDeque::with_capacity(values.len());
+            for value in values {
+                if left {
+                    list.push_front(value.clone().into());
+                } else {
+                    list.push_back(value.clone().into());
+                }
+            }
+            let len = list.len();
+            db.set(String::from(key), DBValue::List(list));
+            len
+        }
+    };
+    db.signal_ready(key);
+    Ok(len)
+}
+
+fn pop(db: &mut Database, key: &str, count: u64, left: bool) -> anyhow::Result<Option<DBValue>> {
+    let result = match get_list_mut(db, key)? {
+        Some(list) => {
+            let mut pop_one = || {
+                if left {
+                    list.pop_front()
+                } else {
+                    list.pop_back()
+                }
+            };
+            if count == 0 {
+                pop_one()
+            } else {
+                let mut values = VecDeque::new();
+                for _ in 0..count {
+                    match pop_one() {
+                        Some(value) => values.push_back(value),
+                        None => break,
+                    }
+                }
+                Some(DBValue::List(values))
+            }
+        }
+        None => None,
+    };
+    remove_if_empty(db, key);
+    Ok(result)
+}
+
+// 依次检查key，从第一个非空列表中弹出元素，返回[key, value]
+fn blocking_pop(
+    db: &mut Database,
+    keys: &[String],
+    timeout_ms: u64,
+    left: bool,
+) -> anyhow::Result<Option<DBValue>> {
+    for key in keys {
+        if let Some(value) = pop(db, key, 0, left)? {
+            return Ok(Some(DBValue::List(VecDeque::from(vec![
+                DBValue::String(key.clone()),
+                value,
+            ]))));
+        }
+    }
+    Err(BlockedError {
+        keys: keys.to_vec(),
+        timeout_ms,
+        retry: None,
+    }
+    .into())
+}
+
+#[async_trait]
+impl ExecutableCommand for LPushCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let len = push(db, &self.key, &self.values, true)?;
+            return Ok(Some(DBValue::Int64(len as i64)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::LPush(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for LPushCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "LPush {} ({} values)", &self.key, self.values.len())
+    }
+}
+
+impl TryFrom<Cmd> for LPushCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::LPush(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for RPushCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let len = push(db, &self.key, &self.values, false)?;
+            return Ok(Some(DBValue::Int64(len as i64)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::RPush(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for RPushCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "RPush {} ({} values)", &self.key, self.values.len())
+    }
+}
+
+impl TryFrom<Cmd> for RPushCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::RPush(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for LPopCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    fn may_grow_memory(&self) -> bool {
+        false
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            return pop(db, &self.key, self.count, true);
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::LPop(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for LPopCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "LPop {} {}", &self.key, self.count)
+    }
+}
+
+impl TryFrom<Cmd> for LPopCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::LPop(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for RPopCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    fn may_grow_memory(&self) -> bool {
+        false
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            return pop(db, &self.key, self.count, false);
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::RPop(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for RPopCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "RPop {} {}", &self.key, self.count)
+    }
+}
+
+impl TryFrom<Cmd> for RPopCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::RPop(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for LRangeCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::READ
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let mut values = VecDeque::new();
+            if let Some(list) = get_list(db, &self.key)? {
+                if let Some((start, end)) = until::normalize_range(self.start, self.end, list.len())
+                {
+                    values.extend(list.range(start..=end).cloned());
+                }
+            }
+            return Ok(Some(DBValue::List(values)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::LRange(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for LRangeCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "LRange {} {} {}", &self.key, self.start, self.end)
+    }
+}
+
+impl TryFrom<Cmd> for LRangeCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::LRange(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for LLenCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::READ
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let len = get_list(db, &self.key)?.map_or(0, |list| list.len());
+            return Ok(Some(DBValue::Int64(len as i64)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::LLen(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for LLenCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "LLen {}", &self.key)
+    }
+}
+
+impl TryFrom<Cmd> for LLenCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::LLen(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for LIndexCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::READ
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            if let Some(list) = get_list(db, &self.key)? {
+                return Ok(list_index(self.index, list.len()).map(|i| list[i].clone()));
+            }
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::LIndex(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for LIndexCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "LIndex {} {}", &self.key, self.index)
+    }
+}
+
+impl TryFrom<Cmd> for LIndexCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::LIndex(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for LSetCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let value: DBValue = self.value.clone().map_or(DBValue::None, |v| v.into());
+            return match get_list_mut(db, &self.key)? {
+                Some(list) => match list_index(self.index, list.len()) {
+                    Some(i) => Ok(Some(std::mem::replace(&mut list[i], value))),
+                    None => Err(anyhow!("Index {} out of range", self.index)),
+                },
+                None => Err(anyhow!("No such key {}", &self.key)),
+            };
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::LSet(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for LSetCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(value) = &self.value {
+            write!(f, "LSet {} {} {}", &self.key, self.index, value)
+        } else {
+            write!(f, "LSet {} {} None", &self.key, self.index)
+        }
+    }
+}
+
+impl TryFrom<Cmd> for LSetCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::LSet(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for LTrimCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    fn may_grow_memory(&self) -> bool {
+        false
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            if let Some(list) = get_list_mut(db, &self.key)? {
+                match until::normalize_range(self.start, self.end, list.len()) {
+                    Some((start, end)) => {
+                        list.truncate(end + 1);
+                        list.drain(..start);
+                    }
+                    None => list.clear(),
+                }
+            }
+            remove_if_empty(db, &self.key);
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::LTrim(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for LTrimCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "LTrim {} {} {}", &self.key, self.start, self.end)
+    }
+}
+
+impl TryFrom<Cmd> for LTrimCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::LTrim(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for LRemCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    fn may_grow_memory(&self) -> bool {
+        false
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let target: DBValue = self.value.clone().map_or(DBValue::None, |v| v.into());
+            let mut removed = 0usize;
+            if let Some(list) = get_list_mut(db, &self.key)? {
+                // count > 0 从头部开始删除，count < 0 从尾部开始删除，count = 0 删除全部
+                let matched = list.iter().filter(|value| **value == target).count();
+                let limit = match self.count.unsigned_abs() as usize {
+                    0 => matched,
+                    limit => limit.min(matched),
+                };
+                // 从尾部删除时保留前面的匹配元素
+                let mut skip = if self.count < 0 { matched - limit } else { 0 };
+                list.retain(|value| {
+                    if removed == limit || *value != target {
+                        return true;
+                    }
+                    if skip > 0 {
+                        skip -= 1;
+                        return true;
+                    }
+                    removed += 1;
+                    false
+                });
+            }
+            remove_if_empty(db, &self.key);
+            return Ok(Some(DBValue::Int64(removed as i64)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::LRem(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for LRemCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(value) = &self.value {
+            write!(f, "LRem {} {} {}", &self.key, self.count, value)
+        } else {
+            write!(f, "LRem {} {} None", &self.key, self.count)
+        }
+    }
+}
+
+impl TryFrom<Cmd> for LRemCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::LRem(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for BlPopCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            return blocking_pop(db, &self.keys, self.timeout_ms, true);
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::BlPop(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for BlPopCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "BLPop {} {}", self.keys.join(" "), self.timeout_ms)
+    }
+}
+
+impl TryFrom<Cmd> for BlPopCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::BlPop(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for BrPopCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            return blocking_pop(db, &self.keys, self.timeout_ms, false);
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::BrPop(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for BrPopCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "BRPop {} {}", self.keys.join(" "), self.timeout_ms)
+    }
+}
+
+impl TryFrom<Cmd> for BrPopCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::BrPop(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::command::{BlockedError, ExecutableCommand};
+    use crate::db::{database::Database, dbvalue::DBValue};
+    use crate::proto::{
+        BlPopCmd, LIndexCmd, LPushCmd, LRangeCmd, LRemCmd, LSetCmd, LTrimCmd, RPushCmd,
+    };
+
+    async fn rpush(db: &mut Database, values: &[&str]) {
+        let rpush = RPushCmd {
+            key: String::from("list"),
+            values: values
+                .iter()
+                .map(|v| DBValue::String(String::from(*v)).into())
+                .collect(),
+        };
+        rpush.execute(None, Some(db)).await.unwrap();
+    }
+
+    async fn lrange(db: &mut Database, start: i64, end: i64) -> Vec<String> {
+        let lrange = LRangeCmd {
+            key: String::from("list"),
+            start,
+            end,
+        };
+        match lrange.execute(None, Some(db)).await.unwrap() {
+            Some(DBValue::List(values)) => values
+                .iter()
+                .map(|v| match v {
+                    DBValue::String(s) => s.clone(),
+                    _ => panic!("list should only contain strings"),
+                })
+                .collect(),
+            _ => panic!("lrange should reply a list"),
+        }
+    }
+
+    async fn lrem(db: &mut Database, count: i64, value: &str) -> Option<DBValue> {
+        let lrem = LRemCmd {
+            key: String::from("list"),
+            count,
+            value: Some(DBValue::String(String::from(value)).into()),
+        };
+        lrem.execute(None, Some(db)).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn empty_push_test() {
+        let mut db = Database::new();
+        let lpush = LPushCmd {
+            key: String::from("queue"),
+            values: vec![],
+        };
+        assert!(lpush.execute(None, Some(&mut db)).await.is_err());
+        let rpush = RPushCmd {
+            key: String::from("queue"),
+            values: vec![],
+        };
+        assert!(rpush.execute(None, Some(&mut db)).await.is_err());
+        assert!(!db.contains_key("queue"));
+
+        let rpush = RPushCmd {
+            key: String::from("queue"),
+            values: vec![DBValue::Int64(1).into()],
+        };
+        let len = rpush.execute(None, Some(&mut db)).await.unwrap();
+        assert!(matches!(len, Some(DBValue::Int64(1))));
+    }
+
+    #[tokio::test]
+    async fn negative_index_test() {
+        let mut db = Database::new();
+        rpush(&mut db, &["a", "b", "c", "d", "e"]).await;
+        assert_eq!(lrange(&mut db, -3, -1).await, vec!["c", "d", "e"]);
+        assert_eq!(lrange(&mut db, 0, -4).await, vec!["a", "b"]);
+        assert!(lrange(&mut db, -1, -3).await.is_empty());
+        assert_eq!(lrange(&mut db, -100, 100).await.len(), 5);
+
+        let lindex = LIndexCmd {
+            key: String::from("list"),
+            index: -2,
+        };
+        let value = lindex.execute(None, Some(&mut db)).await.unwrap();
+        assert!(value == Some(DBValue::String(String::from("d"))));
+        let lindex = LIndexCmd {
+            key: String::from("list"),
+            index: -6,
+        };
+        assert!(lindex.execute(None, Some(&mut db)).await.unwrap().is_none());
+
+        // LSET返回被替换的值，越界时报错
+        let lset = LSetCmd {
+            key: String::from("list"),
+            index: -1,
+            value: Some(DBValue::String(String::from("z")).into()),
+        };
+        let old = lset.execute(None, Some(&mut db)).await.unwrap();
+        assert!(old == Some(DBValue::String(String::from("e"))));
+        let lset = LSetCmd { index: -6, ..lset };
+        assert!(lset.execute(None, Some(&mut db)).await.is_err());
+
+        let ltrim = LTrimCmd {
+            key: String::from("list"),
+            start: 1,
+            end: -2,
+        };
+        ltrim.execute(None, Some(&mut db)).await.unwrap();
+        assert_eq!(lrange(&mut db, 0, -1).await, vec!["b", "c", "d"]);
+        // 区间为空时删除整个列表
+        let ltrim = LTrimCmd {
+            key: String::from("list"),
+            start: -1,
+            end: 0,
+        };
+        ltrim.execute(None, Some(&mut db)).await.unwrap();
+        assert!(!db.contains_key("list"));
+    }
+
+    #[tokio::test]
+    async fn lrem_test() {
+        let mut db = Database::new();
+        rpush(&mut db, &["x", "a", "x", "b", "x", "c", "x"]).await;
+        // 正数从头部开始删除
+        assert!(lrem(&mut db, 2, "x").await == Some(DBValue::Int64(2)));
+        assert_eq!(lrange(&mut db, 0, -1).await, vec!["a", "b", "x", "c", "x"]);
+        // 负数从尾部开始删除
+        assert!(lrem(&mut db, -1, "x").await == Some(DBValue::Int64(1)));
+        assert_eq!(lrange(&mut db, 0, -1).await, vec!["a", "b", "x", "c"]);
+        rpush(&mut db, &["x", "x"]).await;
+        assert!(lrem(&mut db, -5, "x").await == Some(DBValue::Int64(3)));
+        assert_eq!(lrange(&mut db, 0, -1).await, vec!["a", "b", "c"]);
+        // 0删除全部匹配的元素，列表为空时删除key
+        rpush(&mut db, &["a"]).await;
+        assert!(lrem(&mut db, 0, "a").await == Some(DBValue::Int64(2)));
+        assert!(lrem(&mut db, 0, "missing").await == Some(DBValue::Int64(0)));
+        assert!(lrem(&mut db, 0, "b").await == Some(DBValue::Int64(1)));
+        assert!(lrem(&mut db, 0, "c").await == Some(DBValue::Int64(1)));
+        assert!(!db.contains_key("list"));
+    }
+
+    #[tokio::test]
+    async fn blpop_wakeup_test() {
+        let mut db = Database::new();
+        let blpop = BlPopCmd {
+            keys: vec![String::from("empty"), String::from("list")],
+            timeout_ms: 100,
+        };
+        // 所有列表为空时挂起，等待所有key
+        match blpop.execute(None, Some(&mut db)).await {
+            Err(err) => {
+                let blocked = err.downcast::<BlockedError>().unwrap();
+                assert_eq!(blocked.keys, blpop.keys);
+                assert_eq!(blocked.timeout_ms, 100);
+            }
+            Ok(_) => panic!("blpop should block on empty lists"),
+        }
+        // 推入后重新执行时弹出元素，返回[key, value]
+        rpush(&mut db, &["job-1", "job-2"]).await;
+        let result = blpop.execute(None, Some(&mut db)).await.unwrap();
+        let expected = vec![
+            DBValue::String(String::from("list")),
+            DBValue::String(String::from("job-1")),
+        ];
+        assert!(result == Some(DBValue::List(expected.into())));
+        assert_eq!(lrange(&mut db, 0, -1).await, vec!["job-2"]);
+    }
+}
@@ -0,0 +1,275 @@
+use super::{BlockedError, CommandType, ExecutableCommand};
+use crate::db::{database::Database, dbvalue::DBValue};
+use crate::proto::command_message::Cmd;
+use crate::proto::{LockCmd, RenewLockCmd, TryLockCmd, UnlockCmd};
+use crate::runtime::Runtime;
+use anyhow::anyhow;
+use async_trait::async_trait;
+use std::any::Any;
+use std::fmt::Display;
+
+#[async_trait]
+impl ExecutableCommand for LockCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    fn may_grow_memory(&self) -> bool {
+        false
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            // 锁被其它会话持有时挂起，锁释放或者租约到期后唤醒重新获取
+            return match db.acquire_lock(&self.name, self.lease_ms)? {
+                Some(token) => Ok(Some(DBValue::Int64(token as i64))),
+                None => Err(BlockedError {
+                    keys: vec![self.name.clone()],
+                    timeout_ms: self.timeout_ms,
+                    retry: None,
+                }
+                .into()),
+            };
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::Lock(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for LockCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Lock {} lease={}ms timeout={}ms",
+            &self.name, self.lease_ms, self.timeout_ms
+        )
+    }
+}
+
+impl TryFrom<Cmd> for LockCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::Lock(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for TryLockCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    fn may_grow_memory(&self) -> bool {
+        false
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let token = db.acquire_lock(&self.name, self.lease_ms)?.unwrap_or(0);
+            return Ok(Some(DBValue::Int64(token as i64)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::TryLock(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for TryLockCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "TryLock {} lease={}ms", &self.name, self.lease_ms)
+    }
+}
+
+impl TryFrom<Cmd> for TryLockCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::TryLock(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for UnlockCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    fn may_grow_memory(&self) -> bool {
+        false
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let remaining = db.release_lock(&self.name)?;
+            return Ok(Some(DBValue::Int64(remaining as i64)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::Unlock(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for UnlockCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Unlock {}", &self.name)
+    }
+}
+
+impl TryFrom<Cmd> for UnlockCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::Unlock(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for RenewLockCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    fn may_grow_memory(&self) -> bool {
+        false
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let renewed = db.renew_lock(&self.name, self.lease_ms);
+            return Ok(Some(DBValue::Boolean(renewed)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::RenewLock(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for RenewLockCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "RenewLock {} lease={}ms", &self.name, self.lease_ms)
+    }
+}
+
+impl TryFrom<Cmd> for RenewLockCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::RenewLock(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::command::{BlockedError, ExecutableCommand};
+    use crate::db::database::Database;
+    use crate::db::dbvalue::DBValue;
+    use crate::proto::{LockCmd, TryLockCmd, UnlockCmd};
+
+    fn token(value: Option<DBValue>) -> i64 {
+        match value {
+            Some(DBValue::Int64(token)) => token,
+            _ => panic!("lock should reply token"),
+        }
+    }
+
+    #[tokio::test]
+    async fn fencing_token_test() {
+        let mut db = Database::new();
+        let lock = LockCmd {
+            name: String::from("job"),
+            lease_ms: 1_000,
+            timeout_ms: 0,
+        };
+        db.set_clock(1_000);
+        db.set_index(42);
+        db.set_session("worker-1");
+        let first = token(lock.execute(None, Some(&mut db)).await.unwrap());
+        assert_eq!(first, 42);
+
+        // 其它会话获取时阻塞，尝试获取返回0
+        db.set_session("worker-2");
+        match lock.execute(None, Some(&mut db)).await {
+            Err(err) => assert!(err.is::<BlockedError>()),
+            Ok(_) => panic!("lock held by other session should block"),
+        }
+        let try_lock = TryLockCmd {
+            name: String::from("job"),
+            lease_ms: 1_000,
+        };
+        assert_eq!(
+            token(try_lock.execute(None, Some(&mut db)).await.unwrap()),
+            0
+        );
+        let unlock = UnlockCmd {
+            name: String::from("job"),
+        };
+        assert!(unlock.execute(None, Some(&mut db)).await.is_err());
+
+        // 租约到期后锁被释放，新的令牌大于之前的令牌
+        db.set_clock(2_000);
+        db.set_index(0);
+        let second = token(try_lock.execute(None, Some(&mut db)).await.unwrap());
+        assert!(second > first);
+        assert_eq!(db.lock_holder("job").unwrap().owner, "worker-2");
+        assert_eq!(token(unlock.execute(None, Some(&mut db)).await.unwrap()), 0);
+        assert!(db.lock_holder("job").is_none());
+    }
+}
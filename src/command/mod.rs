@@ -16,12 +16,39 @@ use std::any::Any;
 use std::fmt::Display;
 use tokio::sync::mpsc;
 
+pub mod batch;
+pub mod bitmap;
+pub mod bloom;
+pub mod expire;
+pub mod evict;
+pub mod executor;
+pub mod generic;
+pub mod hash_expire;
 pub mod hash_get;
 pub mod hash_put;
 pub mod hello;
+pub mod hyperloglog;
+pub mod index;
 pub mod invalid;
+pub mod json;
+pub mod list;
+pub mod lock;
+pub mod namespace;
+pub mod pubsub;
+pub mod query;
+pub mod numeric;
+pub mod primitive;
+pub mod processor;
 pub mod raft;
 pub mod register_info;
+pub mod scan;
+pub mod script;
+pub mod session;
+pub mod set;
+pub mod sorted_set;
+pub mod stream;
+pub mod string;
+pub mod watch;
 
 #[derive(PartialEq, Eq, Clone, Debug, Hash)]
 pub enum CommandType {
@@ -59,16 +86,48 @@ pub trait ExecutableCommand: Display + Send + Sync + Any {
         self.cmd_type() == CommandType::READ
     }
 
+    // 是否可能增加内存占用，内存超限且不淘汰时拒绝执行
+    fn may_grow_memory(&self) -> bool {
+        self.is_write_type()
+    }
+
     fn is_valid(&self) -> bool {
-        self.as_any().is::<InvalidCommand>()
+        !self.as_any().is::<InvalidCommand>()
+    }
+}
+
+// 阻塞类命令在数据未就绪时返回该错误，由数据库任务挂起客户端，直到key被写入或者超时
+#[derive(Debug)]
+pub struct BlockedError {
+    // 等待的key
+    pub keys: Vec<String>,
+    // 超时时间，0表示永久阻塞
+    pub timeout_ms: u64,
+    // 唤醒后重新执行的命令，None表示重新执行原命令
+    pub retry: Option<Cmd>,
+}
+
+impl Display for BlockedError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Blocked on keys [{}]", self.keys.join(","))
     }
 }
 
+impl std::error::Error for BlockedError {}
+
 pub struct Command {
     // 命令
     inner: Box<dyn ExecutableCommand>,
     // 用于返回命令执行结果的发送器
     tx: Option<mpsc::Sender<anyhow::Result<Option<DBValue>>>>,
+    // 命令时间戳(毫秒)，经raft复制的命令使用提案时写入的时间戳，保证各副本一致
+    ts: u128,
+    // 命令所在的命名空间，空字符串表示默认命名空间
+    namespace: String,
+    // 发送命令的客户端会话，空字符串表示没有会话
+    session: String,
+    // 命令所在的raft日志索引，命令未经raft复制时为0
+    index: u64,
 }
 
 impl Command {
@@ -79,9 +138,67 @@ impl Command {
         Command {
             inner: impl_cmd,
             tx,
+            ts: until::now_ts().unwrap_or(0),
+            namespace: String::new(),
+            session: String::new(),
+            index: 0,
         }
     }
 
+    pub fn with_ts(mut self, ts: u128) -> Self {
+        self.ts = ts;
+        self
+    }
+
+    pub fn ts(&self) -> u128 {
+        self.ts
+    }
+
+    pub fn with_namespace(mut self, namespace: String) -> Self {
+        self.namespace = namespace;
+        self
+    }
+
+    pub fn namespace(&self) -> &str {
+        &self.namespace
+    }
+
+    pub fn with_session(mut self, session: String) -> Self {
+        self.session = session;
+        self
+    }
+
+    pub fn session(&self) -> &str {
+        &self.session
+    }
+
+    pub fn with_index(mut self, index: u64) -> Self {
+        self.index = index;
+        self
+    }
+
+    pub fn index(&self) -> u64 {
+        self.index
+    }
+
+    // 替换为另一个命令，保留命令的上下文和发送器
+    pub fn with_cmd(mut self, cmd: Cmd) -> anyhow::Result<Self> {
+        self.inner = parse_proto_command(cmd)?;
+        Ok(self)
+    }
+
+    // 通过protobuf编解码复制命令，发送器共享
+    pub fn try_clone(&self) -> anyhow::Result<Command> {
+        Ok(Command {
+            inner: parse_proto_command(self.inner.to_cmd()?)?,
+            tx: self.tx.clone(),
+            ts: self.ts,
+            namespace: self.namespace.clone(),
+            session: self.session.clone(),
+            index: self.index,
+        })
+    }
+
     pub async fn execute(
         &self,
         app: Option<&Runtime>,
@@ -108,7 +225,7 @@ impl Command {
     }
 
     pub fn encode_to_payload(&self) -> anyhow::Result<bytes::Bytes> {
-        let now_ts = until::now_ts()? as i64;
+        let now_ts = self.ts as i64;
         let now_ts_sec: i64 = now_ts / 1000;
         let now_ts_mills: i64 = now_ts - now_ts_sec * 1000;
         let now_ts_nanos: i64 = now_ts_mills * 1000000;
@@ -118,7 +235,9 @@ impl Command {
             nanos: now_ts_nanos as i32,
         };
         let msg = CommandMessage {
+            namespace: self.namespace.clone(),
             ts: Some(ts),
+            session: self.session.clone(),
             cmd: Some(self.inner.to_cmd()?),
         };
         let mut buff = bytes::BytesMut::new();
@@ -158,7 +277,16 @@ impl From<&[u8]> for Command {
             Ok(command_message) => match command_message.cmd {
                 Some(cmd) => {
                     if let Ok(cmd) = parse_proto_command(cmd) {
-                        Command::new(cmd, None)
+                        let command = Command::new(cmd, None)
+                            .with_namespace(command_message.namespace)
+                            .with_session(command_message.session);
+                        match command_message.ts {
+                            Some(ts) => {
+                                let ts = ts.seconds as u128 * 1000 + ts.nanos as u128 / 1000000;
+                                command.with_ts(ts)
+                            }
+                            None => command,
+                        }
                     } else {
                         Command::new(Box::new(InvalidCommand {}), None)
                     }
@@ -0,0 +1,332 @@
+use super::{CommandType, ExecutableCommand};
+use crate::db::{database::Database, dbvalue::DBValue};
+use crate::proto::command_message::Cmd;
+use crate::proto::{FlushCmd, NamespaceConfigCmd, NamespaceInfoCmd, SelectCmd, SwapCmd};
+use crate::runtime::Runtime;
+use ahash::AHashMap;
+use anyhow::anyhow;
+use async_trait::async_trait;
+use std::any::Any;
+use std::fmt::Display;
+
+#[async_trait]
+impl ExecutableCommand for SelectCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::READ
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        _db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        // 命名空间的切换由连接处理
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::Select(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for SelectCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Select {}", &self.namespace)
+    }
+}
+
+impl TryFrom<Cmd> for SelectCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::Select(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for FlushCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    fn may_grow_memory(&self) -> bool {
+        false
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            db.flush();
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::Flush(*self))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for FlushCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Flush")
+    }
+}
+
+impl TryFrom<Cmd> for FlushCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::Flush(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for SwapCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    fn may_grow_memory(&self) -> bool {
+        false
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            db.swap(&self.namespace);
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::Swap(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for SwapCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Swap {}", &self.namespace)
+    }
+}
+
+impl TryFrom<Cmd> for SwapCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::Swap(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for NamespaceConfigCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    fn may_grow_memory(&self) -> bool {
+        false
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            db.create_namespace();
+            let config = &mut db.keyspace_mut().config;
+            config.default_ttl_ms = self.default_ttl_ms;
+            config.max_memory = self.max_memory as usize;
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::NamespaceConfig(*self))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for NamespaceConfigCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "NamespaceConfig {} {}",
+            self.default_ttl_ms, self.max_memory
+        )
+    }
+}
+
+impl TryFrom<Cmd> for NamespaceConfigCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::NamespaceConfig(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for NamespaceInfoCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::READ
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let used_memory = db.used_memory();
+            let space = db.keyspace();
+            let mut info = AHashMap::new();
+            let mut put = |name: &str, value: DBValue| {
+                info.insert(String::from(name), value);
+            };
+            put("namespace", DBValue::String(String::from(db.namespace())));
+            put("keys", DBValue::Int64(space.db.len() as i64));
+            put("expires", DBValue::Int64(space.expires.len() as i64));
+            put("used_memory", DBValue::Int64(used_memory as i64));
+            put("reads", DBValue::Int64(space.stats.reads as i64));
+            put("writes", DBValue::Int64(space.stats.writes as i64));
+            put(
+                "expired_keys",
+                DBValue::Int64(space.stats.expired_keys as i64),
+            );
+            put(
+                "evicted_keys",
+                DBValue::Int64(space.stats.evicted_keys as i64),
+            );
+            put(
+                "default_ttl_ms",
+                DBValue::Int64(space.config.default_ttl_ms as i64),
+            );
+            put("max_memory", DBValue::Int64(space.config.max_memory as i64));
+            return Ok(Some(DBValue::Hash(info)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::NamespaceInfo(*self))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for NamespaceInfoCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "NamespaceInfo")
+    }
+}
+
+impl TryFrom<Cmd> for NamespaceInfoCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::NamespaceInfo(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::ExecutableCommand;
+    use crate::db::{database::Database, dbvalue::DBValue};
+    use crate::proto::{FlushCmd, NamespaceConfigCmd, SwapCmd};
+
+    #[tokio::test]
+    async fn namespace_isolation_test() {
+        let mut db = Database::new();
+        db.set_clock(1_000);
+        db.set(String::from("k"), DBValue::Int64(1));
+        db.select("team-a");
+        assert!(!db.contains_key("k"));
+        db.set(String::from("k"), DBValue::Int64(2));
+
+        // 交换后数据互换，配置保留在原命名空间
+        let config = NamespaceConfigCmd {
+            default_ttl_ms: 500,
+            max_memory: 0,
+        };
+        config.execute(None, Some(&mut db)).await.unwrap();
+        let swap = SwapCmd {
+            namespace: String::from(""),
+        };
+        swap.execute(None, Some(&mut db)).await.unwrap();
+        assert!(db.get("k") == Some(&DBValue::Int64(1)));
+        db.set(String::from("ttl"), DBValue::Int64(3));
+        assert_eq!(db.expire_time("ttl"), Some(1_500));
+
+        FlushCmd {}.execute(None, Some(&mut db)).await.unwrap();
+        assert_eq!(db.dbsize(), 0);
+        db.select("default");
+        assert!(db.get("k") == Some(&DBValue::Int64(2)));
+        assert_eq!(db.namespaces(), vec!["default", "team-a"]);
+    }
+
+    #[tokio::test]
+    async fn namespace_create_test() {
+        let mut db = Database::new();
+        // 只读访问不创建命名空间
+        db.select("ghost");
+        assert!(db.get("k").is_none());
+        assert_eq!(db.namespaces(), vec!["default"]);
+        db.select("default");
+        assert_eq!(db.namespaces(), vec!["default"]);
+
+        // 写入或者显式配置时创建
+        db.select("team-a");
+        db.set(String::from("k"), DBValue::Int64(1));
+        db.select("team-b");
+        let config = NamespaceConfigCmd {
+            default_ttl_ms: 0,
+            max_memory: 1024,
+        };
+        config.execute(None, Some(&mut db)).await.unwrap();
+        db.select("default");
+        assert_eq!(db.namespaces(), vec!["default", "team-a", "team-b"]);
+    }
+}
@@ -0,0 +1,415 @@
+use super::{CommandType, ExecutableCommand};
+use crate::db::{database::Database, dbvalue::DBValue};
+use crate::proto::command_message::Cmd;
+use crate::proto::{DecrByCmd, HashIncrByCmd, HashIncrByFloatCmd, IncrByCmd, IncrByFloatCmd};
+use crate::runtime::Runtime;
+use ahash::AHashMap;
+use anyhow::anyhow;
+use async_trait::async_trait;
+use prost_types::Timestamp;
+use std::any::Any;
+use std::fmt::Display;
+
+#[derive(Clone, Copy, Debug)]
+enum Increment {
+    Int(i64),
+    Float(f64),
+}
+
+// 兼容旧数据：以字符串形式保存的计数器也可以自增，结果统一转为数值类型
+fn parse_number(s: &str) -> anyhow::Result<DBValue> {
+    if let Ok(v) = s.parse::<i64>() {
+        return Ok(DBValue::Int64(v));
+    }
+    match s.parse::<f64>() {
+        Ok(v) if v.is_finite() => Ok(DBValue::Float64(v)),
+        _ => Err(anyhow!("Value {} is not a number", s)),
+    }
+}
+
+fn finite(value: f64) -> anyhow::Result<DBValue> {
+    if value.is_finite() {
+        Ok(DBValue::Float64(value))
+    } else {
+        Err(anyhow!("Increment would produce NaN or Infinity"))
+    }
+}
+
+// 时间戳按毫秒自增，保留不足一毫秒的纳秒部分
+fn shift_timestamp(ts: &Timestamp, millis: i64) -> anyhow::Result<DBValue> {
+    let overflow = || anyhow!("Increment or decrement would overflow");
+    let current = ts
+        .seconds
+        .checked_mul(1000)
+        .and_then(|ms| ms.checked_add(ts.nanos.div_euclid(1_000_000) as i64))
+        .ok_or_else(overflow)?;
+    let shifted = current.checked_add(millis).ok_or_else(overflow)?;
+    Ok(DBValue::Timestamp(Timestamp {
+        seconds: shifted.div_euclid(1000),
+        nanos: (shifted.rem_euclid(1000) * 1_000_000) as i32 + ts.nanos.rem_euclid(1_000_000),
+    }))
+}
+
+/// 计算自增后的值，不存在的值视为0
+fn apply_increment(current: Option<&DBValue>, increment: Increment) -> anyhow::Result<DBValue> {
+    let base = match current {
+        None | Some(DBValue::None) => DBValue::Int64(0),
+        Some(DBValue::String(s)) => parse_number(s)?,
+        Some(v @ (DBValue::Int64(_) | DBValue::Float64(_))) => v.clone(),
+        Some(DBValue::Timestamp(ts)) => {
+            return match increment {
+                Increment::Int(millis) => shift_timestamp(ts, millis),
+                Increment::Float(_) => Err(anyhow!(
+                    "Value is a Timestamp, use the integer increment in milliseconds instead"
+                )),
+            }
+        }
+        Some(v) => {
+            return Err(anyhow!(
+                "Mismatch DBValue type, required Int64, Float64 or Timestamp but got {}",
+                v
+            ))
+        }
+    };
+    match (base, increment) {
+        (DBValue::Int64(v), Increment::Int(inc)) => v
+            .checked_add(inc)
+            .map(DBValue::Int64)
+            .ok_or_else(|| anyhow!("Increment or decrement would overflow")),
+        (DBValue::Int64(v), Increment::Float(inc)) => finite(v as f64 + inc),
+        (DBValue::Float64(_), Increment::Int(_)) => Err(anyhow!(
+            "Value is a Float64, use the float increment instead"
+        )),
+        (DBValue::Float64(v), Increment::Float(inc)) => finite(v + inc),
+        _ => unreachable!(),
+    }
+}
+
+fn incr_key(db: &mut Database, key: &str, increment: Increment) -> anyhow::Result<DBValue> {
+    let value = apply_increment(db.get(key), increment)?;
+    // 原地修改，保留key的过期时间
+    db.replace(String::from(key), value.clone());
+    Ok(value)
+}
+
+fn incr_hash_member(
+    db: &mut Database,
+    key: &str,
+    member_key: &str,
+    increment: Increment,
+) -> anyhow::Result<DBValue> {
+    match db.get_mut(key) {
+        Some(DBValue::Hash(hash)) => {
+            let value = apply_increment(hash.get(member_key), increment)?;
+            hash.insert(String::from(member_key), value.clone());
+            Ok(value)
+        }
+        Some(value) => Err(anyhow!(
+            "Mismatch DBValue type, required Hash but got {}",
+            value
+        )),
+        None => {
+            let value = apply_increment(None, increment)?;
+            let mut hash = AHashMap::new();
+            hash.insert(String::from(member_key), value.clone());
+            db.set(String::from(key), DBValue::Hash(hash));
+            Ok(value)
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for IncrByCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            return incr_key(db, &self.key, Increment::Int(self.increment)).map(Some);
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::IncrBy(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for IncrByCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "IncrBy {} {}", &self.key, self.increment)
+    }
+}
+
+impl TryFrom<Cmd> for IncrByCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::IncrBy(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for DecrByCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let increment = self
+                .decrement
+                .checked_neg()
+                .ok_or_else(|| anyhow!("Increment or decrement would overflow"))?;
+            return incr_key(db, &self.key, Increment::Int(increment)).map(Some);
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::DecrBy(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for DecrByCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "DecrBy {} {}", &self.key, self.decrement)
+    }
+}
+
+impl TryFrom<Cmd> for DecrByCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::DecrBy(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for IncrByFloatCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            return incr_key(db, &self.key, Increment::Float(self.increment)).map(Some);
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::IncrByFloat(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for IncrByFloatCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "IncrByFloat {} {}", &self.key, self.increment)
+    }
+}
+
+impl TryFrom<Cmd> for IncrByFloatCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::IncrByFloat(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for HashIncrByCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let increment = Increment::Int(self.increment);
+            return incr_hash_member(db, &self.key, &self.member_key, increment).map(Some);
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::HashIncrBy(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for HashIncrByCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "HashIncrBy {} {} {}",
+            &self.key, &self.member_key, self.increment
+        )
+    }
+}
+
+impl TryFrom<Cmd> for HashIncrByCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::HashIncrBy(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for HashIncrByFloatCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let increment = Increment::Float(self.increment);
+            return incr_hash_member(db, &self.key, &self.member_key, increment).map(Some);
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::HashIncrByFloat(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for HashIncrByFloatCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "HashIncrByFloat {} {} {}",
+            &self.key, &self.member_key, self.increment
+        )
+    }
+}
+
+impl TryFrom<Cmd> for HashIncrByFloatCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::HashIncrByFloat(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{apply_increment, incr_key, Increment};
+    use crate::db::{database::Database, dbvalue::DBValue};
+    use prost_types::Timestamp;
+
+    #[test]
+    fn apply_increment_test() {
+        assert!(matches!(
+            apply_increment(None, Increment::Int(5)),
+            Ok(DBValue::Int64(5))
+        ));
+        let legacy = DBValue::String(String::from("10"));
+        assert!(matches!(
+            apply_increment(Some(&legacy), Increment::Int(-3)),
+            Ok(DBValue::Int64(7))
+        ));
+        assert!(matches!(
+            apply_increment(Some(&DBValue::Int64(1)), Increment::Float(0.5)),
+            Ok(DBValue::Float64(v)) if v == 1.5
+        ));
+        assert!(apply_increment(Some(&DBValue::Int64(i64::MAX)), Increment::Int(1)).is_err());
+        assert!(apply_increment(Some(&DBValue::Float64(1.0)), Increment::Int(1)).is_err());
+        assert!(apply_increment(
+            Some(&DBValue::Float64(f64::MAX)),
+            Increment::Float(f64::MAX)
+        )
+        .is_err());
+        let text = DBValue::String(String::from("abc"));
+        assert!(apply_increment(Some(&text), Increment::Int(1)).is_err());
+    }
+
+    #[test]
+    fn timestamp_increment_test() {
+        let ts = |seconds, nanos| DBValue::Timestamp(Timestamp { seconds, nanos });
+        assert!(
+            apply_increment(Some(&ts(10, 500_000_123)), Increment::Int(1_600)).unwrap()
+                == ts(12, 100_000_123)
+        );
+        assert!(
+            apply_increment(Some(&ts(1, 0)), Increment::Int(-1_001)).unwrap()
+                == ts(-1, 999_000_000)
+        );
+        assert!(apply_increment(Some(&ts(1, 0)), Increment::Float(1.0)).is_err());
+        assert!(apply_increment(Some(&ts(i64::MAX / 1000, 0)), Increment::Int(i64::MAX)).is_err());
+        assert!(apply_increment(Some(&ts(i64::MAX, 0)), Increment::Int(1)).is_err());
+    }
+
+    #[test]
+    fn incr_keeps_ttl_test() {
+        let mut db = Database::new();
+        db.set_clock(1_000);
+        db.set(String::from("counter"), DBValue::Int64(1));
+        db.expire_at("counter", 5_000);
+        incr_key(&mut db, "counter", Increment::Int(1)).unwrap();
+        assert_eq!(db.expire_time("counter"), Some(5_000));
+        // 整体覆盖时清除过期时间
+        db.set(String::from("counter"), DBValue::Int64(0));
+        assert_eq!(db.expire_time("counter"), None);
+    }
+}
@@ -0,0 +1,947 @@
+use super::{BlockedError, CommandType, ExecutableCommand};
+use crate::db::{database::Database, dbvalue::DBValue};
+use crate::proto::command_message::Cmd;
+use crate::proto::{
+    AtomicAddAndGetCmd, AtomicCompareAndSetCmd, AtomicGetAndSetCmd, AtomicGetCmd, LatchAwaitCmd,
+    LatchCountDownCmd, LatchGetCountCmd, LatchTrySetCountCmd, RefCompareAndSetCmd, RefGetAndSetCmd,
+    RefGetCmd, SemaphoreAcquireCmd, SemaphoreAvailableCmd, SemaphoreInitCmd, SemaphoreReleaseCmd,
+    SemaphoreTryAcquireCmd,
+};
+use crate::runtime::Runtime;
+use anyhow::anyhow;
+use async_trait::async_trait;
+use std::any::Any;
+use std::fmt::Display;
+
+#[async_trait]
+impl ExecutableCommand for AtomicGetCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::READ
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let value = db.keyspace().primitives.atomic_get(&self.name);
+            return Ok(Some(DBValue::Int64(value)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::AtomicGet(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for AtomicGetCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "AtomicGet {}", &self.name)
+    }
+}
+
+impl TryFrom<Cmd> for AtomicGetCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::AtomicGet(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for AtomicGetAndSetCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    fn may_grow_memory(&self) -> bool {
+        false
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let primitives = &mut db.keyspace_mut().primitives;
+            let old = primitives.atomic_get_and_set(&self.name, self.value);
+            return Ok(Some(DBValue::Int64(old)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::AtomicGetAndSet(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for AtomicGetAndSetCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "AtomicGetAndSet {} {}", &self.name, self.value)
+    }
+}
+
+impl TryFrom<Cmd> for AtomicGetAndSetCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::AtomicGetAndSet(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for AtomicAddAndGetCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    fn may_grow_memory(&self) -> bool {
+        false
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let primitives = &mut db.keyspace_mut().primitives;
+            let value = primitives.atomic_add_and_get(&self.name, self.delta)?;
+            return Ok(Some(DBValue::Int64(value)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::AtomicAddAndGet(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for AtomicAddAndGetCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "AtomicAddAndGet {} {}", &self.name, self.delta)
+    }
+}
+
+impl TryFrom<Cmd> for AtomicAddAndGetCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::AtomicAddAndGet(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for AtomicCompareAndSetCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    fn may_grow_memory(&self) -> bool {
+        false
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let primitives = &mut db.keyspace_mut().primitives;
+            let updated = primitives.atomic_compare_and_set(&self.name, self.expect, self.update);
+            return Ok(Some(DBValue::Boolean(updated)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::AtomicCompareAndSet(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for AtomicCompareAndSetCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "AtomicCompareAndSet {} {} {}",
+            &self.name, self.expect, self.update
+        )
+    }
+}
+
+impl TryFrom<Cmd> for AtomicCompareAndSetCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::AtomicCompareAndSet(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for RefGetCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::READ
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let value = db.keyspace().primitives.reference_get(&self.name).cloned();
+            return Ok(value);
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::RefGet(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for RefGetCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "RefGet {}", &self.name)
+    }
+}
+
+impl TryFrom<Cmd> for RefGetCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::RefGet(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for RefGetAndSetCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let value = self.value.clone().map(DBValue::from);
+            let primitives = &mut db.keyspace_mut().primitives;
+            return Ok(primitives.reference_get_and_set(&self.name, value));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::RefGetAndSet(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for RefGetAndSetCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "RefGetAndSet {}", &self.name)
+    }
+}
+
+impl TryFrom<Cmd> for RefGetAndSetCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::RefGetAndSet(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for RefCompareAndSetCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let expect = self.expect.clone().map(DBValue::from);
+            let update = self.update.clone().map(DBValue::from);
+            let primitives = &mut db.keyspace_mut().primitives;
+            let updated = primitives.reference_compare_and_set(&self.name, expect.as_ref(), update);
+            return Ok(Some(DBValue::Boolean(updated)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::RefCompareAndSet(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for RefCompareAndSetCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "RefCompareAndSet {}", &self.name)
+    }
+}
+
+impl TryFrom<Cmd> for RefCompareAndSetCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::RefCompareAndSet(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for LatchTrySetCountCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    fn may_grow_memory(&self) -> bool {
+        false
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let primitives = &mut db.keyspace_mut().primitives;
+            let updated = primitives.latch_try_set_count(&self.name, self.count);
+            return Ok(Some(DBValue::Boolean(updated)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::LatchTrySetCount(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for LatchTrySetCountCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "LatchTrySetCount {} {}", &self.name, self.count)
+    }
+}
+
+impl TryFrom<Cmd> for LatchTrySetCountCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::LatchTrySetCount(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for LatchCountDownCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    fn may_grow_memory(&self) -> bool {
+        false
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let remaining = db.keyspace_mut().primitives.latch_count_down(&self.name);
+            if remaining == 0 {
+                // 计数归0，唤醒等待的客户端
+                db.signal_ready(&self.name);
+            }
+            return Ok(Some(DBValue::Int64(remaining as i64)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::LatchCountDown(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for LatchCountDownCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "LatchCountDown {}", &self.name)
+    }
+}
+
+impl TryFrom<Cmd> for LatchCountDownCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::LatchCountDown(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for LatchGetCountCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::READ
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let count = db.keyspace().primitives.latch_count(&self.name);
+            return Ok(Some(DBValue::Int64(count as i64)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::LatchGetCount(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for LatchGetCountCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "LatchGetCount {}", &self.name)
+    }
+}
+
+impl TryFrom<Cmd> for LatchGetCountCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::LatchGetCount(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for LatchAwaitCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::READ
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            if db.keyspace().primitives.latch_count(&self.name) == 0 {
+                return Ok(Some(DBValue::Boolean(true)));
+            }
+            // 计数未归0时挂起，倒计数命令应用后唤醒
+            return Err(BlockedError {
+                keys: vec![self.name.clone()],
+                timeout_ms: self.timeout_ms,
+                retry: None,
+            }
+            .into());
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::LatchAwait(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for LatchAwaitCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "LatchAwait {} timeout={}ms", &self.name, self.timeout_ms)
+    }
+}
+
+impl TryFrom<Cmd> for LatchAwaitCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::LatchAwait(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for SemaphoreInitCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    fn may_grow_memory(&self) -> bool {
+        false
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let primitives = &mut db.keyspace_mut().primitives;
+            let initialized = primitives.semaphore_init(&self.name, self.permits);
+            return Ok(Some(DBValue::Boolean(initialized)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::SemaphoreInit(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for SemaphoreInitCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SemaphoreInit {} {}", &self.name, self.permits)
+    }
+}
+
+impl TryFrom<Cmd> for SemaphoreInitCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::SemaphoreInit(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for SemaphoreAcquireCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    fn may_grow_memory(&self) -> bool {
+        false
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let primitives = &mut db.keyspace_mut().primitives;
+            if primitives.semaphore_try_acquire(&self.name, self.permits)? {
+                return Ok(Some(DBValue::Boolean(true)));
+            }
+            // 许可不足时挂起，释放许可的命令应用后唤醒
+            return Err(BlockedError {
+                keys: vec![self.name.clone()],
+                timeout_ms: self.timeout_ms,
+                retry: None,
+            }
+            .into());
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::SemaphoreAcquire(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for SemaphoreAcquireCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "SemaphoreAcquire {} {} timeout={}ms",
+            &self.name, self.permits, self.timeout_ms
+        )
+    }
+}
+
+impl TryFrom<Cmd> for SemaphoreAcquireCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::SemaphoreAcquire(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for SemaphoreTryAcquireCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    fn may_grow_memory(&self) -> bool {
+        false
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let primitives = &mut db.keyspace_mut().primitives;
+            let acquired = primitives.semaphore_try_acquire(&self.name, self.permits)?;
+            return Ok(Some(DBValue::Boolean(acquired)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::SemaphoreTryAcquire(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for SemaphoreTryAcquireCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SemaphoreTryAcquire {} {}", &self.name, self.permits)
+    }
+}
+
+impl TryFrom<Cmd> for SemaphoreTryAcquireCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::SemaphoreTryAcquire(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for SemaphoreReleaseCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    fn may_grow_memory(&self) -> bool {
+        false
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let primitives = &mut db.keyspace_mut().primitives;
+            let available = primitives.semaphore_release(&self.name, self.permits)?;
+            db.signal_ready(&self.name);
+            return Ok(Some(DBValue::Int64(available as i64)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::SemaphoreRelease(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for SemaphoreReleaseCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SemaphoreRelease {} {}", &self.name, self.permits)
+    }
+}
+
+impl TryFrom<Cmd> for SemaphoreReleaseCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::SemaphoreRelease(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for SemaphoreAvailableCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::READ
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let available = db.keyspace().primitives.semaphore_available(&self.name);
+            return Ok(available.map(|permits| DBValue::Int64(permits as i64)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::SemaphoreAvailable(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for SemaphoreAvailableCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SemaphoreAvailable {}", &self.name)
+    }
+}
+
+impl TryFrom<Cmd> for SemaphoreAvailableCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::SemaphoreAvailable(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::command::{BlockedError, Command, ExecutableCommand};
+    use crate::db::database::{
+        execute_command, expire_blocked_clients, serve_blocked_clients, Database,
+    };
+    use crate::db::dbvalue::DBValue;
+    use crate::proto::{
+        LatchAwaitCmd, LatchCountDownCmd, LatchTrySetCountCmd, SemaphoreAcquireCmd,
+        SemaphoreAvailableCmd, SemaphoreInitCmd, SemaphoreReleaseCmd,
+    };
+    use crate::runtime::Runtime;
+    use tokio::sync::mpsc;
+
+    async fn run(app: &Runtime, db: &mut Database, cmd: impl ExecutableCommand + 'static) {
+        let command = Command::new(Box::new(cmd), None);
+        execute_command(app, db, &command).await.unwrap();
+        serve_blocked_clients(app, db).await;
+    }
+
+    #[tokio::test]
+    async fn latch_await_test() {
+        let app = Runtime::new_with_default_config();
+        let mut db = Database::new();
+        let wait = LatchAwaitCmd {
+            name: String::from("ready"),
+            timeout_ms: 0,
+        };
+        // 计数为0时不阻塞
+        let result = wait.execute(None, Some(&mut db)).await.unwrap();
+        assert!(result == Some(DBValue::Boolean(true)));
+
+        let set_count = LatchTrySetCountCmd {
+            name: String::from("ready"),
+            count: 1,
+        };
+        run(&app, &mut db, set_count).await;
+        match wait.execute(None, Some(&mut db)).await {
+            Err(err) => assert!(err.is::<BlockedError>()),
+            Ok(_) => panic!("await should block until the count reaches 0"),
+        }
+        let (tx, mut rx) = mpsc::channel(1);
+        let command = Command::new(Box::new(wait), Some(tx));
+        execute_command(&app, &mut db, &command).await.unwrap();
+        assert_eq!(db.blocked.len(), 1);
+
+        let count_down = LatchCountDownCmd {
+            name: String::from("ready"),
+        };
+        run(&app, &mut db, count_down).await;
+        assert!(db.blocked.is_empty());
+        assert!(matches!(
+            rx.recv().await,
+            Some(Ok(Some(DBValue::Boolean(true))))
+        ));
+    }
+
+    #[tokio::test]
+    async fn semaphore_release_wakeup_test() {
+        let app = Runtime::new_with_default_config();
+        let mut db = Database::new();
+        let acquire = SemaphoreAcquireCmd {
+            name: String::from("slots"),
+            permits: 2,
+            timeout_ms: 0,
+        };
+        // 未初始化的信号量直接返回错误
+        assert!(acquire.execute(None, Some(&mut db)).await.is_err());
+        let init = SemaphoreInitCmd {
+            name: String::from("slots"),
+            permits: 1,
+        };
+        run(&app, &mut db, init).await;
+
+        let (tx, mut rx) = mpsc::channel(1);
+        let command = Command::new(Box::new(acquire), Some(tx));
+        execute_command(&app, &mut db, &command).await.unwrap();
+        assert_eq!(db.blocked.len(), 1);
+
+        // 释放后许可足够，阻塞的客户端获取许可
+        let release = SemaphoreReleaseCmd {
+            name: String::from("slots"),
+            permits: 1,
+        };
+        run(&app, &mut db, release).await;
+        assert!(db.blocked.is_empty());
+        assert!(matches!(
+            rx.recv().await,
+            Some(Ok(Some(DBValue::Boolean(true))))
+        ));
+        let available = SemaphoreAvailableCmd {
+            name: String::from("slots"),
+        };
+        let result = available.execute(None, Some(&mut db)).await.unwrap();
+        assert!(result == Some(DBValue::Int64(0)));
+    }
+
+    #[tokio::test]
+    async fn wait_timeout_test() {
+        let app = Runtime::new_with_default_config();
+        let mut db = Database::new();
+        db.set_clock(1_000);
+        let init = SemaphoreInitCmd {
+            name: String::from("slots"),
+            permits: 0,
+        };
+        run(&app, &mut db, init).await;
+        let (tx, mut rx) = mpsc::channel(1);
+        let acquire = Command::new(
+            Box::new(SemaphoreAcquireCmd {
+                name: String::from("slots"),
+                permits: 1,
+                timeout_ms: 100,
+            }),
+            Some(tx),
+        );
+        execute_command(&app, &mut db, &acquire).await.unwrap();
+        assert_eq!(db.blocked.len(), 1);
+
+        // 超时后返回空结果，之后释放的许可不再被该客户端获取
+        expire_blocked_clients(&mut db).await;
+        assert!(db.blocked.is_empty());
+        assert!(matches!(rx.recv().await, Some(Ok(None))));
+        let release = SemaphoreReleaseCmd {
+            name: String::from("slots"),
+            permits: 1,
+        };
+        run(&app, &mut db, release).await;
+        let available = SemaphoreAvailableCmd {
+            name: String::from("slots"),
+        };
+        let result = available.execute(None, Some(&mut db)).await.unwrap();
+        assert!(result == Some(DBValue::Int64(1)));
+    }
+}
@@ -0,0 +1,119 @@
+use super::{CommandType, ExecutableCommand};
+use crate::db::{database::Database, dbvalue::DBValue};
+use crate::proto::command_message::Cmd;
+use crate::proto::EntryProcessCmd;
+use crate::runtime::Runtime;
+use anyhow::anyhow;
+use async_trait::async_trait;
+use std::any::Any;
+use std::fmt::Display;
+
+#[async_trait]
+impl ExecutableCommand for EntryProcessCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            // 处理器名称和参数随命令经raft复制，每个副本执行相同的修改
+            let args: Vec<DBValue> = self.args.iter().cloned().map(DBValue::from).collect();
+            let result = db.process_entry(&self.key, &self.processor, &args)?;
+            db.signal_ready(&self.key);
+            return Ok(Some(result));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::EntryProcess(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for EntryProcessCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "EntryProcess {} {} ({} args)",
+            &self.key,
+            &self.processor,
+            self.args.len()
+        )
+    }
+}
+
+impl TryFrom<Cmd> for EntryProcessCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::EntryProcess(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::command::ExecutableCommand;
+    use crate::db::database::Database;
+    use crate::db::dbvalue::DBValue;
+    use crate::proto::EntryProcessCmd;
+    use anyhow::anyhow;
+
+    fn process(key: &str, processor: &str, args: Vec<DBValue>) -> EntryProcessCmd {
+        EntryProcessCmd {
+            key: String::from(key),
+            processor: String::from(processor),
+            args: args.into_iter().map(|arg| arg.into()).collect(),
+        }
+    }
+
+    #[tokio::test]
+    async fn entry_process_test() {
+        let mut db = Database::new();
+        // 自定义处理器：修改后返回错误，修改不生效
+        db.processors.register(
+            "double_or_fail",
+            |_: &str, value: &mut DBValue, _: &[DBValue]| match value {
+                DBValue::Int64(v) if *v < 100 => {
+                    *v *= 2;
+                    Ok(DBValue::Int64(*v))
+                }
+                DBValue::Int64(v) => {
+                    *v = 0;
+                    Err(anyhow!("too large"))
+                }
+                _ => Err(anyhow!("not a number")),
+            },
+        );
+        db.set(String::from("n"), DBValue::Int64(60));
+        let cmd = process("n", "double_or_fail", vec![]);
+        let result = cmd.execute(None, Some(&mut db)).await.unwrap();
+        assert!(result == Some(DBValue::Int64(120)));
+        assert!(cmd.execute(None, Some(&mut db)).await.is_err());
+        assert!(db.get("n") == Some(&DBValue::Int64(120)));
+
+        let incr = process(
+            "page",
+            "hash.incr",
+            vec![DBValue::String(String::from("hits")), DBValue::Int64(1)],
+        );
+        incr.execute(None, Some(&mut db)).await.unwrap();
+        let result = incr.execute(None, Some(&mut db)).await.unwrap();
+        assert!(result == Some(DBValue::Int64(2)));
+        assert!(process("page", "missing", vec![])
+            .execute(None, Some(&mut db))
+            .await
+            .is_err());
+    }
+}
@@ -0,0 +1,233 @@
+use super::{CommandType, ExecutableCommand};
+use crate::db::{database::Database, dbvalue::DBValue};
+use crate::proto::command_message::Cmd;
+use crate::proto::{PSubscribeCmd, PUnsubscribeCmd, PublishCmd, SubscribeCmd, UnsubscribeCmd};
+use crate::pubsub::ClusterPublish;
+use crate::runtime::Runtime;
+use anyhow::anyhow;
+use async_trait::async_trait;
+use std::any::Any;
+use std::fmt::Display;
+
+#[async_trait]
+impl ExecutableCommand for PublishCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::READ
+    }
+
+    async fn execute(
+        &self,
+        app: Option<&Runtime>,
+        _db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        // 消息不写入数据库，也不经过raft，直接投递给本节点的订阅者并转发到其它节点
+        if let Some(app) = app {
+            let message = self.message.clone().map_or(DBValue::None, DBValue::from);
+            let receivers = app.pubsub.publish(&self.channel, &message).await;
+            if !self.forwarded {
+                app.postman
+                    .send(Box::new(ClusterPublish(self.clone())))
+                    .await?;
+            }
+            return Ok(Some(DBValue::Int64(receivers as i64)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::Publish(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for PublishCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.message {
+            Some(message) => write!(f, "Publish {} {}", &self.channel, message),
+            None => write!(f, "Publish {} None", &self.channel),
+        }
+    }
+}
+
+impl TryFrom<Cmd> for PublishCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::Publish(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for SubscribeCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::READ
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        _db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        // 订阅关系属于连接，由命令服务器在收到命令时处理
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::Subscribe(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for SubscribeCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Subscribe [{}]", self.channels.join(","))
+    }
+}
+
+impl TryFrom<Cmd> for SubscribeCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::Subscribe(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for PSubscribeCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::READ
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        _db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        // 订阅关系属于连接，由命令服务器在收到命令时处理
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::PSubscribe(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for PSubscribeCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "PSubscribe [{}]", self.patterns.join(","))
+    }
+}
+
+impl TryFrom<Cmd> for PSubscribeCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::PSubscribe(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for UnsubscribeCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::READ
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        _db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        // 订阅关系属于连接，由命令服务器在收到命令时处理
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::Unsubscribe(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for UnsubscribeCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Unsubscribe [{}]", self.channels.join(","))
+    }
+}
+
+impl TryFrom<Cmd> for UnsubscribeCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::Unsubscribe(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for PUnsubscribeCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::READ
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        _db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        // 订阅关系属于连接，由命令服务器在收到命令时处理
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::PUnsubscribe(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for PUnsubscribeCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "PUnsubscribe [{}]", self.patterns.join(","))
+    }
+}
+
+impl TryFrom<Cmd> for PUnsubscribeCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::PUnsubscribe(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
@@ -0,0 +1,271 @@
+use super::{CommandType, ExecutableCommand};
+use crate::db::query::{self, AggregateFunction, GroupedAggregate, Predicate};
+use crate::db::{database::Database, dbvalue::DBValue};
+use crate::proto::command_message::Cmd;
+use crate::proto::{AggregateCmd, AggregateFunction as PAggregateFunction, QueryCmd};
+use crate::runtime::Runtime;
+use ahash::AHashMap;
+use anyhow::anyhow;
+use async_trait::async_trait;
+use std::any::Any;
+use std::collections::VecDeque;
+use std::fmt::Display;
+
+// 按key顺序遍历命名空间中满足条件的hash，跳过已过期的key和成员。
+// 所有节点都保存完整的数据副本，查询在收到命令的节点本地执行
+fn for_each_match<'a>(
+    db: &'a Database,
+    predicate: &Predicate,
+    mut f: impl FnMut(
+        &'a str,
+        &'a AHashMap<String, DBValue>,
+        &dyn Fn(&str) -> Option<&'a DBValue>,
+    ) -> bool,
+) {
+    for key in db.keyspace().keys.iter() {
+        let Some(DBValue::Hash(hash)) = db.get(key) else {
+            continue;
+        };
+        let field = |name: &str| -> Option<&'a DBValue> {
+            if db.is_field_expired(key, name) {
+                None
+            } else {
+                hash.get(name)
+            }
+        };
+        if predicate.eval(&field) && !f(key, hash, &field) {
+            break;
+        }
+    }
+}
+
+impl From<PAggregateFunction> for AggregateFunction {
+    fn from(value: PAggregateFunction) -> Self {
+        match value {
+            PAggregateFunction::Count => AggregateFunction::Count,
+            PAggregateFunction::Sum => AggregateFunction::Sum,
+            PAggregateFunction::Avg => AggregateFunction::Avg,
+            PAggregateFunction::Min => AggregateFunction::Min,
+            PAggregateFunction::Max => AggregateFunction::Max,
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for QueryCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::READ
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let predicate = Predicate::parse(&self.predicate)?;
+            let limit = if self.limit == 0 {
+                usize::MAX
+            } else {
+                self.limit as usize
+            };
+            // 每个匹配的hash回复为[key, hash]
+            let mut entries = VecDeque::new();
+            for_each_match(db, &predicate, |key, hash, field| {
+                let live: AHashMap<String, DBValue> = hash
+                    .keys()
+                    .filter_map(|name| field(name).map(|value| (name.clone(), value.clone())))
+                    .collect();
+                entries.push_back(DBValue::List(VecDeque::from(vec![
+                    DBValue::String(String::from(key)),
+                    DBValue::Hash(live),
+                ])));
+                entries.len() < limit
+            });
+            return Ok(Some(DBValue::List(entries)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::Query(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for QueryCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Query [{}] limit={}", &self.predicate, self.limit)
+    }
+}
+
+impl TryFrom<Cmd> for QueryCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::Query(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for AggregateCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::READ
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let function = AggregateFunction::from(
+                PAggregateFunction::try_from(self.function)
+                    .map_err(|_| anyhow!("Unknown aggregate function {}", self.function))?,
+            );
+            if function != AggregateFunction::Count && self.field.is_empty() {
+                return Err(anyhow!(
+                    "Aggregate function {:?} requires a field",
+                    function
+                ));
+            }
+            let predicate = Predicate::parse(&self.predicate)?;
+            // 先计算可以合并的部分结果，再计算最终结果
+            let mut groups = GroupedAggregate::new();
+            for_each_match(db, &predicate, |_, _, field| {
+                let group = if self.group_by.is_empty() {
+                    String::new()
+                } else {
+                    query::group_name(field(&self.group_by))
+                };
+                groups
+                    .entry(group)
+                    .or_default()
+                    .accumulate(field(&self.field));
+                true
+            });
+            if self.group_by.is_empty() {
+                let partial = groups.remove("").unwrap_or_default();
+                return Ok(Some(partial.finish(function)));
+            }
+            // 分组结果回复为分组名称到聚合结果的hash
+            let result = groups
+                .into_iter()
+                .map(|(group, partial)| (group, partial.finish(function)))
+                .collect();
+            return Ok(Some(DBValue::Hash(result)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::Aggregate(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for AggregateCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Aggregate {} [{}] field={} group_by={}",
+            self.function, &self.predicate, &self.field, &self.group_by
+        )
+    }
+}
+
+impl TryFrom<Cmd> for AggregateCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::Aggregate(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::command::ExecutableCommand;
+    use crate::db::database::Database;
+    use crate::db::dbvalue::DBValue;
+    use crate::proto::{AggregateCmd, AggregateFunction, HashPutCmd, QueryCmd};
+
+    async fn put(db: &mut Database, key: &str, member: &str, value: DBValue) {
+        let put = HashPutCmd {
+            key: String::from(key),
+            member_key: String::from(member),
+            member_value: Some(value.into()),
+            ephemeral: false,
+        };
+        put.execute(None, Some(db)).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn query_and_aggregate_test() {
+        let mut db = Database::new();
+        for (key, region, age) in [
+            ("user:1", "eu", 30),
+            ("user:2", "us", 20),
+            ("user:3", "eu", 50),
+        ] {
+            put(
+                &mut db,
+                key,
+                "region",
+                DBValue::String(String::from(region)),
+            )
+            .await;
+            put(&mut db, key, "age", DBValue::Int64(age)).await;
+        }
+        db.set(String::from("plain"), DBValue::Int64(1));
+
+        let query = QueryCmd {
+            predicate: String::from("region = 'eu' AND age > 40"),
+            limit: 0,
+        };
+        match query.execute(None, Some(&mut db)).await.unwrap() {
+            Some(DBValue::List(entries)) => {
+                assert_eq!(entries.len(), 1);
+                assert!(
+                    matches!(&entries[0], DBValue::List(entry) if entry[0] == DBValue::String(String::from("user:3")))
+                );
+            }
+            _ => panic!("query should reply entries"),
+        }
+
+        let avg = AggregateCmd {
+            predicate: String::new(),
+            function: AggregateFunction::Avg as i32,
+            field: String::from("age"),
+            group_by: String::from("region"),
+        };
+        match avg.execute(None, Some(&mut db)).await.unwrap() {
+            Some(DBValue::Hash(groups)) => {
+                assert!(groups["eu"] == DBValue::Float64(40.0));
+                assert!(groups["us"] == DBValue::Float64(20.0));
+            }
+            _ => panic!("grouped aggregate should reply hash"),
+        }
+        let count = AggregateCmd {
+            predicate: String::from("age >= 30"),
+            function: AggregateFunction::Count as i32,
+            field: String::new(),
+            group_by: String::new(),
+        };
+        let result = count.execute(None, Some(&mut db)).await.unwrap();
+        assert!(result == Some(DBValue::Int64(2)));
+    }
+}
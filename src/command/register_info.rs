@@ -1,3 +1,4 @@
+use super::batch::Batch;
 use super::ExecutableCommand;
 use crate::proto::command_message::Cmd;
 
@@ -7,5 +8,154 @@ pub fn parse_proto_command(cmd: Cmd) -> anyhow::Result<Box<dyn ExecutableCommand
         Cmd::HashPut(v) => Ok(Box::new(v)),
         Cmd::HashGet(v) => Ok(Box::new(v)),
         Cmd::Raft(v) => Ok(Box::new(v)),
+        Cmd::Get(v) => Ok(Box::new(v)),
+        Cmd::Set(v) => Ok(Box::new(v)),
+        Cmd::Del(v) => Ok(Box::new(v)),
+        Cmd::Exists(v) => Ok(Box::new(v)),
+        Cmd::GetSet(v) => Ok(Box::new(v)),
+        Cmd::Append(v) => Ok(Box::new(v)),
+        Cmd::Strlen(v) => Ok(Box::new(v)),
+        Cmd::SetRange(v) => Ok(Box::new(v)),
+        Cmd::GetRange(v) => Ok(Box::new(v)),
+        Cmd::Type(v) => Ok(Box::new(v)),
+        Cmd::LPush(v) => Ok(Box::new(v)),
+        Cmd::RPush(v) => Ok(Box::new(v)),
+        Cmd::LPop(v) => Ok(Box::new(v)),
+        Cmd::RPop(v) => Ok(Box::new(v)),
+        Cmd::LRange(v) => Ok(Box::new(v)),
+        Cmd::LLen(v) => Ok(Box::new(v)),
+        Cmd::LIndex(v) => Ok(Box::new(v)),
+        Cmd::LSet(v) => Ok(Box::new(v)),
+        Cmd::LTrim(v) => Ok(Box::new(v)),
+        Cmd::LRem(v) => Ok(Box::new(v)),
+        Cmd::BlPop(v) => Ok(Box::new(v)),
+        Cmd::BrPop(v) => Ok(Box::new(v)),
+        Cmd::SAdd(v) => Ok(Box::new(v)),
+        Cmd::SRem(v) => Ok(Box::new(v)),
+        Cmd::SIsMember(v) => Ok(Box::new(v)),
+        Cmd::SMembers(v) => Ok(Box::new(v)),
+        Cmd::SCard(v) => Ok(Box::new(v)),
+        Cmd::SPop(v) => Ok(Box::new(v)),
+        Cmd::SRandMember(v) => Ok(Box::new(v)),
+        Cmd::SInter(v) => Ok(Box::new(v)),
+        Cmd::SUnion(v) => Ok(Box::new(v)),
+        Cmd::SDiff(v) => Ok(Box::new(v)),
+        Cmd::SInterStore(v) => Ok(Box::new(v)),
+        Cmd::SUnionStore(v) => Ok(Box::new(v)),
+        Cmd::SDiffStore(v) => Ok(Box::new(v)),
+        Cmd::ZAdd(v) => Ok(Box::new(v)),
+        Cmd::ZRem(v) => Ok(Box::new(v)),
+        Cmd::ZScore(v) => Ok(Box::new(v)),
+        Cmd::ZIncrBy(v) => Ok(Box::new(v)),
+        Cmd::ZRange(v) => Ok(Box::new(v)),
+        Cmd::ZRevRange(v) => Ok(Box::new(v)),
+        Cmd::ZRank(v) => Ok(Box::new(v)),
+        Cmd::ZCount(v) => Ok(Box::new(v)),
+        Cmd::ZRemRangeByScore(v) => Ok(Box::new(v)),
+        Cmd::ZCard(v) => Ok(Box::new(v)),
+        Cmd::IncrBy(v) => Ok(Box::new(v)),
+        Cmd::DecrBy(v) => Ok(Box::new(v)),
+        Cmd::IncrByFloat(v) => Ok(Box::new(v)),
+        Cmd::HashIncrBy(v) => Ok(Box::new(v)),
+        Cmd::HashIncrByFloat(v) => Ok(Box::new(v)),
+        Cmd::Expire(v) => Ok(Box::new(v)),
+        Cmd::PExpire(v) => Ok(Box::new(v)),
+        Cmd::ExpireAt(v) => Ok(Box::new(v)),
+        Cmd::Ttl(v) => Ok(Box::new(v)),
+        Cmd::PTtl(v) => Ok(Box::new(v)),
+        Cmd::Persist(v) => Ok(Box::new(v)),
+        Cmd::ActiveExpire(v) => Ok(Box::new(v)),
+        Cmd::HashExpire(v) => Ok(Box::new(v)),
+        Cmd::HashTtl(v) => Ok(Box::new(v)),
+        Cmd::HashPersist(v) => Ok(Box::new(v)),
+        Cmd::Evict(v) => Ok(Box::new(v)),
+        Cmd::Scan(v) => Ok(Box::new(v)),
+        Cmd::HashScan(v) => Ok(Box::new(v)),
+        Cmd::SScan(v) => Ok(Box::new(v)),
+        Cmd::ZScan(v) => Ok(Box::new(v)),
+        Cmd::Select(v) => Ok(Box::new(v)),
+        Cmd::Flush(v) => Ok(Box::new(v)),
+        Cmd::Swap(v) => Ok(Box::new(v)),
+        Cmd::NamespaceConfig(v) => Ok(Box::new(v)),
+        Cmd::NamespaceInfo(v) => Ok(Box::new(v)),
+        Cmd::Publish(v) => Ok(Box::new(v)),
+        Cmd::Subscribe(v) => Ok(Box::new(v)),
+        Cmd::PSubscribe(v) => Ok(Box::new(v)),
+        Cmd::Unsubscribe(v) => Ok(Box::new(v)),
+        Cmd::PUnsubscribe(v) => Ok(Box::new(v)),
+        Cmd::Watch(v) => Ok(Box::new(v)),
+        Cmd::Unwatch(v) => Ok(Box::new(v)),
+        Cmd::Lock(v) => Ok(Box::new(v)),
+        Cmd::TryLock(v) => Ok(Box::new(v)),
+        Cmd::Unlock(v) => Ok(Box::new(v)),
+        Cmd::RenewLock(v) => Ok(Box::new(v)),
+        Cmd::AtomicGet(v) => Ok(Box::new(v)),
+        Cmd::AtomicGetAndSet(v) => Ok(Box::new(v)),
+        Cmd::AtomicAddAndGet(v) => Ok(Box::new(v)),
+        Cmd::AtomicCompareAndSet(v) => Ok(Box::new(v)),
+        Cmd::RefGet(v) => Ok(Box::new(v)),
+        Cmd::RefGetAndSet(v) => Ok(Box::new(v)),
+        Cmd::RefCompareAndSet(v) => Ok(Box::new(v)),
+        Cmd::LatchTrySetCount(v) => Ok(Box::new(v)),
+        Cmd::LatchCountDown(v) => Ok(Box::new(v)),
+        Cmd::LatchGetCount(v) => Ok(Box::new(v)),
+        Cmd::LatchAwait(v) => Ok(Box::new(v)),
+        Cmd::SemaphoreInit(v) => Ok(Box::new(v)),
+        Cmd::SemaphoreAcquire(v) => Ok(Box::new(v)),
+        Cmd::SemaphoreTryAcquire(v) => Ok(Box::new(v)),
+        Cmd::SemaphoreRelease(v) => Ok(Box::new(v)),
+        Cmd::SemaphoreAvailable(v) => Ok(Box::new(v)),
+        Cmd::SessionOpen(v) => Ok(Box::new(v)),
+        Cmd::SessionHeartbeat(v) => Ok(Box::new(v)),
+        Cmd::SessionClose(v) => Ok(Box::new(v)),
+        Cmd::SessionExpire(v) => Ok(Box::new(v)),
+        Cmd::XAdd(v) => Ok(Box::new(v)),
+        Cmd::XRange(v) => Ok(Box::new(v)),
+        Cmd::XRevRange(v) => Ok(Box::new(v)),
+        Cmd::XLen(v) => Ok(Box::new(v)),
+        Cmd::XRead(v) => Ok(Box::new(v)),
+        Cmd::XTrim(v) => Ok(Box::new(v)),
+        Cmd::XGroupCreate(v) => Ok(Box::new(v)),
+        Cmd::XReadGroup(v) => Ok(Box::new(v)),
+        Cmd::XAck(v) => Ok(Box::new(v)),
+        Cmd::XPending(v) => Ok(Box::new(v)),
+        Cmd::XClaim(v) => Ok(Box::new(v)),
+        Cmd::JsonGet(v) => Ok(Box::new(v)),
+        Cmd::JsonSet(v) => Ok(Box::new(v)),
+        Cmd::JsonDel(v) => Ok(Box::new(v)),
+        Cmd::JsonArrAppend(v) => Ok(Box::new(v)),
+        Cmd::JsonNumIncrBy(v) => Ok(Box::new(v)),
+        Cmd::IndexCreate(v) => Ok(Box::new(v)),
+        Cmd::IndexDrop(v) => Ok(Box::new(v)),
+        Cmd::IndexMatch(v) => Ok(Box::new(v)),
+        Cmd::IndexRange(v) => Ok(Box::new(v)),
+        Cmd::Query(v) => Ok(Box::new(v)),
+        Cmd::Aggregate(v) => Ok(Box::new(v)),
+        Cmd::EntryProcess(v) => Ok(Box::new(v)),
+        Cmd::Eval(v) => Ok(Box::new(v)),
+        Cmd::EvalSha(v) => Ok(Box::new(v)),
+        Cmd::ScriptLoad(v) => Ok(Box::new(v)),
+        Cmd::ScriptExists(v) => Ok(Box::new(v)),
+        Cmd::ScriptFlush(v) => Ok(Box::new(v)),
+        Cmd::ExecuteTask(v) => Ok(Box::new(v)),
+        Cmd::TaskResult(v) => Ok(Box::new(v)),
+        Cmd::SubmitTask(v) => Ok(Box::new(v)),
+        Cmd::PfAdd(v) => Ok(Box::new(v)),
+        Cmd::PfCount(v) => Ok(Box::new(v)),
+        Cmd::PfMerge(v) => Ok(Box::new(v)),
+        Cmd::BfReserve(v) => Ok(Box::new(v)),
+        Cmd::BfAdd(v) => Ok(Box::new(v)),
+        Cmd::BfExists(v) => Ok(Box::new(v)),
+        Cmd::SetBit(v) => Ok(Box::new(v)),
+        Cmd::GetBit(v) => Ok(Box::new(v)),
+        Cmd::BitCount(v) => Ok(Box::new(v)),
+        Cmd::BitPos(v) => Ok(Box::new(v)),
+        Cmd::BitOp(v) => Ok(Box::new(v)),
+        Cmd::BitField(v) => Ok(Box::new(v)),
+        Cmd::MGet(v) => Ok(Box::new(v)),
+        Cmd::MSet(v) => Ok(Box::new(v)),
+        Cmd::MSetNx(v) => Ok(Box::new(v)),
+        Cmd::Batch(v) => Ok(Box::new(Batch::from(v))),
+        Cmd::BlockTimeout(v) => Ok(Box::new(v)),
     }
 }
@@ -0,0 +1,486 @@
+use super::{CommandType, ExecutableCommand};
+use crate::db::{database::Database, dbvalue::DBValue};
+use crate::proto::command_message::Cmd;
+use crate::proto::{HashScanCmd, SScanCmd, ScanCmd, ZScanCmd};
+use crate::runtime::Runtime;
+use crate::until;
+use anyhow::anyhow;
+use async_trait::async_trait;
+use std::any::Any;
+use std::collections::VecDeque;
+use std::fmt::Display;
+
+// 游标起点和终点
+const CURSOR_DONE: &str = "0";
+// 默认每次遍历数量
+const DEFAULT_COUNT: usize = 10;
+// 单次遍历数量上限，避免一次遍历长时间占用数据库任务
+const MAX_COUNT: usize = 1000;
+
+// 游标为上一次遍历到的最后一个元素，加上前缀与结束标记区分
+fn decode_cursor(cursor: &str) -> anyhow::Result<Option<&str>> {
+    if cursor.is_empty() || cursor == CURSOR_DONE {
+        return Ok(None);
+    }
+    match cursor.strip_prefix(':') {
+        Some(after) => Ok(Some(after)),
+        None => Err(anyhow!("Invalid cursor {}", cursor)),
+    }
+}
+
+fn encode_cursor(next: Option<&str>) -> String {
+    match next {
+        Some(after) => format!(":{}", after),
+        None => String::from(CURSOR_DONE),
+    }
+}
+
+fn scan_count(count: u32) -> usize {
+    match count as usize {
+        0 => DEFAULT_COUNT,
+        n => n.min(MAX_COUNT),
+    }
+}
+
+fn pattern_match(pattern: &str, value: &str) -> bool {
+    pattern.is_empty() || until::glob_match(pattern, value)
+}
+
+// 有序集合的游标为上一次遍历到的最后一个成员的分数和成员，
+// 遍历期间分数发生变化的成员可能重复返回或者不返回
+fn decode_score_cursor(after: &str) -> anyhow::Result<(f64, &str)> {
+    after
+        .split_once(':')
+        .and_then(|(score, member)| match score.parse::<f64>() {
+            Ok(score) if !score.is_nan() => Some((score, member)),
+            _ => None,
+        })
+        .ok_or_else(|| anyhow!("Invalid cursor :{}", after))
+}
+
+fn scan_reply(next: Option<&str>, items: VecDeque<DBValue>) -> DBValue {
+    DBValue::List(VecDeque::from([
+        DBValue::String(encode_cursor(next)),
+        DBValue::List(items),
+    ]))
+}
+
+#[async_trait]
+impl ExecutableCommand for ScanCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::READ
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let after = decode_cursor(&self.cursor)?;
+            let (keys, next) = db.scan(after, scan_count(self.count));
+            let keys = keys
+                .into_iter()
+                .filter(|key| pattern_match(&self.pattern, key))
+                .map(|key| DBValue::String(String::from(key)))
+                .collect();
+            return Ok(Some(scan_reply(next, keys)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::Scan(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for ScanCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Scan {} {} {}", &self.cursor, &self.pattern, self.count)
+    }
+}
+
+impl TryFrom<Cmd> for ScanCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::Scan(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for HashScanCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::READ
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let after = decode_cursor(&self.cursor)?;
+            match db.get(&self.key) {
+                Some(DBValue::Hash(_)) => {}
+                Some(value) => {
+                    return Err(anyhow!(
+                        "Mismatch DBValue type, required Hash but got {}",
+                        value
+                    ))
+                }
+                None => return Ok(Some(scan_reply(None, VecDeque::new()))),
+            }
+            let (members, next) = db.scan_members(
+                &self.key,
+                after,
+                scan_count(self.count),
+                |value| match value {
+                    DBValue::Hash(hash) => hash.keys().cloned().collect(),
+                    _ => Vec::new(),
+                },
+            );
+            // 返回成员和值交替排列的列表，跳过快照生成之后删除的成员
+            let mut items = VecDeque::with_capacity(members.len() * 2);
+            if let Some(DBValue::Hash(hash)) = db.get(&self.key) {
+                for member in members {
+                    if !pattern_match(&self.pattern, &member)
+                        || db.is_field_expired(&self.key, &member)
+                    {
+                        continue;
+                    }
+                    if let Some(value) = hash.get(&member) {
+                        let value = value.clone();
+                        items.push_back(DBValue::String(member));
+                        items.push_back(value);
+                    }
+                }
+            }
+            return Ok(Some(scan_reply(next.as_deref(), items)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::HashScan(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for HashScanCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "HashScan {} {} {} {}",
+            &self.key, &self.cursor, &self.pattern, self.count
+        )
+    }
+}
+
+impl TryFrom<Cmd> for HashScanCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::HashScan(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for SScanCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::READ
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let after = decode_cursor(&self.cursor)?;
+            match db.get(&self.key) {
+                Some(DBValue::Set(_)) => {}
+                Some(value) => {
+                    return Err(anyhow!(
+                        "Mismatch DBValue type, required Set but got {}",
+                        value
+                    ))
+                }
+                None => return Ok(Some(scan_reply(None, VecDeque::new()))),
+            }
+            let (members, next) = db.scan_members(
+                &self.key,
+                after,
+                scan_count(self.count),
+                |value| match value {
+                    DBValue::Set(set) => set.iter().cloned().collect(),
+                    _ => Vec::new(),
+                },
+            );
+            let items = match db.get(&self.key) {
+                Some(DBValue::Set(set)) => members
+                    .into_iter()
+                    .filter(|member| set.contains(member) && pattern_match(&self.pattern, member))
+                    .map(DBValue::String)
+                    .collect(),
+                _ => VecDeque::new(),
+            };
+            return Ok(Some(scan_reply(next.as_deref(), items)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::SScan(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for SScanCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "SScan {} {} {} {}",
+            &self.key, &self.cursor, &self.pattern, self.count
+        )
+    }
+}
+
+impl TryFrom<Cmd> for SScanCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::SScan(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for ZScanCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::READ
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let after = decode_cursor(&self.cursor)?
+                .map(decode_score_cursor)
+                .transpose()?;
+            let zset = match db.get(&self.key) {
+                Some(DBValue::SortedSet(zset)) => zset,
+                Some(value) => {
+                    return Err(anyhow!(
+                        "Mismatch DBValue type, required SortedSet but got {}",
+                        value
+                    ))
+                }
+                None => return Ok(Some(scan_reply(None, VecDeque::new()))),
+            };
+            // 按排名从游标位置继续遍历，每次只访问一页成员
+            let mut iter = zset.iter_after(after);
+            let mut items = VecDeque::new();
+            let mut last = None;
+            for (member, score) in iter.by_ref().take(scan_count(self.count)) {
+                last = Some((score, member));
+                if pattern_match(&self.pattern, member) {
+                    items.push_back(DBValue::String(String::from(member)));
+                    items.push_back(DBValue::Float64(score));
+                }
+            }
+            let next = match iter.next() {
+                Some(_) => last.map(|(score, member)| format!("{}:{}", score, member)),
+                None => None,
+            };
+            return Ok(Some(scan_reply(next.as_deref(), items)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::ZScan(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for ZScanCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "ZScan {} {} {} {}",
+            &self.key, &self.cursor, &self.pattern, self.count
+        )
+    }
+}
+
+impl TryFrom<Cmd> for ZScanCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::ZScan(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::ExecutableCommand;
+    use crate::db::sorted_set::SortedSet;
+    use crate::db::{database::Database, dbvalue::DBValue};
+    use crate::proto::{SScanCmd, ScanCmd, ZScanCmd};
+    use ahash::AHashSet;
+
+    #[tokio::test]
+    async fn sscan_test() {
+        let mut db = Database::new();
+        let set: AHashSet<String> = ["d", "a", "e", "c", "b"].map(String::from).into();
+        db.set(String::from("s"), DBValue::Set(set));
+        let mut cursor = String::new();
+        let mut found = Vec::new();
+        loop {
+            let scan = SScanCmd {
+                key: String::from("s"),
+                cursor: cursor.clone(),
+                pattern: String::new(),
+                count: 2,
+            };
+            let reply = scan.execute(None, Some(&mut db)).await.unwrap();
+            let Some(DBValue::List(reply)) = reply else {
+                panic!("sscan should reply a list");
+            };
+            if let (DBValue::String(next), DBValue::List(members)) = (&reply[0], &reply[1]) {
+                found.extend(members.iter().map(|m| m.to_string()));
+                cursor = next.clone();
+            }
+            // 遍历过程中删除的成员不再返回
+            if let Some(DBValue::Set(set)) = db.get_mut("s") {
+                set.remove("d");
+            }
+            if cursor == "0" {
+                break;
+            }
+        }
+        let expected: Vec<String> = ["a", "b", "c", "e"]
+            .map(|m| DBValue::String(String::from(m)).to_string())
+            .to_vec();
+        assert_eq!(found, expected);
+    }
+
+    #[tokio::test]
+    async fn scan_keyspace_test() {
+        let mut db = Database::new();
+        for i in 0..25 {
+            db.set(format!("user:{:02}", i), DBValue::Int64(i));
+            db.set(format!("session:{:02}", i), DBValue::Int64(i));
+        }
+        let mut cursor = String::new();
+        let mut found = Vec::new();
+        let mut rounds = 0;
+        loop {
+            let scan = ScanCmd {
+                cursor: cursor.clone(),
+                pattern: String::from("user:*"),
+                count: 10,
+            };
+            let reply = scan.execute(None, Some(&mut db)).await.unwrap();
+            let Some(DBValue::List(reply)) = reply else {
+                panic!("scan should reply a list");
+            };
+            if let (DBValue::String(next), DBValue::List(keys)) = (&reply[0], &reply[1]) {
+                found.extend(keys.iter().map(|k| k.to_string()));
+                cursor = next.clone();
+            }
+            rounds += 1;
+            // 遍历过程中删除的key不再返回
+            if rounds == 1 {
+                db.remove("user:24");
+            }
+            if cursor == "0" {
+                break;
+            }
+        }
+        assert_eq!(rounds, 5);
+        assert_eq!(found.len(), 24);
+    }
+
+    #[tokio::test]
+    async fn zscan_test() {
+        let mut db = Database::new();
+        let mut zset = SortedSet::new();
+        for (member, score) in [("a", 3.0), ("b", 1.5), ("c", 1.5), ("d", -2.0), ("e", 10.0)] {
+            zset.insert(String::from(member), score);
+        }
+        db.set(String::from("z"), DBValue::SortedSet(zset));
+        let mut cursor = String::new();
+        let mut found = Vec::new();
+        let mut cursors = Vec::new();
+        loop {
+            let scan = ZScanCmd {
+                key: String::from("z"),
+                cursor: cursor.clone(),
+                pattern: String::new(),
+                count: 2,
+            };
+            let reply = scan.execute(None, Some(&mut db)).await.unwrap();
+            let Some(DBValue::List(reply)) = reply else {
+                panic!("zscan should reply a list");
+            };
+            if let (DBValue::String(next), DBValue::List(items)) = (&reply[0], &reply[1]) {
+                found.extend(items.iter().step_by(2).map(|m| m.to_string()));
+                cursor = next.clone();
+            }
+            // 遍历过程中删除的成员不再返回
+            if let Some(DBValue::SortedSet(zset)) = db.get_mut("z") {
+                zset.remove("a");
+            }
+            if cursor == "0" {
+                break;
+            }
+            cursors.push(cursor.clone());
+        }
+        // 按分数顺序从游标位置继续遍历
+        assert_eq!(cursors, vec![":1.5:b"]);
+        let expected: Vec<String> = ["d", "b", "c", "e"]
+            .map(|m| DBValue::String(String::from(m)).to_string())
+            .to_vec();
+        assert_eq!(found, expected);
+
+        let invalid = ZScanCmd {
+            key: String::from("z"),
+            cursor: String::from(":abc"),
+            pattern: String::new(),
+            count: 2,
+        };
+        assert!(invalid.execute(None, Some(&mut db)).await.is_err());
+    }
+}
@@ -0,0 +1,334 @@
+use super::{CommandType, ExecutableCommand};
+use crate::db::{database::Database, dbvalue::DBValue};
+use crate::proto::command_message::Cmd;
+use crate::proto::{EvalCmd, EvalShaCmd, ScriptExistsCmd, ScriptFlushCmd, ScriptLoadCmd};
+use crate::runtime::Runtime;
+use anyhow::anyhow;
+use async_trait::async_trait;
+use std::any::Any;
+use std::fmt::Display;
+
+#[async_trait]
+impl ExecutableCommand for EvalCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            // 脚本内容随命令经raft复制，每个副本编译并执行相同的脚本，
+            // 不缓存编译结果，需要重复执行的脚本通过ScriptLoad加载
+            let ast = db.scripts.compile(&self.script)?;
+            let args: Vec<DBValue> = self.args.iter().cloned().map(DBValue::from).collect();
+            return Ok(Some(db.run_script(&ast, &self.keys, &args)?));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::Eval(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for EvalCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Eval ({} bytes) {:?} ({} args)",
+            self.script.len(),
+            &self.keys,
+            self.args.len()
+        )
+    }
+}
+
+impl TryFrom<Cmd> for EvalCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::Eval(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for EvalShaCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let args: Vec<DBValue> = self.args.iter().cloned().map(DBValue::from).collect();
+            return Ok(Some(db.eval_script(&self.sha, &self.keys, &args)?));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::EvalSha(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for EvalShaCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "EvalSha {} {:?} ({} args)",
+            &self.sha,
+            &self.keys,
+            self.args.len()
+        )
+    }
+}
+
+impl TryFrom<Cmd> for EvalShaCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::EvalSha(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for ScriptLoadCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    fn may_grow_memory(&self) -> bool {
+        false
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let sha = db.scripts.load(&self.script)?;
+            return Ok(Some(DBValue::String(sha)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::ScriptLoad(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for ScriptLoadCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ScriptLoad ({} bytes)", self.script.len())
+    }
+}
+
+impl TryFrom<Cmd> for ScriptLoadCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::ScriptLoad(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for ScriptExistsCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::READ
+    }
+
+    fn may_grow_memory(&self) -> bool {
+        false
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let exists = self
+                .shas
+                .iter()
+                .map(|sha| DBValue::Boolean(db.scripts.exists(sha)))
+                .collect();
+            return Ok(Some(DBValue::List(exists)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::ScriptExists(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for ScriptExistsCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ScriptExists {:?}", &self.shas)
+    }
+}
+
+impl TryFrom<Cmd> for ScriptExistsCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::ScriptExists(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for ScriptFlushCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    fn may_grow_memory(&self) -> bool {
+        false
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let count = db.scripts.flush();
+            return Ok(Some(DBValue::Int64(count as i64)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::ScriptFlush(*self))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for ScriptFlushCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ScriptFlush")
+    }
+}
+
+impl TryFrom<Cmd> for ScriptFlushCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::ScriptFlush(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::command::ExecutableCommand;
+    use crate::db::database::Database;
+    use crate::db::dbvalue::DBValue;
+    use crate::proto::{EvalCmd, EvalShaCmd, ScriptExistsCmd, ScriptFlushCmd, ScriptLoadCmd};
+
+    // 令牌桶限流：桶中有令牌时扣减并返回true
+    const TAKE: &str = r#"
+        let tokens = get(KEYS[0]);
+        if tokens == () { tokens = ARGV[0]; }
+        if tokens <= 0 { return false; }
+        put(KEYS[0], tokens - 1);
+        true
+    "#;
+
+    fn eval(script: &str, keys: &[&str]) -> EvalCmd {
+        EvalCmd {
+            script: String::from(script),
+            keys: keys.iter().map(|key| String::from(*key)).collect(),
+            args: vec![DBValue::Int64(2).into()],
+        }
+    }
+
+    #[tokio::test]
+    async fn eval_test() {
+        let mut db = Database::new();
+        let load = ScriptLoadCmd {
+            script: String::from(TAKE),
+        };
+        let Some(DBValue::String(sha)) = load.execute(None, Some(&mut db)).await.unwrap() else {
+            panic!("ScriptLoad should return sha");
+        };
+        let take = EvalShaCmd {
+            sha,
+            keys: vec![String::from("bucket")],
+            args: vec![DBValue::Int64(2).into()],
+        };
+        for expected in [true, true, false] {
+            let result = take.execute(None, Some(&mut db)).await.unwrap();
+            assert!(result == Some(DBValue::Boolean(expected)));
+        }
+        assert!(db.get("bucket") == Some(&DBValue::Int64(0)));
+
+        // 脚本出错时之前的修改不生效
+        let failed = eval(r#"put(KEYS[0], 10); throw "abort";"#, &["bucket"]);
+        assert!(failed.execute(None, Some(&mut db)).await.is_err());
+        assert!(db.get("bucket") == Some(&DBValue::Int64(0)));
+        let undeclared = eval(r#"put("other", 1)"#, &["bucket"]);
+        assert!(undeclared.execute(None, Some(&mut db)).await.is_err());
+        assert!(db.get("other").is_none());
+
+        // Eval不缓存脚本，清空后已加载的脚本不再存在
+        let exists = ScriptExistsCmd {
+            shas: vec![take.sha.clone(), crate::db::script::script_sha(r#"put("other", 1)"#)],
+        };
+        let result = exists.execute(None, Some(&mut db)).await.unwrap();
+        assert!(
+            result
+                == Some(DBValue::List(
+                    [DBValue::Boolean(true), DBValue::Boolean(false)].into()
+                ))
+        );
+        let flushed = ScriptFlushCmd {}.execute(None, Some(&mut db)).await.unwrap();
+        assert!(flushed == Some(DBValue::Int64(1)));
+        assert!(take.execute(None, Some(&mut db)).await.is_err());
+    }
+}
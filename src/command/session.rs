@@ -0,0 +1,269 @@
+use super::{CommandType, ExecutableCommand};
+use crate::db::{database::Database, dbvalue::DBValue};
+use crate::proto::command_message::Cmd;
+use crate::proto::{SessionCloseCmd, SessionExpireCmd, SessionHeartbeatCmd, SessionOpenCmd};
+use crate::runtime::Runtime;
+use anyhow::anyhow;
+use async_trait::async_trait;
+use std::any::Any;
+use std::fmt::Display;
+
+#[async_trait]
+impl ExecutableCommand for SessionOpenCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    fn may_grow_memory(&self) -> bool {
+        false
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let deadline = db.open_session(self.timeout_ms)?;
+            return Ok(Some(DBValue::Int64(deadline as i64)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::SessionOpen(*self))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for SessionOpenCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SessionOpen timeout={}ms", self.timeout_ms)
+    }
+}
+
+impl TryFrom<Cmd> for SessionOpenCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::SessionOpen(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for SessionHeartbeatCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    fn may_grow_memory(&self) -> bool {
+        false
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let deadline = db.heartbeat_session()?;
+            return Ok(Some(DBValue::Int64(deadline as i64)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::SessionHeartbeat(*self))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for SessionHeartbeatCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SessionHeartbeat")
+    }
+}
+
+impl TryFrom<Cmd> for SessionHeartbeatCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::SessionHeartbeat(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for SessionCloseCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    fn may_grow_memory(&self) -> bool {
+        false
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let id = String::from(db.session());
+            return Ok(Some(DBValue::Boolean(db.close_session(&id))));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::SessionClose(*self))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for SessionCloseCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SessionClose")
+    }
+}
+
+impl TryFrom<Cmd> for SessionCloseCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::SessionClose(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for SessionExpireCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    fn may_grow_memory(&self) -> bool {
+        false
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            // 提案后会话可能又收到了心跳，只关闭在提案时间点仍然过期的会话
+            let mut closed = 0;
+            for id in self.sessions.iter() {
+                if db.is_session_expired(id) && db.close_session(id) {
+                    closed += 1;
+                }
+            }
+            return Ok(Some(DBValue::Int64(closed)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::SessionExpire(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for SessionExpireCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SessionExpire [{}]", self.sessions.join(","))
+    }
+}
+
+impl TryFrom<Cmd> for SessionExpireCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::SessionExpire(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::command::ExecutableCommand;
+    use crate::db::{database::Database, dbvalue::DBValue};
+    use crate::proto::{HashPutCmd, SessionExpireCmd, SessionOpenCmd, SetCmd, TryLockCmd};
+
+    #[tokio::test]
+    async fn ephemeral_expire_test() {
+        let mut db = Database::new();
+        db.set_clock(1_000);
+        db.set_session("registry-1");
+        let set = SetCmd {
+            key: String::from("service:api"),
+            value: Some(DBValue::String(String::from("10.0.0.1:80")).into()),
+            expire_ms: 0,
+            ephemeral: true,
+        };
+        // 没有打开会话时不能写入临时数据
+        assert!(set.execute(None, Some(&mut db)).await.is_err());
+        let open = SessionOpenCmd { timeout_ms: 500 };
+        open.execute(None, Some(&mut db)).await.unwrap();
+        set.execute(None, Some(&mut db)).await.unwrap();
+        let put = HashPutCmd {
+            key: String::from("members"),
+            member_key: String::from("registry-1"),
+            member_value: Some(DBValue::Boolean(true).into()),
+            ephemeral: true,
+        };
+        put.execute(None, Some(&mut db)).await.unwrap();
+        let lock = TryLockCmd {
+            name: String::from("leader"),
+            lease_ms: 60_000,
+        };
+        lock.execute(None, Some(&mut db)).await.unwrap();
+        db.select("other");
+        db.set(String::from("service:api"), DBValue::Int64(1));
+
+        let expire = SessionExpireCmd {
+            sessions: vec![String::from("registry-1")],
+        };
+        db.select("");
+        db.set_clock(1_200);
+        // 会话尚未过期
+        expire.execute(None, Some(&mut db)).await.unwrap();
+        assert!(db.contains_key("service:api"));
+
+        db.set_clock(1_500);
+        expire.execute(None, Some(&mut db)).await.unwrap();
+        assert!(!db.contains_key("service:api"));
+        assert!(!db.contains_key("members"));
+        assert!(db.lock_holder("leader").is_none());
+        assert!(db.client_session("registry-1").is_none());
+        // 其它命名空间中的同名普通key不受影响
+        db.select("other");
+        assert!(db.contains_key("service:api"));
+    }
+}
@@ -0,0 +1,841 @@
+use super::{CommandType, ExecutableCommand};
+use crate::db::{database::Database, dbvalue::DBValue};
+use crate::proto::command_message::Cmd;
+use crate::proto::{
+    SAddCmd, SCardCmd, SDiffCmd, SDiffStoreCmd, SInterCmd, SInterStoreCmd, SIsMemberCmd,
+    SMembersCmd, SPopCmd, SRandMemberCmd, SRemCmd, SUnionCmd, SUnionStoreCmd,
+};
+use crate::runtime::Runtime;
+use crate::until::{self, SplitMix64};
+use ahash::AHashSet;
+use anyhow::anyhow;
+use async_trait::async_trait;
+use std::any::Any;
+use std::collections::VecDeque;
+use std::fmt::Display;
+
+// 允许重复时单次随机返回的最大成员数量，结果数量不受集合大小限制
+const MAX_RANDOM_MEMBERS: u64 = 100_000;
+
+fn get_set<'a>(db: &'a Database, key: &str) -> anyhow::Result<Option<&'a AHashSet<String>>> {
+    match db.get(key) {
+        Some(DBValue::Set(set)) => Ok(Some(set)),
+        Some(value) => Err(anyhow!(
+            "Mismatch DBValue type, required Set but got {}",
+            value
+        )),
+        None => Ok(None),
+    }
+}
+
+fn get_set_mut<'a>(
+    db: &'a mut Database,
+    key: &str,
+) -> anyhow::Result<Option<&'a mut AHashSet<String>>> {
+    match db.get_mut(key) {
+        Some(DBValue::Set(set)) => Ok(Some(set)),
+        Some(value) => Err(anyhow!(
+            "Mismatch DBValue type, required Set but got {}",
+            value
+        )),
+        None => Ok(None),
+    }
+}
+
+// 集合为空时删除key
+fn remove_if_empty(db: &mut Database, key: &str) {
+    if let Some(DBValue::Set(set)) = db.get(key) {
+        if set.is_empty() {
+            db.remove(key);
+        }
+    }
+}
+
+// 随机选择成员。AHashSet的遍历顺序在各进程间不一致，因此先排序再用确定性的随机数选择，
+// 保证经raft复制的SPOP在所有副本上删除相同的成员
+fn pick_members(
+    set: &AHashSet<String>,
+    count: usize,
+    allow_repeat: bool,
+    rng: &mut SplitMix64,
+) -> Vec<String> {
+    let mut members: Vec<&String> = set.iter().collect();
+    members.sort_unstable();
+    if members.is_empty() {
+        return Vec::new();
+    }
+    if allow_repeat {
+        return (0..count)
+            .map(|_| members[rng.next_below(members.len())].clone())
+            .collect();
+    }
+    let count = count.min(members.len());
+    // 部分Fisher-Yates洗牌
+    for i in 0..count {
+        let j = i + rng.next_below(members.len() - i);
+        members.swap(i, j);
+    }
+    members[..count].iter().map(|m| (*m).clone()).collect()
+}
+
+fn new_rng(db: &Database, key: &str) -> SplitMix64 {
+    SplitMix64::new(db.now() as u64 ^ until::stable_hash(key))
+}
+
+#[derive(Clone, Copy)]
+enum SetOp {
+    Inter,
+    Union,
+    Diff,
+}
+
+fn combine(db: &Database, keys: &[String], op: SetOp) -> anyhow::Result<AHashSet<String>> {
+    let mut sets = Vec::with_capacity(keys.len());
+    for key in keys {
+        sets.push(get_set(db, key)?);
+    }
+    let mut iter = sets.into_iter();
+    let mut result = match iter.next() {
+        Some(Some(first)) => first.clone(),
+        _ => AHashSet::new(),
+    };
+    for set in iter {
+        match op {
+            SetOp::Inter => match set {
+                Some(set) => result.retain(|m| set.contains(m)),
+                None => result.clear(),
+            },
+            SetOp::Union => {
+                if let Some(set) = set {
+                    result.extend(set.iter().cloned());
+                }
+            }
+            SetOp::Diff => {
+                if let Some(set) = set {
+                    result.retain(|m| !set.contains(m));
+                }
+            }
+        }
+    }
+    Ok(result)
+}
+
+fn combine_and_store(
+    db: &mut Database,
+    destination: &str,
+    keys: &[String],
+    op: SetOp,
+) -> anyhow::Result<Option<DBValue>> {
+    let result = combine(db, keys, op)?;
+    let len = result.len();
+    if result.is_empty() {
+        db.remove(destination);
+    } else {
+        db.set(String::from(destination), DBValue::Set(result));
+    }
+    Ok(Some(DBValue::Int64(len as i64)))
+}
+
+#[async_trait]
+impl ExecutableCommand for SAddCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let added = match get_set_mut(db, &self.key)? {
+                Some(set) => self
+                    .members
+                    .iter()
+                    .filter(|m| set.insert((*m).clone()))
+                    .count(),
+                None => {
+                    let set: AHashSet<String> = self.members.iter().cloned().collect();
+                    let added = set.len();
+                    if added > 0 {
+                        db.set(self.key.clone(), DBValue::Set(set));
+                    }
+                    added
+                }
+            };
+            return Ok(Some(DBValue::Int64(added as i64)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::SAdd(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for SAddCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SAdd {} {}", &self.key, self.members.join(" "))
+    }
+}
+
+impl TryFrom<Cmd> for SAddCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::SAdd(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for SRemCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    fn may_grow_memory(&self) -> bool {
+        false
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let removed = match get_set_mut(db, &self.key)? {
+                Some(set) => self.members.iter().filter(|m| set.remove(*m)).count(),
+                None => 0,
+            };
+            remove_if_empty(db, &self.key);
+            return Ok(Some(DBValue::Int64(removed as i64)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::SRem(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for SRemCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SRem {} {}", &self.key, self.members.join(" "))
+    }
+}
+
+impl TryFrom<Cmd> for SRemCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::SRem(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for SIsMemberCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::READ
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let exists = get_set(db, &self.key)?.is_some_and(|set| set.contains(&self.member));
+            return Ok(Some(DBValue::Boolean(exists)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::SIsMember(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for SIsMemberCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SIsMember {} {}", &self.key, &self.member)
+    }
+}
+
+impl TryFrom<Cmd> for SIsMemberCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::SIsMember(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for SMembersCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::READ
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let members = get_set(db, &self.key)?.cloned().unwrap_or_default();
+            return Ok(Some(DBValue::Set(members)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::SMembers(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for SMembersCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SMembers {}", &self.key)
+    }
+}
+
+impl TryFrom<Cmd> for SMembersCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::SMembers(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for SCardCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::READ
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let len = get_set(db, &self.key)?.map_or(0, |set| set.len());
+            return Ok(Some(DBValue::Int64(len as i64)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::SCard(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for SCardCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SCard {}", &self.key)
+    }
+}
+
+impl TryFrom<Cmd> for SCardCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::SCard(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for SPopCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    fn may_grow_memory(&self) -> bool {
+        false
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let mut rng = new_rng(db, &self.key);
+            let popped = match get_set_mut(db, &self.key)? {
+                Some(set) => {
+                    let popped = pick_members(set, self.count.max(1) as usize, false, &mut rng);
+                    for member in &popped {
+                        set.remove(member);
+                    }
+                    popped
+                }
+                None => Vec::new(),
+            };
+            remove_if_empty(db, &self.key);
+            if self.count == 0 {
+                return Ok(popped.into_iter().next().map(DBValue::String));
+            }
+            return Ok(Some(DBValue::Set(popped.into_iter().collect())));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::SPop(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for SPopCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SPop {} {}", &self.key, self.count)
+    }
+}
+
+impl TryFrom<Cmd> for SPopCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::SPop(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for SRandMemberCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::READ
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            if self.count.unsigned_abs() > MAX_RANDOM_MEMBERS {
+                return Err(anyhow!(
+                    "Count {} out of range, maximum is {}",
+                    self.count,
+                    MAX_RANDOM_MEMBERS
+                ));
+            }
+            let mut rng = new_rng(db, &self.key);
+            let members = match get_set(db, &self.key)? {
+                Some(set) => pick_members(
+                    set,
+                    self.count.unsigned_abs().max(1) as usize,
+                    self.count < 0,
+                    &mut rng,
+                ),
+                None => Vec::new(),
+            };
+            if self.count == 0 {
+                return Ok(members.into_iter().next().map(DBValue::String));
+            }
+            // 允许重复时结果中可能有相同成员，因此返回列表
+            return Ok(Some(DBValue::List(
+                members.into_iter().map(DBValue::String).collect::<VecDeque<_>>(),
+            )));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::SRandMember(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for SRandMemberCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SRandMember {} {}", &self.key, self.count)
+    }
+}
+
+impl TryFrom<Cmd> for SRandMemberCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::SRandMember(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for SInterCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::READ
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            return Ok(Some(DBValue::Set(combine(db, &self.keys, SetOp::Inter)?)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::SInter(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for SInterCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SInter {}", self.keys.join(" "))
+    }
+}
+
+impl TryFrom<Cmd> for SInterCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::SInter(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for SUnionCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::READ
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            return Ok(Some(DBValue::Set(combine(db, &self.keys, SetOp::Union)?)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::SUnion(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for SUnionCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SUnion {}", self.keys.join(" "))
+    }
+}
+
+impl TryFrom<Cmd> for SUnionCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::SUnion(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for SDiffCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::READ
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            return Ok(Some(DBValue::Set(combine(db, &self.keys, SetOp::Diff)?)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::SDiff(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for SDiffCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SDiff {}", self.keys.join(" "))
+    }
+}
+
+impl TryFrom<Cmd> for SDiffCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::SDiff(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for SInterStoreCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            return combine_and_store(db, &self.destination, &self.keys, SetOp::Inter);
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::SInterStore(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for SInterStoreCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SInterStore {} {}", &self.destination, self.keys.join(" "))
+    }
+}
+
+impl TryFrom<Cmd> for SInterStoreCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::SInterStore(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for SUnionStoreCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            return combine_and_store(db, &self.destination, &self.keys, SetOp::Union);
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::SUnionStore(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for SUnionStoreCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SUnionStore {} {}", &self.destination, self.keys.join(" "))
+    }
+}
+
+impl TryFrom<Cmd> for SUnionStoreCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::SUnionStore(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for SDiffStoreCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            return combine_and_store(db, &self.destination, &self.keys, SetOp::Diff);
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::SDiffStore(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for SDiffStoreCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SDiffStore {} {}", &self.destination, self.keys.join(" "))
+    }
+}
+
+impl TryFrom<Cmd> for SDiffStoreCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::SDiffStore(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{combine, pick_members, SetOp};
+    use crate::command::ExecutableCommand;
+    use crate::db::{database::Database, dbvalue::DBValue};
+    use crate::proto::SRandMemberCmd;
+    use crate::until::SplitMix64;
+    use ahash::AHashSet;
+
+    #[test]
+    fn pick_members_is_deterministic_test() {
+        let set: AHashSet<String> = (0..20).map(|i| format!("member-{}", i)).collect();
+        // 不同的AHashSet实例遍历顺序不同，但相同种子选出的成员必须一致
+        let other: AHashSet<String> = set.iter().cloned().collect();
+        let a = pick_members(&set, 5, false, &mut SplitMix64::new(42));
+        let b = pick_members(&other, 5, false, &mut SplitMix64::new(42));
+        assert_eq!(a, b);
+        let distinct: AHashSet<&String> = a.iter().collect();
+        assert_eq!(distinct.len(), 5);
+    }
+
+    #[test]
+    fn combine_test() {
+        let mut db = Database::new();
+        let a: AHashSet<String> = ["a", "b", "c"].iter().map(|s| s.to_string()).collect();
+        let b: AHashSet<String> = ["b", "c", "d"].iter().map(|s| s.to_string()).collect();
+        db.set(String::from("a"), DBValue::Set(a));
+        db.set(String::from("b"), DBValue::Set(b));
+        let keys = vec![String::from("a"), String::from("b")];
+
+        let mut inter: Vec<String> = combine(&db, &keys, SetOp::Inter).unwrap().into_iter().collect();
+        inter.sort();
+        assert_eq!(inter, vec!["b", "c"]);
+        assert_eq!(combine(&db, &keys, SetOp::Union).unwrap().len(), 4);
+        let diff: Vec<String> = combine(&db, &keys, SetOp::Diff).unwrap().into_iter().collect();
+        assert_eq!(diff, vec!["a"]);
+
+        // 不存在的key视为空集合
+        let missing = vec![String::from("a"), String::from("missing")];
+        assert!(combine(&db, &missing, SetOp::Inter).unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn srandmember_count_limit_test() {
+        let mut db = Database::new();
+        let set: AHashSet<String> = ["a", "b"].iter().map(|s| s.to_string()).collect();
+        db.set(String::from("s"), DBValue::Set(set));
+        for count in [i64::MIN, -100_001] {
+            let cmd = SRandMemberCmd {
+                key: String::from("s"),
+                count,
+            };
+            assert!(cmd.execute(None, Some(&mut db)).await.is_err());
+        }
+        let cmd = SRandMemberCmd {
+            key: String::from("s"),
+            count: -5,
+        };
+        let result = cmd.execute(None, Some(&mut db)).await.unwrap();
+        assert!(matches!(result, Some(DBValue::List(list)) if list.len() == 5));
+    }
+}
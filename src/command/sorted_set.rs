@@ -0,0 +1,935 @@
+use super::{CommandType, ExecutableCommand};
+use crate::db::sorted_set::{LexBound, ScoreBound, SortedSet};
+use crate::db::{database::Database, dbvalue::DBValue};
+use crate::proto::command_message::Cmd;
+use crate::proto::{
+    ZAddCmd, ZCardCmd, ZCountCmd, ZIncrByCmd, ZRangeBy, ZRangeCmd, ZRankCmd, ZRemCmd,
+    ZRemRangeByScoreCmd, ZRevRangeCmd, ZScoreCmd,
+};
+use crate::runtime::Runtime;
+use crate::until;
+use anyhow::anyhow;
+use async_trait::async_trait;
+use std::any::Any;
+use std::collections::VecDeque;
+use std::fmt::Display;
+
+fn get_zset<'a>(db: &'a Database, key: &str) -> anyhow::Result<Option<&'a SortedSet>> {
+    match db.get(key) {
+        Some(DBValue::SortedSet(zset)) => Ok(Some(zset)),
+        Some(value) => Err(anyhow!(
+            "Mismatch DBValue type, required SortedSet but got {}",
+            value
+        )),
+        None => Ok(None),
+    }
+}
+
+fn get_zset_mut<'a>(db: &'a mut Database, key: &str) -> anyhow::Result<Option<&'a mut SortedSet>> {
+    match db.get_mut(key) {
+        Some(DBValue::SortedSet(zset)) => Ok(Some(zset)),
+        Some(value) => Err(anyhow!(
+            "Mismatch DBValue type, required SortedSet but got {}",
+            value
+        )),
+        None => Ok(None),
+    }
+}
+
+// 不存在时创建空的有序集合
+fn get_or_create_zset<'a>(db: &'a mut Database, key: &str) -> anyhow::Result<&'a mut SortedSet> {
+    if get_zset(db, key)?.is_none() {
+        db.set(String::from(key), DBValue::SortedSet(SortedSet::new()));
+    }
+    match db.get_mut(key) {
+        Some(DBValue::SortedSet(zset)) => Ok(zset),
+        _ => Err(anyhow!("SortedSet {} not found", key)),
+    }
+}
+
+// 有序集合为空时删除key
+fn remove_if_empty(db: &mut Database, key: &str) {
+    if let Some(DBValue::SortedSet(zset)) = db.get(key) {
+        if zset.is_empty() {
+            db.remove(key);
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn range(
+    zset: &SortedSet,
+    start: &str,
+    stop: &str,
+    by: ZRangeBy,
+    rev: bool,
+    offset: u64,
+    limit: u64,
+    with_scores: bool,
+) -> anyhow::Result<DBValue> {
+    let items: Vec<(&str, f64)> = match by {
+        ZRangeBy::Rank => {
+            let start: i64 = start
+                .parse()
+                .map_err(|_| anyhow!("Invalid rank {}", start))?;
+            let stop: i64 = stop.parse().map_err(|_| anyhow!("Invalid rank {}", stop))?;
+            match until::normalize_range(start, stop, zset.len()) {
+                Some((start, end)) => {
+                    let iter: Box<dyn Iterator<Item = (&str, f64)>> = if rev {
+                        Box::new(zset.iter().rev())
+                    } else {
+                        Box::new(zset.iter())
+                    };
+                    // 按排名跳过前面的成员，耗时与start成正比
+                    iter.skip(start).take(end - start + 1).collect()
+                }
+                None => Vec::new(),
+            }
+        }
+        ZRangeBy::Score => {
+            // 逆序时start为上界，stop为下界
+            let (min, max) = if rev { (stop, start) } else { (start, stop) };
+            let min = ScoreBound::parse(min)?;
+            let max = ScoreBound::parse(max)?;
+            let iter: Box<dyn Iterator<Item = (&str, f64)>> = if rev {
+                Box::new(zset.range_by_score(min, max).rev())
+            } else {
+                Box::new(zset.range_by_score(min, max))
+            };
+            limit_items(iter, offset, limit)
+        }
+        ZRangeBy::Lex => {
+            let (min, max) = if rev { (stop, start) } else { (start, stop) };
+            let min = LexBound::parse(min)?;
+            let max = LexBound::parse(max)?;
+            let iter: Box<dyn Iterator<Item = (&str, f64)>> = if rev {
+                Box::new(zset.range_by_lex(min, max).rev())
+            } else {
+                Box::new(zset.range_by_lex(min, max))
+            };
+            limit_items(iter, offset, limit)
+        }
+    };
+    let mut values = VecDeque::with_capacity(if with_scores {
+        items.len() * 2
+    } else {
+        items.len()
+    });
+    for (member, score) in items {
+        values.push_back(DBValue::String(String::from(member)));
+        if with_scores {
+            values.push_back(DBValue::Float64(score));
+        }
+    }
+    Ok(DBValue::List(values))
+}
+
+fn limit_items<'a>(
+    iter: Box<dyn Iterator<Item = (&'a str, f64)> + 'a>,
+    offset: u64,
+    limit: u64,
+) -> Vec<(&'a str, f64)> {
+    let iter = iter.skip(offset as usize);
+    if limit == 0 {
+        iter.collect()
+    } else {
+        iter.take(limit as usize).collect()
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for ZAddCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            if self.nx && self.xx {
+                return Err(anyhow!("ZAdd NX and XX options are not compatible"));
+            }
+            if (self.gt && self.lt) || (self.nx && (self.gt || self.lt)) {
+                return Err(anyhow!("ZAdd GT, LT, and NX options are not compatible"));
+            }
+            if self.entries.iter().any(|e| e.score.is_nan()) {
+                return Err(anyhow!("ZAdd score is not a valid float"));
+            }
+            // key不存在且不会插入任何成员时直接返回，避免创建后又删除空集合
+            if get_zset(db, &self.key)?.is_none() && (self.xx || self.entries.is_empty()) {
+                return Ok(Some(DBValue::Int64(0)));
+            }
+            let zset = get_or_create_zset(db, &self.key)?;
+            let mut added = 0usize;
+            let mut changed = 0usize;
+            for entry in &self.entries {
+                match zset.score(&entry.member) {
+                    Some(old) => {
+                        if self.nx
+                            || (self.gt && entry.score <= old)
+                            || (self.lt && entry.score >= old)
+                        {
+                            continue;
+                        }
+                        if old != entry.score {
+                            zset.insert(entry.member.clone(), entry.score);
+                            changed += 1;
+                        }
+                    }
+                    None => {
+                        if self.xx {
+                            continue;
+                        }
+                        zset.insert(entry.member.clone(), entry.score);
+                        added += 1;
+                    }
+                }
+            }
+            let count = if self.ch { added + changed } else { added };
+            return Ok(Some(DBValue::Int64(count as i64)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::ZAdd(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for ZAddCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ZAdd {} ({} entries)", &self.key, self.entries.len())
+    }
+}
+
+impl TryFrom<Cmd> for ZAddCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::ZAdd(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for ZRemCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    fn may_grow_memory(&self) -> bool {
+        false
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let removed = match get_zset_mut(db, &self.key)? {
+                Some(zset) => self.members.iter().filter(|m| zset.remove(m)).count(),
+                None => 0,
+            };
+            remove_if_empty(db, &self.key);
+            return Ok(Some(DBValue::Int64(removed as i64)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::ZRem(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for ZRemCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ZRem {} {}", &self.key, self.members.join(" "))
+    }
+}
+
+impl TryFrom<Cmd> for ZRemCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::ZRem(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for ZScoreCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::READ
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let score = get_zset(db, &self.key)?.and_then(|zset| zset.score(&self.member));
+            return Ok(score.map(DBValue::Float64));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::ZScore(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for ZScoreCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ZScore {} {}", &self.key, &self.member)
+    }
+}
+
+impl TryFrom<Cmd> for ZScoreCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::ZScore(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for ZIncrByCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let old = get_zset(db, &self.key)?.and_then(|zset| zset.score(&self.member));
+            let score = old.unwrap_or(0.0) + self.increment;
+            if score.is_nan() {
+                return Err(anyhow!("ZIncrBy resulting score is not a number (NaN)"));
+            }
+            get_or_create_zset(db, &self.key)?.insert(self.member.clone(), score);
+            return Ok(Some(DBValue::Float64(score)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::ZIncrBy(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for ZIncrByCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ZIncrBy {} {} {}", &self.key, self.increment, &self.member)
+    }
+}
+
+impl TryFrom<Cmd> for ZIncrByCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::ZIncrBy(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for ZRangeCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::READ
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            return match get_zset(db, &self.key)? {
+                Some(zset) => Ok(Some(range(
+                    zset,
+                    &self.start,
+                    &self.stop,
+                    self.by(),
+                    self.rev,
+                    self.offset,
+                    self.limit,
+                    self.with_scores,
+                )?)),
+                None => Ok(Some(DBValue::List(VecDeque::new()))),
+            };
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::ZRange(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for ZRangeCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "ZRange {} {} {} {:?}",
+            &self.key,
+            &self.start,
+            &self.stop,
+            self.by()
+        )
+    }
+}
+
+impl TryFrom<Cmd> for ZRangeCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::ZRange(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for ZRevRangeCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::READ
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            return match get_zset(db, &self.key)? {
+                Some(zset) => Ok(Some(range(
+                    zset,
+                    &self.start,
+                    &self.stop,
+                    self.by(),
+                    true,
+                    self.offset,
+                    self.limit,
+                    self.with_scores,
+                )?)),
+                None => Ok(Some(DBValue::List(VecDeque::new()))),
+            };
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::ZRevRange(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for ZRevRangeCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "ZRevRange {} {} {} {:?}",
+            &self.key,
+            &self.start,
+            &self.stop,
+            self.by()
+        )
+    }
+}
+
+impl TryFrom<Cmd> for ZRevRangeCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::ZRevRange(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for ZRankCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::READ
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            if let Some(zset) = get_zset(db, &self.key)? {
+                let rank = zset.rank(&self.member).map(|rank| {
+                    if self.rev {
+                        zset.len() - 1 - rank
+                    } else {
+                        rank
+                    }
+                });
+                return Ok(rank.map(|rank| DBValue::Int64(rank as i64)));
+            }
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::ZRank(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for ZRankCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ZRank {} {} rev={}", &self.key, &self.member, self.rev)
+    }
+}
+
+impl TryFrom<Cmd> for ZRankCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::ZRank(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for ZCountCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::READ
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let min = ScoreBound::parse(&self.min)?;
+            let max = ScoreBound::parse(&self.max)?;
+            let count =
+                get_zset(db, &self.key)?.map_or(0, |zset| zset.range_by_score(min, max).count());
+            return Ok(Some(DBValue::Int64(count as i64)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::ZCount(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for ZCountCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ZCount {} {} {}", &self.key, &self.min, &self.max)
+    }
+}
+
+impl TryFrom<Cmd> for ZCountCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::ZCount(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for ZRemRangeByScoreCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    fn may_grow_memory(&self) -> bool {
+        false
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let min = ScoreBound::parse(&self.min)?;
+            let max = ScoreBound::parse(&self.max)?;
+            let mut removed = 0usize;
+            if let Some(zset) = get_zset_mut(db, &self.key)? {
+                let members: Vec<String> = zset
+                    .range_by_score(min, max)
+                    .map(|(member, _)| String::from(member))
+                    .collect();
+                for member in members {
+                    zset.remove(&member);
+                    removed += 1;
+                }
+            }
+            remove_if_empty(db, &self.key);
+            return Ok(Some(DBValue::Int64(removed as i64)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::ZRemRangeByScore(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for ZRemRangeByScoreCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ZRemRangeByScore {} {} {}", &self.key, &self.min, &self.max)
+    }
+}
+
+impl TryFrom<Cmd> for ZRemRangeByScoreCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::ZRemRangeByScore(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for ZCardCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::READ
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let len = get_zset(db, &self.key)?.map_or(0, |zset| zset.len());
+            return Ok(Some(DBValue::Int64(len as i64)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::ZCard(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for ZCardCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ZCard {}", &self.key)
+    }
+}
+
+impl TryFrom<Cmd> for ZCardCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::ZCard(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::command::ExecutableCommand;
+    use crate::db::database::Database;
+    use crate::db::dbvalue::DBValue;
+    use crate::proto::{
+        SortedSetEntry, ZAddCmd, ZCardCmd, ZCountCmd, ZRangeBy, ZRangeCmd, ZRankCmd,
+        ZRemRangeByScoreCmd, ZRevRangeCmd, ZScoreCmd,
+    };
+
+    fn zadd(entries: &[(&str, f64)]) -> ZAddCmd {
+        ZAddCmd {
+            key: String::from("board"),
+            entries: entries
+                .iter()
+                .map(|(member, score)| SortedSetEntry {
+                    member: String::from(*member),
+                    score: *score,
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    async fn score(db: &mut Database, member: &str) -> Option<DBValue> {
+        let cmd = ZScoreCmd {
+            key: String::from("board"),
+            member: String::from(member),
+        };
+        cmd.execute(None, Some(db)).await.unwrap()
+    }
+
+    fn members(names: &[&str]) -> DBValue {
+        DBValue::List(
+            names
+                .iter()
+                .map(|name| DBValue::String(String::from(*name)))
+                .collect(),
+        )
+    }
+
+    #[tokio::test]
+    async fn zadd_options_test() {
+        let mut db = Database::new();
+        let added = zadd(&[("a", 1.0), ("b", 2.0)]);
+        assert!(added.execute(None, Some(&mut db)).await.unwrap() == Some(DBValue::Int64(2)));
+
+        // NX只添加新成员
+        let nx = ZAddCmd {
+            nx: true,
+            ..zadd(&[("a", 5.0), ("c", 3.0)])
+        };
+        assert!(nx.execute(None, Some(&mut db)).await.unwrap() == Some(DBValue::Int64(1)));
+        assert!(score(&mut db, "a").await == Some(DBValue::Float64(1.0)));
+
+        // XX只更新已存在的成员，CH时返回分数变化的成员数量
+        let xx = ZAddCmd {
+            xx: true,
+            ch: true,
+            ..zadd(&[("a", 5.0), ("d", 4.0)])
+        };
+        assert!(xx.execute(None, Some(&mut db)).await.unwrap() == Some(DBValue::Int64(1)));
+        assert!(score(&mut db, "a").await == Some(DBValue::Float64(5.0)));
+        assert!(score(&mut db, "d").await.is_none());
+
+        // GT只在分数变大时更新，LT只在分数变小时更新
+        let gt = ZAddCmd {
+            gt: true,
+            ch: true,
+            ..zadd(&[("a", 4.0), ("b", 6.0)])
+        };
+        assert!(gt.execute(None, Some(&mut db)).await.unwrap() == Some(DBValue::Int64(1)));
+        assert!(score(&mut db, "a").await == Some(DBValue::Float64(5.0)));
+        assert!(score(&mut db, "b").await == Some(DBValue::Float64(6.0)));
+        let lt = ZAddCmd {
+            lt: true,
+            ch: true,
+            ..zadd(&[("a", 4.0), ("b", 7.0), ("e", 1.0)])
+        };
+        assert!(lt.execute(None, Some(&mut db)).await.unwrap() == Some(DBValue::Int64(2)));
+        assert!(score(&mut db, "a").await == Some(DBValue::Float64(4.0)));
+        assert!(score(&mut db, "b").await == Some(DBValue::Float64(6.0)));
+        // 不带CH时只返回新增的成员数量
+        let unchanged = zadd(&[("a", 9.0)]);
+        assert!(unchanged.execute(None, Some(&mut db)).await.unwrap() == Some(DBValue::Int64(0)));
+
+        // 不兼容的选项组合和NaN分数
+        for invalid in [
+            ZAddCmd {
+                nx: true,
+                xx: true,
+                ..zadd(&[("a", 1.0)])
+            },
+            ZAddCmd {
+                gt: true,
+                lt: true,
+                ..zadd(&[("a", 1.0)])
+            },
+            ZAddCmd {
+                nx: true,
+                gt: true,
+                ..zadd(&[("a", 1.0)])
+            },
+            ZAddCmd {
+                nx: true,
+                lt: true,
+                ..zadd(&[("a", 1.0)])
+            },
+            zadd(&[("a", f64::NAN)]),
+        ] {
+            assert!(invalid.execute(None, Some(&mut db)).await.is_err());
+        }
+    }
+
+    #[tokio::test]
+    async fn zadd_missing_key_test() {
+        let mut db = Database::new();
+        let revision = db.revision();
+        // key不存在时XX和空成员列表不创建也不删除key
+        let xx = ZAddCmd {
+            xx: true,
+            ..zadd(&[("a", 1.0)])
+        };
+        assert!(xx.execute(None, Some(&mut db)).await.unwrap() == Some(DBValue::Int64(0)));
+        let empty = zadd(&[]);
+        assert!(empty.execute(None, Some(&mut db)).await.unwrap() == Some(DBValue::Int64(0)));
+        assert!(!db.contains_key("board"));
+        assert_eq!(db.revision(), revision);
+    }
+
+    #[tokio::test]
+    async fn range_and_rank_test() {
+        let mut db = Database::new();
+        let added = zadd(&[
+            ("alice", 30.0),
+            ("bob", 10.0),
+            ("carol", 20.0),
+            ("dave", 20.0),
+        ]);
+        added.execute(None, Some(&mut db)).await.unwrap();
+
+        let by_rank = ZRangeCmd {
+            key: String::from("board"),
+            start: String::from("1"),
+            stop: String::from("-1"),
+            ..Default::default()
+        };
+        let result = by_rank.execute(None, Some(&mut db)).await.unwrap();
+        assert!(result == Some(members(&["carol", "dave", "alice"])));
+        let with_scores = ZRangeCmd {
+            start: String::from("0"),
+            stop: String::from("0"),
+            rev: true,
+            with_scores: true,
+            ..by_rank.clone()
+        };
+        let result = with_scores.execute(None, Some(&mut db)).await.unwrap();
+        assert!(
+            result
+                == Some(DBValue::List(
+                    vec![
+                        DBValue::String(String::from("alice")),
+                        DBValue::Float64(30.0)
+                    ]
+                    .into()
+                ))
+        );
+
+        let by_score = ZRangeCmd {
+            key: String::from("board"),
+            start: String::from("(10"),
+            stop: String::from("+inf"),
+            by: ZRangeBy::Score as i32,
+            offset: 1,
+            limit: 1,
+            ..Default::default()
+        };
+        let result = by_score.execute(None, Some(&mut db)).await.unwrap();
+        assert!(result == Some(members(&["dave"])));
+        let rev_by_score = ZRevRangeCmd {
+            key: String::from("board"),
+            start: String::from("20"),
+            stop: String::from("-inf"),
+            by: ZRangeBy::Score as i32,
+            ..Default::default()
+        };
+        let result = rev_by_score.execute(None, Some(&mut db)).await.unwrap();
+        assert!(result == Some(members(&["dave", "carol", "bob"])));
+        let by_lex = ZRangeCmd {
+            key: String::from("board"),
+            start: String::from("[b"),
+            stop: String::from("(d"),
+            by: ZRangeBy::Lex as i32,
+            ..Default::default()
+        };
+        let result = by_lex.execute(None, Some(&mut db)).await.unwrap();
+        assert!(result == Some(members(&["bob", "carol"])));
+
+        let rank = ZRankCmd {
+            key: String::from("board"),
+            member: String::from("dave"),
+            rev: false,
+        };
+        let result = rank.execute(None, Some(&mut db)).await.unwrap();
+        assert!(result == Some(DBValue::Int64(2)));
+        let rev_rank = ZRankCmd { rev: true, ..rank };
+        let result = rev_rank.execute(None, Some(&mut db)).await.unwrap();
+        assert!(result == Some(DBValue::Int64(1)));
+
+        let count = ZCountCmd {
+            key: String::from("board"),
+            min: String::from("20"),
+            max: String::from("(30"),
+        };
+        let result = count.execute(None, Some(&mut db)).await.unwrap();
+        assert!(result == Some(DBValue::Int64(2)));
+        let remove = ZRemRangeByScoreCmd {
+            key: String::from("board"),
+            min: String::from("-inf"),
+            max: String::from("20"),
+        };
+        let result = remove.execute(None, Some(&mut db)).await.unwrap();
+        assert!(result == Some(DBValue::Int64(3)));
+        let card = ZCardCmd {
+            key: String::from("board"),
+        };
+        let result = card.execute(None, Some(&mut db)).await.unwrap();
+        assert!(result == Some(DBValue::Int64(1)));
+    }
+}
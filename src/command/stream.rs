@@ -0,0 +1,938 @@
+use super::{BlockedError, CommandType, ExecutableCommand};
+use crate::db::stream::{Stream, StreamFields, StreamId};
+use crate::db::{database::Database, dbvalue::DBValue};
+use crate::proto::command_message::Cmd;
+use crate::proto::{
+    XAckCmd, XAddCmd, XClaimCmd, XGroupCreateCmd, XLenCmd, XPendingCmd, XRangeCmd, XReadCmd,
+    XReadGroupCmd, XRevRangeCmd, XTrimCmd,
+};
+use crate::runtime::Runtime;
+use anyhow::anyhow;
+use async_trait::async_trait;
+use std::any::Any;
+use std::collections::VecDeque;
+use std::fmt::Display;
+
+fn get_stream<'a>(db: &'a Database, key: &str) -> anyhow::Result<Option<&'a Stream>> {
+    match db.get(key) {
+        Some(DBValue::Stream(stream)) => Ok(Some(stream)),
+        Some(value) => Err(anyhow!(
+            "Mismatch DBValue type, required Stream but got {}",
+            value
+        )),
+        None => Ok(None),
+    }
+}
+
+fn get_stream_mut<'a>(db: &'a mut Database, key: &str) -> anyhow::Result<Option<&'a mut Stream>> {
+    match db.get_mut(key) {
+        Some(DBValue::Stream(stream)) => Ok(Some(stream)),
+        Some(value) => Err(anyhow!(
+            "Mismatch DBValue type, required Stream but got {}",
+            value
+        )),
+        None => Ok(None),
+    }
+}
+
+// 0表示不限制数量
+fn limit(count: u64) -> usize {
+    if count == 0 {
+        usize::MAX
+    } else {
+        count as usize
+    }
+}
+
+fn parse_ids(ids: &[String]) -> anyhow::Result<Vec<StreamId>> {
+    ids.iter().map(|id| StreamId::parse(id)).collect()
+}
+
+// 消息回复为[id, [field, value, ...]]
+fn entry_reply(id: &StreamId, fields: &StreamFields) -> DBValue {
+    let mut values = VecDeque::with_capacity(fields.len() * 2);
+    for (name, value) in fields {
+        values.push_back(DBValue::String(name.clone()));
+        values.push_back(value.clone());
+    }
+    DBValue::List(VecDeque::from(vec![
+        DBValue::String(id.to_string()),
+        DBValue::List(values),
+    ]))
+}
+
+fn entries_reply<'a>(entries: impl Iterator<Item = (&'a StreamId, &'a StreamFields)>) -> DBValue {
+    DBValue::List(
+        entries
+            .map(|(id, fields)| entry_reply(id, fields))
+            .collect(),
+    )
+}
+
+// 多个流的读取结果回复为[[key, entries], ...]，所有流都没有消息时返回None
+fn read_reply(results: Vec<(String, DBValue)>) -> Option<DBValue> {
+    if results.is_empty() {
+        return None;
+    }
+    Some(DBValue::List(
+        results
+            .into_iter()
+            .map(|(key, entries)| {
+                DBValue::List(VecDeque::from(vec![DBValue::String(key), entries]))
+            })
+            .collect(),
+    ))
+}
+
+fn check_keys(keys: &[String], ids: &[String]) -> anyhow::Result<()> {
+    if keys.is_empty() || keys.len() != ids.len() {
+        return Err(anyhow!(
+            "Unbalanced stream list of streams: for each stream key an ID must be specified"
+        ));
+    }
+    Ok(())
+}
+
+#[async_trait]
+impl ExecutableCommand for XAddCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            if self.fields.is_empty() {
+                return Err(anyhow!("XADD requires at least one field"));
+            }
+            let fields: StreamFields = self
+                .fields
+                .iter()
+                .map(|field| {
+                    let value = field.value.clone().map_or(DBValue::None, DBValue::from);
+                    (field.name.clone(), value)
+                })
+                .collect();
+            let (now, index) = (db.now(), db.index());
+            let created = get_stream(db, &self.key)?.is_none();
+            if created {
+                db.set(self.key.clone(), DBValue::Stream(Stream::new()));
+            }
+            let Some(stream) = get_stream_mut(db, &self.key)? else {
+                return Ok(None);
+            };
+            // 自动生成的ID来自命令时间戳和raft日志索引，各副本生成相同的ID
+            let id = match self.id.as_str() {
+                "" | "*" => stream.next_id(now, index)?,
+                id => StreamId::parse(id)?,
+            };
+            if let Err(err) = stream.add(id, fields) {
+                if created {
+                    db.remove(&self.key);
+                }
+                return Err(err);
+            }
+            if self.max_len > 0 {
+                stream.trim_len(self.max_len as usize);
+            }
+            db.signal_ready(&self.key);
+            return Ok(Some(DBValue::String(id.to_string())));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::XAdd(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for XAddCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "XAdd {} id={} ({} fields)",
+            &self.key,
+            &self.id,
+            self.fields.len()
+        )
+    }
+}
+
+impl TryFrom<Cmd> for XAddCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::XAdd(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for XRangeCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::READ
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let start = StreamId::parse_start(&self.start)?;
+            let end = StreamId::parse_end(&self.end)?;
+            let reply = match get_stream(db, &self.key)? {
+                Some(stream) => entries_reply(stream.range(start, end).take(limit(self.count))),
+                None => DBValue::List(VecDeque::new()),
+            };
+            return Ok(Some(reply));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::XRange(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for XRangeCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "XRange {} {} {}", &self.key, &self.start, &self.end)
+    }
+}
+
+impl TryFrom<Cmd> for XRangeCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::XRange(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for XRevRangeCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::READ
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let start = StreamId::parse_start(&self.start)?;
+            let end = StreamId::parse_end(&self.end)?;
+            let reply = match get_stream(db, &self.key)? {
+                Some(stream) => {
+                    entries_reply(stream.range(start, end).rev().take(limit(self.count)))
+                }
+                None => DBValue::List(VecDeque::new()),
+            };
+            return Ok(Some(reply));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::XRevRange(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for XRevRangeCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "XRevRange {} {} {}", &self.key, &self.end, &self.start)
+    }
+}
+
+impl TryFrom<Cmd> for XRevRangeCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::XRevRange(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for XLenCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::READ
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let len = get_stream(db, &self.key)?.map_or(0, |stream| stream.len());
+            return Ok(Some(DBValue::Int64(len as i64)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::XLen(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for XLenCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "XLen {}", &self.key)
+    }
+}
+
+impl TryFrom<Cmd> for XLenCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::XLen(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for XReadCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::READ
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            check_keys(&self.keys, &self.ids)?;
+            let mut after = Vec::with_capacity(self.ids.len());
+            for (key, id) in self.keys.iter().zip(self.ids.iter()) {
+                let id = match id.as_str() {
+                    "$" => get_stream(db, key)?.map_or(StreamId::MIN, |stream| stream.last_id()),
+                    id => StreamId::parse(id)?,
+                };
+                after.push(id);
+            }
+            let mut results = Vec::new();
+            for (key, id) in self.keys.iter().zip(after.iter()) {
+                if let Some(stream) = get_stream(db, key)? {
+                    let mut entries = stream.after(*id).take(limit(self.count)).peekable();
+                    if entries.peek().is_some() {
+                        results.push((key.clone(), entries_reply(entries)));
+                    }
+                }
+            }
+            if results.is_empty() && self.block {
+                // $在阻塞时解析为当前的最后ID，唤醒后读取阻塞期间写入的消息
+                let retry = XReadCmd {
+                    ids: after.iter().map(|id| id.to_string()).collect(),
+                    ..self.clone()
+                };
+                return Err(BlockedError {
+                    keys: self.keys.clone(),
+                    timeout_ms: self.timeout_ms,
+                    retry: Some(Cmd::XRead(retry)),
+                }
+                .into());
+            }
+            return Ok(read_reply(results));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::XRead(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for XReadCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "XRead [{}] ids=[{}] block={}",
+            self.keys.join(","),
+            self.ids.join(","),
+            self.block
+        )
+    }
+}
+
+impl TryFrom<Cmd> for XReadCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::XRead(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for XTrimCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    fn may_grow_memory(&self) -> bool {
+        false
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let now = db.now();
+            let Some(stream) = get_stream_mut(db, &self.key)? else {
+                return Ok(Some(DBValue::Int64(0)));
+            };
+            let mut removed = 0;
+            if self.max_len > 0 {
+                removed += stream.trim_len(self.max_len as usize);
+            }
+            if self.max_age_ms > 0 {
+                let min_ms = now.saturating_sub(self.max_age_ms as u128) as u64;
+                removed += stream.trim_before(StreamId::new(min_ms, 0));
+            }
+            return Ok(Some(DBValue::Int64(removed as i64)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::XTrim(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for XTrimCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "XTrim {} max_len={} max_age={}ms",
+            &self.key, self.max_len, self.max_age_ms
+        )
+    }
+}
+
+impl TryFrom<Cmd> for XTrimCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::XTrim(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for XGroupCreateCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            if get_stream(db, &self.key)?.is_none() {
+                if !self.mkstream {
+                    return Err(anyhow!(
+                        "The XGROUP subcommand requires the key to exist, use mkstream to create an empty stream"
+                    ));
+                }
+                db.set(self.key.clone(), DBValue::Stream(Stream::new()));
+            }
+            let Some(stream) = get_stream_mut(db, &self.key)? else {
+                return Ok(None);
+            };
+            let last_delivered = match self.id.as_str() {
+                "$" => stream.last_id(),
+                id => StreamId::parse(id)?,
+            };
+            if !stream.create_group(&self.group, last_delivered) {
+                return Err(anyhow!("Consumer group {} already exists", &self.group));
+            }
+            return Ok(Some(DBValue::Boolean(true)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::XGroupCreate(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for XGroupCreateCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "XGroupCreate {} {} {}", &self.key, &self.group, &self.id)
+    }
+}
+
+impl TryFrom<Cmd> for XGroupCreateCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::XGroupCreate(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for XReadGroupCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            check_keys(&self.keys, &self.ids)?;
+            let now = db.now();
+            let mut results = Vec::new();
+            for (key, id) in self.keys.iter().zip(self.ids.iter()) {
+                let Some(stream) = get_stream_mut(db, key)? else {
+                    return Err(anyhow!(
+                        "No such key {} or consumer group {}",
+                        key,
+                        &self.group
+                    ));
+                };
+                let entries = match id.as_str() {
+                    ">" => stream.read_group(
+                        &self.group,
+                        &self.consumer,
+                        limit(self.count),
+                        now,
+                        self.noack,
+                    )?,
+                    id => {
+                        let after = StreamId::parse(id)?;
+                        stream.read_pending(
+                            &self.group,
+                            &self.consumer,
+                            after,
+                            limit(self.count),
+                        )?
+                    }
+                };
+                if !entries.is_empty() {
+                    let reply = entries_reply(entries.iter().map(|(id, fields)| (id, fields)));
+                    results.push((key.clone(), reply));
+                }
+            }
+            // 只有读取新消息时阻塞，读取待确认消息立即返回
+            if results.is_empty() && self.block && self.ids.iter().all(|id| id == ">") {
+                return Err(BlockedError {
+                    keys: self.keys.clone(),
+                    timeout_ms: self.timeout_ms,
+                    retry: None,
+                }
+                .into());
+            }
+            return Ok(read_reply(results));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::XReadGroup(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for XReadGroupCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "XReadGroup {} {} [{}] ids=[{}] block={}",
+            &self.group,
+            &self.consumer,
+            self.keys.join(","),
+            self.ids.join(","),
+            self.block
+        )
+    }
+}
+
+impl TryFrom<Cmd> for XReadGroupCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::XReadGroup(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for XAckCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    fn may_grow_memory(&self) -> bool {
+        false
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let ids = parse_ids(&self.ids)?;
+            let acked = match get_stream_mut(db, &self.key)? {
+                Some(stream) => stream.ack(&self.group, &ids)?,
+                None => 0,
+            };
+            return Ok(Some(DBValue::Int64(acked as i64)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::XAck(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for XAckCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "XAck {} {} [{}]",
+            &self.key,
+            &self.group,
+            self.ids.join(",")
+        )
+    }
+}
+
+impl TryFrom<Cmd> for XAckCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::XAck(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for XPendingCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::READ
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let now = db.now();
+            let Some(stream) = get_stream(db, &self.key)? else {
+                return Err(anyhow!(
+                    "No such key {} or consumer group {}",
+                    &self.key,
+                    &self.group
+                ));
+            };
+            // 每条待确认消息回复为[id, consumer, idle_ms, deliveries]
+            let pending = stream
+                .group(&self.group)?
+                .pending
+                .iter()
+                .filter(|(_, entry)| self.consumer.is_empty() || entry.consumer == self.consumer)
+                .take(limit(self.count))
+                .map(|(id, entry)| {
+                    let idle = now.saturating_sub(entry.delivered_at);
+                    DBValue::List(VecDeque::from(vec![
+                        DBValue::String(id.to_string()),
+                        DBValue::String(entry.consumer.clone()),
+                        DBValue::Int64(idle as i64),
+                        DBValue::Int64(entry.deliveries as i64),
+                    ]))
+                })
+                .collect();
+            return Ok(Some(DBValue::List(pending)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::XPending(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for XPendingCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "XPending {} {} {}",
+            &self.key, &self.group, &self.consumer
+        )
+    }
+}
+
+impl TryFrom<Cmd> for XPendingCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::XPending(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for XClaimCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    fn may_grow_memory(&self) -> bool {
+        false
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let ids = parse_ids(&self.ids)?;
+            let now = db.now();
+            let Some(stream) = get_stream_mut(db, &self.key)? else {
+                return Err(anyhow!(
+                    "No such key {} or consumer group {}",
+                    &self.key,
+                    &self.group
+                ));
+            };
+            let claimed = stream.claim(&self.group, &self.consumer, self.min_idle_ms, &ids, now)?;
+            let reply = entries_reply(claimed.iter().map(|(id, fields)| (id, fields)));
+            return Ok(Some(reply));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::XClaim(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for XClaimCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "XClaim {} {} {} min_idle={}ms [{}]",
+            &self.key,
+            &self.group,
+            &self.consumer,
+            self.min_idle_ms,
+            self.ids.join(",")
+        )
+    }
+}
+
+impl TryFrom<Cmd> for XClaimCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::XClaim(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::command::{BlockedError, ExecutableCommand};
+    use crate::db::database::Database;
+    use crate::db::dbvalue::DBValue;
+    use crate::proto::command_message::Cmd;
+    use crate::proto::{StreamField, XAckCmd, XAddCmd, XGroupCreateCmd, XReadCmd, XReadGroupCmd};
+
+    fn xadd(key: &str, value: &str) -> XAddCmd {
+        XAddCmd {
+            key: String::from(key),
+            fields: vec![StreamField {
+                name: String::from("event"),
+                value: Some(DBValue::String(String::from(value)).to_protobuf()),
+            }],
+            ..Default::default()
+        }
+    }
+
+    fn reply_id(value: Option<DBValue>) -> String {
+        match value {
+            Some(DBValue::String(id)) => id,
+            _ => panic!("xadd should reply id"),
+        }
+    }
+
+    #[tokio::test]
+    async fn xadd_and_xread_test() {
+        let mut db = Database::new();
+        db.set_clock(1_000);
+        db.set_index(7);
+        let id = reply_id(
+            xadd("events", "a")
+                .execute(None, Some(&mut db))
+                .await
+                .unwrap(),
+        );
+        assert_eq!(id, "1000-7");
+
+        // 阻塞读取时$被解析为当前的最后ID
+        let read = XReadCmd {
+            keys: vec![String::from("events")],
+            ids: vec![String::from("$")],
+            block: true,
+            ..Default::default()
+        };
+        let retry = match read.execute(None, Some(&mut db)).await {
+            Err(err) => err.downcast::<BlockedError>().unwrap().retry,
+            Ok(_) => panic!("xread without new entries should block"),
+        };
+        let Some(Cmd::XRead(retry)) = retry else {
+            panic!("xread should retry with resolved ids");
+        };
+        assert_eq!(retry.ids, vec![String::from("1000-7")]);
+
+        db.set_index(8);
+        xadd("events", "b")
+            .execute(None, Some(&mut db))
+            .await
+            .unwrap();
+        match retry.execute(None, Some(&mut db)).await.unwrap() {
+            Some(DBValue::List(streams)) => assert_eq!(streams.len(), 1),
+            _ => panic!("xread should reply new entries"),
+        }
+    }
+
+    #[tokio::test]
+    async fn consumer_group_test() {
+        let mut db = Database::new();
+        let create = XGroupCreateCmd {
+            key: String::from("jobs"),
+            group: String::from("workers"),
+            id: String::from("$"),
+            mkstream: false,
+        };
+        assert!(create.execute(None, Some(&mut db)).await.is_err());
+        let create = XGroupCreateCmd {
+            mkstream: true,
+            ..create
+        };
+        create.execute(None, Some(&mut db)).await.unwrap();
+        db.set_clock(1_000);
+        let id = reply_id(
+            xadd("jobs", "a")
+                .execute(None, Some(&mut db))
+                .await
+                .unwrap(),
+        );
+
+        let read = XReadGroupCmd {
+            group: String::from("workers"),
+            consumer: String::from("w1"),
+            keys: vec![String::from("jobs")],
+            ids: vec![String::from(">")],
+            block: true,
+            ..Default::default()
+        };
+        assert!(read.execute(None, Some(&mut db)).await.unwrap().is_some());
+        // 没有新消息时阻塞
+        assert!(read.execute(None, Some(&mut db)).await.is_err());
+        let ack = XAckCmd {
+            key: String::from("jobs"),
+            group: String::from("workers"),
+            ids: vec![id],
+        };
+        match ack.execute(None, Some(&mut db)).await.unwrap() {
+            Some(DBValue::Int64(acked)) => assert_eq!(acked, 1),
+            _ => panic!("xack should reply count"),
+        }
+    }
+}
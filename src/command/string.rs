@@ -0,0 +1,787 @@
+use super::{CommandType, ExecutableCommand};
+use crate::db::{database::Database, dbvalue::DBValue};
+use crate::proto::command_message::Cmd;
+use crate::proto::DbValue as PDbValue;
+use crate::proto::{
+    AppendCmd, GetCmd, GetRangeCmd, GetSetCmd, KeyValue, MGetCmd, MSetCmd, MSetNxCmd, SetCmd,
+    SetRangeCmd, StrlenCmd,
+};
+use crate::runtime::Runtime;
+use crate::until;
+use anyhow::anyhow;
+use async_trait::async_trait;
+use std::any::Any;
+use std::fmt::Display;
+
+// 字符串值的最大长度512MB
+const MAX_STRING_SIZE: u64 = 512 << 20;
+
+// 在长度为len的字符串之后写入added字节，写入后的长度不能超过上限
+fn check_string_size(len: u64, added: usize) -> anyhow::Result<()> {
+    match len.checked_add(added as u64) {
+        Some(end) if end <= MAX_STRING_SIZE => Ok(()),
+        _ => Err(anyhow!("String exceeds maximum allowed size (512MB)")),
+    }
+}
+
+// 字符串类命令只接受String或者Bytes类型的值
+fn require_string_value(value: &Option<PDbValue>) -> anyhow::Result<DBValue> {
+    match value {
+        Some(value) => match DBValue::from(value.clone()) {
+            v @ (DBValue::String(_) | DBValue::Bytes(_)) => Ok(v),
+            v => Err(anyhow!(
+                "Mismatch DBValue type, required String or Bytes but got {}",
+                v
+            )),
+        },
+        None => Err(anyhow!("Missing value")),
+    }
+}
+
+// 校验批量写入的所有值，任意一个值的类型不匹配时返回错误
+fn require_string_entries(entries: &[KeyValue]) -> anyhow::Result<Vec<(String, DBValue)>> {
+    entries
+        .iter()
+        .map(|entry| Ok((entry.key.clone(), require_string_value(&entry.value)?)))
+        .collect()
+}
+
+fn value_bytes(value: &DBValue) -> &[u8] {
+    match value {
+        DBValue::String(s) => s.as_bytes(),
+        DBValue::Bytes(b) => &b[..],
+        _ => &[],
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for GetCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::READ
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            return match db.get(&self.key) {
+                Some(value @ (DBValue::String(_) | DBValue::Bytes(_))) => Ok(Some(value.clone())),
+                Some(value) => Err(anyhow!(
+                    "Mismatch DBValue type, required String or Bytes but got {}",
+                    value
+                )),
+                None => Ok(None),
+            };
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::Get(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for GetCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Get {}", &self.key)
+    }
+}
+
+impl TryFrom<Cmd> for GetCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::Get(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for SetCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let value = require_string_value(&self.value)?;
+            if self.ephemeral {
+                db.require_session()?;
+            }
+            db.set(self.key.clone(), value);
+            if self.ephemeral {
+                db.mark_ephemeral(&self.key);
+            }
+            if self.expire_ms > 0 {
+                let deadline = db.now() + self.expire_ms as u128;
+                db.expire_at(&self.key, deadline);
+            }
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::Set(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for SetCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(value) = &self.value {
+            write!(f, "Set {} {}", &self.key, value)
+        } else {
+            write!(f, "Set {} None", &self.key)
+        }
+    }
+}
+
+impl TryFrom<Cmd> for SetCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::Set(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for GetSetCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let value = require_string_value(&self.value)?;
+            if let Some(old) = db.get(&self.key) {
+                if !matches!(old, DBValue::String(_) | DBValue::Bytes(_)) {
+                    return Err(anyhow!(
+                        "Mismatch DBValue type, required String or Bytes but got {}",
+                        old
+                    ));
+                }
+            }
+            return Ok(db.set(self.key.clone(), value));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::GetSet(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for GetSetCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(value) = &self.value {
+            write!(f, "GetSet {} {}", &self.key, value)
+        } else {
+            write!(f, "GetSet {} None", &self.key)
+        }
+    }
+}
+
+impl TryFrom<Cmd> for GetSetCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::GetSet(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for AppendCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let value = require_string_value(&self.value)?;
+            let added = value_bytes(&value).len();
+            let len = match db.get_mut(&self.key) {
+                Some(DBValue::String(s)) => {
+                    check_string_size(s.len() as u64, added)?;
+                    match value {
+                        DBValue::String(v) => s.push_str(&v),
+                        DBValue::Bytes(v) => s.push_str(std::str::from_utf8(&v)?),
+                        _ => {}
+                    }
+                    s.len()
+                }
+                Some(DBValue::Bytes(b)) => {
+                    check_string_size(b.len() as u64, added)?;
+                    b.extend_from_slice(value_bytes(&value));
+                    b.len()
+                }
+                Some(other) => {
+                    return Err(anyhow!(
+                        "Mismatch DBValue type, required String or Bytes but got {}",
+                        other
+                    ))
+                }
+                None => {
+                    check_string_size(0, added)?;
+                    db.set(self.key.clone(), value);
+                    added
+                }
+            };
+            return Ok(Some(DBValue::Int64(len as i64)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::Append(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for AppendCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(value) = &self.value {
+            write!(f, "Append {} {}", &self.key, value)
+        } else {
+            write!(f, "Append {} None", &self.key)
+        }
+    }
+}
+
+impl TryFrom<Cmd> for AppendCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::Append(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for StrlenCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::READ
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let len = match db.get(&self.key) {
+                Some(value @ (DBValue::String(_) | DBValue::Bytes(_))) => value_bytes(value).len(),
+                Some(value) => {
+                    return Err(anyhow!(
+                        "Mismatch DBValue type, required String or Bytes but got {}",
+                        value
+                    ))
+                }
+                None => 0,
+            };
+            return Ok(Some(DBValue::Int64(len as i64)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::Strlen(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for StrlenCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Strlen {}", &self.key)
+    }
+}
+
+impl TryFrom<Cmd> for StrlenCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::Strlen(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for SetRangeCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let value = require_string_value(&self.value)?;
+            let patch = value_bytes(&value);
+            // 限制写入后的长度，避免偏移过大时溢出或者分配过多内存
+            check_string_size(self.offset, patch.len())?;
+            let offset = self.offset as usize;
+            let current = match db.get(&self.key) {
+                Some(value @ (DBValue::String(_) | DBValue::Bytes(_))) => value.clone(),
+                Some(value) => {
+                    return Err(anyhow!(
+                        "Mismatch DBValue type, required String or Bytes but got {}",
+                        value
+                    ))
+                }
+                // key不存在时按照传入值的类型创建
+                None => match value {
+                    DBValue::String(_) => DBValue::String(String::new()),
+                    _ => DBValue::Bytes(Vec::new()),
+                },
+            };
+            let mut buf = value_bytes(&current).to_vec();
+            if !patch.is_empty() {
+                if buf.len() < offset + patch.len() {
+                    // 不足的部分用0填充
+                    buf.resize(offset + patch.len(), 0);
+                }
+                buf[offset..offset + patch.len()].copy_from_slice(patch);
+            }
+            let len = buf.len();
+            let updated = match current {
+                DBValue::String(_) => DBValue::String(
+                    String::from_utf8(buf)
+                        .map_err(|_| anyhow!("SetRange result is not a valid UTF-8 string"))?,
+                ),
+                _ => DBValue::Bytes(buf),
+            };
+            if len > 0 {
+                db.replace(self.key.clone(), updated);
+            }
+            return Ok(Some(DBValue::Int64(len as i64)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::SetRange(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for SetRangeCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(value) = &self.value {
+            write!(f, "SetRange {} {} {}", &self.key, self.offset, value)
+        } else {
+            write!(f, "SetRange {} {} None", &self.key, self.offset)
+        }
+    }
+}
+
+impl TryFrom<Cmd> for SetRangeCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::SetRange(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for GetRangeCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::READ
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            return match db.get(&self.key) {
+                Some(value @ (DBValue::String(_) | DBValue::Bytes(_))) => {
+                    let bytes = value_bytes(value);
+                    let slice = match until::normalize_range(self.start, self.end, bytes.len()) {
+                        Some((start, end)) => &bytes[start..=end],
+                        None => &[],
+                    };
+                    match value {
+                        DBValue::String(_) => Ok(Some(DBValue::String(
+                            String::from_utf8_lossy(slice).to_string(),
+                        ))),
+                        _ => Ok(Some(DBValue::Bytes(slice.to_vec()))),
+                    }
+                }
+                Some(value) => Err(anyhow!(
+                    "Mismatch DBValue type, required String or Bytes but got {}",
+                    value
+                )),
+                None => Ok(None),
+            };
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::GetRange(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for GetRangeCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "GetRange {} {} {}", &self.key, self.start, self.end)
+    }
+}
+
+impl TryFrom<Cmd> for GetRangeCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::GetRange(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for MGetCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::READ
+    }
+
+    fn may_grow_memory(&self) -> bool {
+        false
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            // 不存在或者类型不匹配的key返回None
+            let values = self
+                .keys
+                .iter()
+                .map(|key| match db.get(key) {
+                    Some(value @ (DBValue::String(_) | DBValue::Bytes(_))) => value.clone(),
+                    _ => DBValue::None,
+                })
+                .collect::<Vec<_>>();
+            return Ok(Some(DBValue::List(values.into())));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::MGet(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for MGetCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "MGet {}", self.keys.join(" "))
+    }
+}
+
+impl TryFrom<Cmd> for MGetCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::MGet(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for MSetCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            // 先校验所有的值，避免只写入部分key
+            let values = require_string_entries(&self.entries)?;
+            for (key, value) in values {
+                db.set(key, value);
+            }
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::MSet(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for MSetCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let keys: Vec<&str> = self.entries.iter().map(|e| e.key.as_str()).collect();
+        write!(f, "MSet {}", keys.join(" "))
+    }
+}
+
+impl TryFrom<Cmd> for MSetCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::MSet(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutableCommand for MSetNxCmd {
+    fn cmd_type(&self) -> CommandType {
+        CommandType::WRITE
+    }
+
+    async fn execute(
+        &self,
+        _app: Option<&Runtime>,
+        db: Option<&mut Database>,
+    ) -> anyhow::Result<Option<DBValue>> {
+        if let Some(db) = db {
+            let values = require_string_entries(&self.entries)?;
+            if values.iter().any(|(key, _)| db.contains_key(key)) {
+                return Ok(Some(DBValue::Boolean(false)));
+            }
+            for (key, value) in values {
+                db.set(key, value);
+            }
+            return Ok(Some(DBValue::Boolean(true)));
+        }
+        Ok(None)
+    }
+
+    fn to_cmd(&self) -> anyhow::Result<Cmd> {
+        Ok(Cmd::MSetNx(self.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for MSetNxCmd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let keys: Vec<&str> = self.entries.iter().map(|e| e.key.as_str()).collect();
+        write!(f, "MSetNx {}", keys.join(" "))
+    }
+}
+
+impl TryFrom<Cmd> for MSetNxCmd {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Cmd) -> Result<Self, Self::Error> {
+        if let Cmd::MSetNx(cmd) = value {
+            Ok(cmd)
+        } else {
+            Err(anyhow!("invalid command"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{check_string_size, MAX_STRING_SIZE};
+    use crate::command::ExecutableCommand;
+    use crate::db::{database::Database, dbvalue::DBValue};
+    use crate::proto::{
+        AppendCmd, GetCmd, GetRangeCmd, KeyValue, MGetCmd, MSetCmd, MSetNxCmd, SetCmd, SetRangeCmd,
+    };
+
+    fn string_value(s: &str) -> Option<crate::proto::DbValue> {
+        Some(DBValue::String(String::from(s)).into())
+    }
+
+    #[tokio::test]
+    async fn append_and_range_test() {
+        let mut db = Database::new();
+        let key = String::from("greeting");
+        let set = SetCmd {
+            key: key.clone(),
+            value: string_value("Hello"),
+            expire_ms: 0,
+            ephemeral: false,
+        };
+        set.execute(None, Some(&mut db)).await.unwrap();
+        let append = AppendCmd {
+            key: key.clone(),
+            value: string_value(" World"),
+        };
+        let len = append.execute(None, Some(&mut db)).await.unwrap();
+        assert!(matches!(len, Some(DBValue::Int64(11))));
+
+        let range = GetRangeCmd {
+            key: key.clone(),
+            start: -5,
+            end: -1,
+        };
+        let value = range.execute(None, Some(&mut db)).await.unwrap();
+        assert!(matches!(value, Some(DBValue::String(ref s)) if s == "World"));
+
+        let set_range = SetRangeCmd {
+            key: key.clone(),
+            offset: 6,
+            value: string_value("Redis"),
+        };
+        set_range.execute(None, Some(&mut db)).await.unwrap();
+        let get = GetCmd { key: key.clone() };
+        let value = get.execute(None, Some(&mut db)).await.unwrap();
+        assert!(matches!(value, Some(DBValue::String(ref s)) if s == "Hello Redis"));
+    }
+
+    #[tokio::test]
+    async fn set_range_pads_missing_key_test() {
+        let mut db = Database::new();
+        let set_range = SetRangeCmd {
+            key: String::from("bytes"),
+            offset: 2,
+            value: Some(DBValue::Bytes(vec![1, 2]).into()),
+        };
+        set_range.execute(None, Some(&mut db)).await.unwrap();
+        assert!(matches!(db.get("bytes"), Some(DBValue::Bytes(b)) if b == &vec![0, 0, 1, 2]));
+
+        for offset in [u64::MAX, 512 << 20] {
+            let set_range = SetRangeCmd {
+                key: String::from("bytes"),
+                offset,
+                value: Some(DBValue::Bytes(vec![1]).into()),
+            };
+            assert!(set_range.execute(None, Some(&mut db)).await.is_err());
+        }
+    }
+
+    #[test]
+    fn append_size_limit_test() {
+        // APPEND与SETRANGE使用相同的长度上限
+        assert!(check_string_size(MAX_STRING_SIZE - 1, 1).is_ok());
+        assert!(check_string_size(MAX_STRING_SIZE, 1).is_err());
+        assert!(check_string_size(u64::MAX, 1).is_err());
+        assert!(check_string_size(0, MAX_STRING_SIZE as usize + 1).is_err());
+    }
+
+    #[tokio::test]
+    async fn multi_key_test() {
+        let mut db = Database::new();
+        let entry = |key: &str, value: &str| KeyValue {
+            key: String::from(key),
+            value: string_value(value),
+        };
+        let mset = MSetCmd {
+            entries: vec![entry("user:1", "alice"), entry("user:2", "bob")],
+        };
+        mset.execute(None, Some(&mut db)).await.unwrap();
+        let msetnx = MSetNxCmd {
+            entries: vec![entry("user:2", "carol"), entry("user:3", "dave")],
+        };
+        let result = msetnx.execute(None, Some(&mut db)).await.unwrap();
+        assert!(result == Some(DBValue::Boolean(false)));
+
+        let mget = MGetCmd {
+            keys: vec![
+                String::from("user:1"),
+                String::from("user:2"),
+                String::from("user:3"),
+            ],
+        };
+        let result = mget.execute(None, Some(&mut db)).await.unwrap();
+        let expected = vec![
+            DBValue::String(String::from("alice")),
+            DBValue::String(String::from("bob")),
+            DBValue::None,
+        ];
+        assert!(result == Some(DBValue::List(expected.into())));
+
+        // 任意一个值类型不匹配时不写入
+        let invalid = MSetCmd {
+            entries: vec![
+                entry("user:4", "erin"),
+                KeyValue {
+                    key: String::from("user:5"),
+                    value: Some(DBValue::Int64(1).into()),
+                },
+            ],
+        };
+        assert!(invalid.execute(None, Some(&mut db)).await.is_err());
+        assert!(!db.contains_key("user:4"));
+    }
+}
@@ -2,6 +2,8 @@ use std::hash::{DefaultHasher, Hash, Hasher};
 use std::time::Duration;
 use uuid::Uuid;
 
+use crate::db::eviction::EvictionPolicy;
+
 #[derive(Clone)]
 pub struct Config {
     // 节点ID
@@ -21,6 +23,31 @@ pub struct Config {
     // raft配置
     pub raft_config: raft::prelude::Config,
     pub raft_loop_interval: Duration,
+
+    // 数据库定时任务执行间隔（阻塞命令超时检查等）
+    pub db_cron_interval: Duration,
+    // 定时任务发起的提案超时未应用时(如leader切换导致提案丢失)重新发起
+    pub cron_proposal_timeout: Duration,
+    // 每次主动过期最多删除的key数量
+    pub active_expire_limit: u32,
+    // hash成员全部过期后是否删除hash本身
+    pub remove_empty_hash: bool,
+    // 内存上限(字节)，0表示不限制
+    pub max_memory: usize,
+    // 内存超限时的淘汰策略
+    pub eviction_policy: EvictionPolicy,
+    // 获取锁未指定租约时使用的默认租约
+    pub lock_lease: Duration,
+    // 创建客户端会话未指定超时时间时使用的默认超时时间
+    pub session_timeout: Duration,
+    // 是否记录key的变更事件用于监听，开启后每次修改key都会复制修改前的值
+    pub watch_enabled: bool,
+    // 保留的变更事件数量，用于断线重连后恢复监听
+    pub watch_history: usize,
+    // 脚本单次执行最多执行的操作数
+    pub script_max_operations: u64,
+    // 脚本中单个字符串、数组或者对象的最大长度
+    pub script_max_value_size: usize,
 }
 
 impl Config {
@@ -41,6 +68,18 @@ impl Config {
                 ..Default::default()
             },
             raft_loop_interval: Duration::from_secs(1),
+            db_cron_interval: Duration::from_millis(100),
+            cron_proposal_timeout: Duration::from_secs(10),
+            active_expire_limit: 20,
+            remove_empty_hash: true,
+            max_memory: 0,
+            eviction_policy: EvictionPolicy::NoEviction,
+            lock_lease: Duration::from_secs(30),
+            session_timeout: Duration::from_secs(30),
+            watch_enabled: false,
+            watch_history: 1024,
+            script_max_operations: 1_000_000,
+            script_max_value_size: 1 << 20,
         };
         let node_id = Uuid::new_v4().to_string();
         let mut hasher = DefaultHasher::new();
@@ -0,0 +1,287 @@
+use anyhow::anyhow;
+
+use crate::until::normalize_range;
+
+// 位偏移上限，对应单个值最大512MB
+pub const MAX_BIT_OFFSET: u64 = (512 << 20) * 8;
+
+pub fn check_offset(offset: u64) -> anyhow::Result<()> {
+    if offset >= MAX_BIT_OFFSET {
+        return Err(anyhow!("Bit offset is out of range"));
+    }
+    Ok(())
+}
+
+/// 读取位，第0位为第一个字节的最高位，超出长度的位为0
+pub fn get_bit(buf: &[u8], offset: u64) -> u8 {
+    match buf.get((offset / 8) as usize) {
+        Some(byte) => (byte >> (7 - offset % 8)) & 1,
+        None => 0,
+    }
+}
+
+/// 设置位并返回原来的值，长度不足时用0填充
+pub fn set_bit(buf: &mut Vec<u8>, offset: u64, value: bool) -> u8 {
+    let index = (offset / 8) as usize;
+    if buf.len() <= index {
+        buf.resize(index + 1, 0);
+    }
+    let mask = 1 << (7 - offset % 8);
+    let old = (buf[index] & mask != 0) as u8;
+    if value {
+        buf[index] |= mask;
+    } else {
+        buf[index] &= !mask;
+    }
+    old
+}
+
+/// 按照下标语义规范化区间，bit_unit为true时start、end为位下标，否则为字节下标。
+/// 返回位区间[start, end]，None表示区间为空
+pub fn bit_range(len: usize, start: i64, end: i64, bit_unit: bool) -> Option<(u64, u64)> {
+    if bit_unit {
+        normalize_range(start, end, len * 8).map(|(s, e)| (s as u64, e as u64))
+    } else {
+        normalize_range(start, end, len).map(|(s, e)| (s as u64 * 8, e as u64 * 8 + 7))
+    }
+}
+
+/// 统计位区间内1的数量
+pub fn bit_count(buf: &[u8], range: Option<(u64, u64)>) -> u64 {
+    let Some((start, end)) = range else {
+        return buf.iter().map(|b| b.count_ones() as u64).sum();
+    };
+    let mut count = 0;
+    let mut bit = start;
+    while bit <= end {
+        if bit.is_multiple_of(8) && bit + 7 <= end {
+            count += buf[(bit / 8) as usize].count_ones() as u64;
+            bit += 8;
+        } else {
+            count += get_bit(buf, bit) as u64;
+            bit += 1;
+        }
+    }
+    count
+}
+
+/// 在位区间内查找第一个值为bit的位，找不到时返回None
+pub fn bit_pos(buf: &[u8], bit: bool, start: u64, end: u64) -> Option<u64> {
+    let skip = if bit { 0x00 } else { 0xff };
+    let mut pos = start;
+    while pos <= end {
+        if pos.is_multiple_of(8) && pos + 7 <= end && buf[(pos / 8) as usize] == skip {
+            pos += 8;
+            continue;
+        }
+        if get_bit(buf, pos) == bit as u8 {
+            return Some(pos);
+        }
+        pos += 1;
+    }
+    None
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BitOp {
+    And,
+    Or,
+    Xor,
+    Not,
+}
+
+/// 按位运算，较短的值用0补齐，结果长度为最长的值的长度。NOT只接受一个值
+pub fn bit_op(op: BitOp, sources: &[&[u8]]) -> anyhow::Result<Vec<u8>> {
+    if op == BitOp::Not {
+        let [source] = sources else {
+            return Err(anyhow!("BitOp NOT requires exactly one source key"));
+        };
+        return Ok(source.iter().map(|b| !b).collect());
+    }
+    let len = sources.iter().map(|s| s.len()).max().unwrap_or(0);
+    let mut result = match sources.first() {
+        Some(first) => {
+            let mut result = first.to_vec();
+            result.resize(len, 0);
+            result
+        }
+        None => return Ok(Vec::new()),
+    };
+    for source in &sources[1..] {
+        for (i, byte) in result.iter_mut().enumerate() {
+            let other = source.get(i).copied().unwrap_or(0);
+            match op {
+                BitOp::And => *byte &= other,
+                BitOp::Or => *byte |= other,
+                BitOp::Xor => *byte ^= other,
+                BitOp::Not => {}
+            }
+        }
+    }
+    Ok(result)
+}
+
+/// 位域的整数类型，有符号整数最多64位，无符号整数最多63位
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FieldType {
+    pub signed: bool,
+    pub bits: u32,
+}
+
+impl FieldType {
+    /// 解析i8、u16这样的类型
+    pub fn parse(value: &str) -> anyhow::Result<Self> {
+        let invalid = || anyhow!("Invalid bitfield type {}", value);
+        let signed = match value.chars().next() {
+            Some('i') => true,
+            Some('u') => false,
+            _ => return Err(invalid()),
+        };
+        let bits: u32 = value[1..].parse().map_err(|_| invalid())?;
+        let max = if signed { 64 } else { 63 };
+        if bits == 0 || bits > max {
+            return Err(invalid());
+        }
+        Ok(FieldType { signed, bits })
+    }
+
+    fn min(&self) -> i128 {
+        if self.signed {
+            -(1i128 << (self.bits - 1))
+        } else {
+            0
+        }
+    }
+
+    fn max(&self) -> i128 {
+        if self.signed {
+            (1i128 << (self.bits - 1)) - 1
+        } else {
+            (1i128 << self.bits) - 1
+        }
+    }
+
+    /// 解析位偏移，以#开头表示按字段宽度计算的偏移
+    pub fn parse_offset(&self, value: &str) -> anyhow::Result<u64> {
+        let invalid = || anyhow!("Invalid bitfield offset {}", value);
+        let offset = match value.strip_prefix('#') {
+            Some(index) => index
+                .parse::<u64>()
+                .map_err(|_| invalid())?
+                .checked_mul(self.bits as u64)
+                .ok_or_else(invalid)?,
+            None => value.parse::<u64>().map_err(|_| invalid())?,
+        };
+        let last = offset
+            .checked_add(self.bits as u64 - 1)
+            .ok_or_else(invalid)?;
+        check_offset(last)?;
+        Ok(offset)
+    }
+}
+
+/// 位域溢出时的处理方式
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Overflow {
+    // 回绕
+    Wrap,
+    // 取最大值或最小值
+    Sat,
+    // 不修改并返回None
+    Fail,
+}
+
+/// 读取位域，高位在前
+pub fn get_field(buf: &[u8], offset: u64, ty: FieldType) -> i64 {
+    let mut value: u64 = 0;
+    for i in 0..ty.bits as u64 {
+        value = (value << 1) | get_bit(buf, offset + i) as u64;
+    }
+    if ty.signed {
+        let shift = 64 - ty.bits;
+        ((value << shift) as i64) >> shift
+    } else {
+        value as i64
+    }
+}
+
+/// 写入位域，只写入低bits位
+pub fn set_field(buf: &mut Vec<u8>, offset: u64, ty: FieldType, value: i64) {
+    let value = value as u64;
+    for i in 0..ty.bits as u64 {
+        let bit = (value >> (ty.bits as u64 - 1 - i)) & 1;
+        set_bit(buf, offset + i, bit == 1);
+    }
+}
+
+/// 计算current加上delta后的位域值，按照overflow处理溢出
+pub fn incr_field(current: i64, delta: i64, ty: FieldType, overflow: Overflow) -> Option<i64> {
+    let sum = current as i128 + delta as i128;
+    let (min, max) = (ty.min(), ty.max());
+    if sum >= min && sum <= max {
+        return Some(sum as i64);
+    }
+    match overflow {
+        Overflow::Wrap => {
+            let span = 1i128 << ty.bits;
+            Some(((sum - min).rem_euclid(span) + min) as i64)
+        }
+        Overflow::Sat => Some(sum.clamp(min, max) as i64),
+        Overflow::Fail => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        bit_count, bit_op, bit_pos, bit_range, get_bit, get_field, incr_field, set_bit, set_field,
+        BitOp, FieldType, Overflow,
+    };
+
+    #[test]
+    fn bit_test() {
+        let mut buf = Vec::new();
+        assert_eq!(set_bit(&mut buf, 9, true), 0);
+        assert_eq!(buf, vec![0x00, 0x40]);
+        assert_eq!(get_bit(&buf, 9), 1);
+        assert_eq!(get_bit(&buf, 100), 0);
+        set_bit(&mut buf, 0, true);
+        assert_eq!(bit_count(&buf, None), 2);
+        assert_eq!(bit_count(&buf, bit_range(buf.len(), 1, 1, false)), 1);
+        assert_eq!(bit_count(&buf, bit_range(buf.len(), 1, 9, true)), 1);
+
+        let range = bit_range(buf.len(), 0, -1, false).unwrap();
+        assert_eq!(bit_pos(&buf, true, 1, range.1), Some(9));
+        assert_eq!(bit_pos(&[0xff], false, 0, 7), None);
+
+        let or = bit_op(BitOp::Or, &[&[0x0f], &[0xf0, 0x01]]).unwrap();
+        assert_eq!(or, vec![0xff, 0x01]);
+        let and = bit_op(BitOp::And, &[&[0x0f], &[0xff, 0x01]]).unwrap();
+        assert_eq!(and, vec![0x0f, 0x00]);
+        assert_eq!(bit_op(BitOp::Not, &[&[0x0f]]).unwrap(), vec![0xf0]);
+        assert!(bit_op(BitOp::Not, &[&[0x0f], &[0x01]]).is_err());
+    }
+
+    #[test]
+    fn bitfield_test() {
+        let i8 = FieldType::parse("i8").unwrap();
+        let u4 = FieldType::parse("u4").unwrap();
+        assert!(FieldType::parse("u64").is_err());
+        assert_eq!(u4.parse_offset("#2").unwrap(), 8);
+        // 字段的最后一位超出u64时报错而不是溢出
+        assert!(i8.parse_offset(&u64::MAX.to_string()).is_err());
+        assert!(u4.parse_offset(&format!("#{}", u64::MAX / 4)).is_err());
+
+        let mut buf = Vec::new();
+        set_field(&mut buf, 4, i8, -3);
+        assert_eq!(get_field(&buf, 4, i8), -3);
+        assert_eq!(buf, vec![0x0f, 0xd0]);
+        assert_eq!(get_field(&buf, 0, u4), 0);
+
+        assert_eq!(incr_field(127, 1, i8, Overflow::Wrap), Some(-128));
+        assert_eq!(incr_field(127, 1, i8, Overflow::Sat), Some(127));
+        assert_eq!(incr_field(127, 1, i8, Overflow::Fail), None);
+        assert_eq!(incr_field(15, 3, u4, Overflow::Wrap), Some(2));
+        assert_eq!(incr_field(0, -1, u4, Overflow::Sat), Some(0));
+    }
+}
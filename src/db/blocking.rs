@@ -0,0 +1,141 @@
+use std::collections::VecDeque;
+
+use ahash::AHashSet;
+
+use crate::command::Command;
+
+// 命名空间与key之间的分隔符
+const NAMESPACE_SEPARATOR: char = '\u{0}';
+
+/// 带命名空间的key，用于区分不同命名空间中的同名key
+pub fn qualified_key(namespace: &str, key: &str) -> String {
+    format!("{}{}{}", namespace, NAMESPACE_SEPARATOR, key)
+}
+
+pub fn split_qualified_key(qualified: &str) -> (&str, &str) {
+    qualified
+        .split_once(NAMESPACE_SEPARATOR)
+        .unwrap_or(("", qualified))
+}
+
+// 被阻塞命令挂起的客户端
+pub struct BlockedClient {
+    pub command: Command,
+    // 等待的key(带命名空间)，任意一个key被写入都会唤醒
+    pub keys: Vec<String>,
+    // 超时时间戳(毫秒)，None表示永久阻塞
+    pub deadline: Option<u128>,
+}
+
+#[derive(Default)]
+pub struct BlockedClients {
+    // 按照阻塞的先后顺序排列，保证各副本唤醒顺序一致
+    clients: VecDeque<BlockedClient>,
+    // 有数据写入且存在等待客户端的key
+    ready_keys: AHashSet<String>,
+}
+
+impl BlockedClients {
+    pub fn block(&mut self, client: BlockedClient) {
+        self.clients.push_back(client);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.clients.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.clients.len()
+    }
+
+    /// 标记key有新数据写入
+    pub fn signal_ready(&mut self, key: &str) {
+        if self.ready_keys.contains(key) {
+            return;
+        }
+        if self.clients.iter().any(|c| c.keys.iter().any(|k| k == key)) {
+            self.ready_keys.insert(String::from(key));
+        }
+    }
+
+    /// 标记命名空间中所有被等待的key为就绪，用于清空或者交换命名空间
+    pub fn signal_namespace(&mut self, namespace: &str) {
+        for client in self.clients.iter() {
+            for key in client.keys.iter() {
+                if split_qualified_key(key).0 == namespace {
+                    self.ready_keys.insert(key.clone());
+                }
+            }
+        }
+    }
+
+    pub fn has_ready(&self) -> bool {
+        !self.ready_keys.is_empty()
+    }
+
+    /// 取出所有等待已就绪key的客户端，返回值保持阻塞顺序
+    pub fn take_ready(&mut self) -> Vec<BlockedClient> {
+        let ready_keys = std::mem::take(&mut self.ready_keys);
+        let mut ready = Vec::new();
+        let mut remaining = VecDeque::with_capacity(self.clients.len());
+        for client in self.clients.drain(..) {
+            if client.keys.iter().any(|k| ready_keys.contains(k)) {
+                ready.push(client);
+            } else {
+                remaining.push_back(client);
+            }
+        }
+        self.clients = remaining;
+        ready
+    }
+
+    /// 将唤醒后仍然无法执行的客户端放回队列头部，保持原有的先后顺序
+    pub fn restore(&mut self, clients: Vec<BlockedClient>) {
+        for client in clients.into_iter().rev() {
+            self.clients.push_front(client);
+        }
+    }
+
+    /// 取出所有已经超时的本地客户端。经raft复制的客户端在各副本上都存在，
+    /// 不能按本节点的时钟超时，由leader发起超时提案后通过take_indexes移除
+    pub fn take_expired(&mut self, now: u128) -> Vec<BlockedClient> {
+        let mut expired = Vec::new();
+        let mut remaining = VecDeque::with_capacity(self.clients.len());
+        for client in self.clients.drain(..) {
+            match client.deadline {
+                Some(deadline) if deadline <= now && client.command.index() == 0 => {
+                    expired.push(client)
+                }
+                _ => remaining.push_back(client),
+            }
+        }
+        self.clients = remaining;
+        expired
+    }
+
+    /// 已经超时的经raft复制的客户端，返回阻塞命令的raft日志索引
+    pub fn timed_out(&self, now: u128) -> Vec<u64> {
+        self.clients
+            .iter()
+            .filter(|client| client.command.index() > 0)
+            .filter(|client| matches!(client.deadline, Some(deadline) if deadline <= now))
+            .map(|client| client.command.index())
+            .collect()
+    }
+
+    /// 取出阻塞命令的raft日志索引在indexes中的客户端，已经被唤醒的客户端忽略
+    pub fn take_indexes(&mut self, indexes: &[u64]) -> Vec<BlockedClient> {
+        let mut taken = Vec::new();
+        let mut remaining = VecDeque::with_capacity(self.clients.len());
+        for client in self.clients.drain(..) {
+            let index = client.command.index();
+            if index > 0 && indexes.contains(&index) {
+                taken.push(client);
+            } else {
+                remaining.push_back(client);
+            }
+        }
+        self.clients = remaining;
+        taken
+    }
+}
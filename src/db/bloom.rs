@@ -0,0 +1,238 @@
+use std::f64::consts::LN_2;
+
+use anyhow::anyhow;
+
+use crate::until::murmur64a;
+
+// 默认误判率和容量，BfAdd创建过滤器时使用
+pub const DEFAULT_ERROR_RATE: f64 = 0.01;
+pub const DEFAULT_CAPACITY: u64 = 100;
+// 默认扩容倍数
+pub const DEFAULT_EXPANSION: u32 = 2;
+// 每次扩容时新过滤器的误判率收紧比例，使整体误判率收敛于初始误判率的两倍以内
+const TIGHTENING_RATIO: f64 = 0.5;
+// 单个过滤器位数组的上限，与字符串的最大长度一致
+const MAX_LAYER_BITS: u64 = (512 << 20) * 8;
+const HASH_SEED: u64 = 0x9747b28c;
+
+/// 单个固定大小的布隆过滤器
+#[derive(Clone, Debug, PartialEq)]
+pub struct BloomLayer {
+    pub capacity: u64,
+    pub count: u64,
+    pub error_rate: f64,
+    pub hashes: u32,
+    // 位数组的位数
+    pub bits: u64,
+    pub data: Vec<u8>,
+}
+
+impl BloomLayer {
+    fn new(capacity: u64, error_rate: f64) -> anyhow::Result<Self> {
+        let capacity = capacity.max(1);
+        let bits = (-(capacity as f64) * error_rate.ln() / (LN_2 * LN_2)).ceil();
+        if !(bits <= MAX_LAYER_BITS as f64) {
+            return Err(anyhow!(
+                "Bloom filter with capacity {} and error rate {} exceeds {} bits",
+                capacity,
+                error_rate,
+                MAX_LAYER_BITS
+            ));
+        }
+        let bits = (bits as u64).max(8);
+        let hashes = ((-error_rate.log2()).ceil() as u32).max(1);
+        Ok(BloomLayer {
+            capacity,
+            count: 0,
+            error_rate,
+            hashes,
+            bits,
+            data: vec![0u8; bits.div_ceil(8) as usize],
+        })
+    }
+
+    /// 检查解码得到的过滤器，位数组的长度必须与位数一致
+    pub fn is_valid(&self) -> bool {
+        self.hashes > 0
+            && self.bits > 0
+            && self.bits <= MAX_LAYER_BITS
+            && self.data.len() as u64 == self.bits.div_ceil(8)
+    }
+
+    // 双重哈希生成k个位置
+    fn positions(&self, hash: (u64, u64)) -> impl Iterator<Item = u64> + '_ {
+        (0..self.hashes as u64)
+            .map(move |i| hash.0.wrapping_add(i.wrapping_mul(hash.1)) % self.bits)
+    }
+
+    fn contains(&self, hash: (u64, u64)) -> bool {
+        self.positions(hash)
+            .all(|bit| self.data[(bit / 8) as usize] & (1 << (bit % 8)) != 0)
+    }
+
+    fn insert(&mut self, hash: (u64, u64)) {
+        let positions: Vec<u64> = self.positions(hash).collect();
+        for bit in positions {
+            self.data[(bit / 8) as usize] |= 1 << (bit % 8);
+        }
+        self.count += 1;
+    }
+
+    fn is_full(&self) -> bool {
+        self.count >= self.capacity
+    }
+}
+
+/// 可扩容的布隆过滤器，当前过滤器写满后追加容量更大、误判率更低的过滤器
+#[derive(Clone, Debug, PartialEq)]
+pub struct BloomFilter {
+    pub error_rate: f64,
+    // 扩容倍数，0表示不扩容，写满后拒绝添加
+    pub expansion: u32,
+    pub layers: Vec<BloomLayer>,
+}
+
+fn hash_item(item: &[u8]) -> (u64, u64) {
+    let h1 = murmur64a(item, HASH_SEED);
+    let h2 = murmur64a(item, h1);
+    (h1, h2 | 1)
+}
+
+impl BloomFilter {
+    pub fn new(error_rate: f64, capacity: u64, expansion: u32) -> anyhow::Result<Self> {
+        if !(error_rate > 0.0 && error_rate < 1.0) {
+            return Err(anyhow!("Error rate must be between 0 and 1"));
+        }
+        if capacity == 0 {
+            return Err(anyhow!("Capacity must be positive"));
+        }
+        Ok(BloomFilter {
+            error_rate,
+            expansion,
+            layers: vec![BloomLayer::new(capacity, error_rate * TIGHTENING_RATIO)?],
+        })
+    }
+
+    /// 由编码的数据恢复过滤器，校验每个过滤器的参数
+    pub fn decode(
+        error_rate: f64,
+        expansion: u32,
+        layers: Vec<BloomLayer>,
+    ) -> anyhow::Result<Self> {
+        if layers.is_empty() || !layers.iter().all(BloomLayer::is_valid) {
+            return Err(anyhow!("Invalid bloom filter layers"));
+        }
+        Ok(BloomFilter {
+            error_rate,
+            expansion,
+            layers,
+        })
+    }
+
+    /// 容量为所有过滤器容量之和
+    pub fn capacity(&self) -> u64 {
+        self.layers.iter().map(|layer| layer.capacity).sum()
+    }
+
+    /// 已添加的元素数量
+    pub fn len(&self) -> u64 {
+        self.layers.iter().map(|layer| layer.count).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn contains(&self, item: &[u8]) -> bool {
+        let hash = hash_item(item);
+        self.layers.iter().any(|layer| layer.contains(hash))
+    }
+
+    /// 添加元素，元素可能已经存在时返回false
+    pub fn add(&mut self, item: &[u8]) -> anyhow::Result<bool> {
+        let hash = hash_item(item);
+        if self.layers.iter().any(|layer| layer.contains(hash)) {
+            return Ok(false);
+        }
+        let Some(last) = self.layers.last() else {
+            return Err(anyhow!("Bloom filter has no layer"));
+        };
+        if last.is_full() {
+            if self.expansion == 0 {
+                return Err(anyhow!("Bloom filter is full"));
+            }
+            let capacity = last.capacity.saturating_mul(self.expansion as u64);
+            let error_rate = last.error_rate * TIGHTENING_RATIO;
+            self.layers.push(BloomLayer::new(capacity, error_rate)?);
+        }
+        if let Some(last) = self.layers.last_mut() {
+            last.insert(hash);
+        }
+        Ok(true)
+    }
+
+    /// 估算占用的内存字节数
+    pub fn mem_size(&self) -> usize {
+        32 + self
+            .layers
+            .iter()
+            .map(|layer| 48 + layer.data.len())
+            .sum::<usize>()
+    }
+}
+
+impl Default for BloomFilter {
+    /// 使用默认误判率和容量的空过滤器
+    fn default() -> Self {
+        let layer = BloomLayer::new(DEFAULT_CAPACITY, DEFAULT_ERROR_RATE * TIGHTENING_RATIO);
+        BloomFilter {
+            error_rate: DEFAULT_ERROR_RATE,
+            expansion: DEFAULT_EXPANSION,
+            layers: layer.into_iter().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::BloomFilter;
+
+    #[test]
+    fn scalable_bloom_test() {
+        let mut filter = BloomFilter::new(0.01, 100, 2).unwrap();
+        for i in 0..1000 {
+            filter.add(format!("seen:{}", i).as_bytes()).unwrap();
+        }
+        assert!(filter.layers.len() > 1);
+        // 没有漏判
+        assert!((0..1000).all(|i| filter.contains(format!("seen:{}", i).as_bytes())));
+        let false_positives = (1000..11000)
+            .filter(|i| filter.contains(format!("seen:{}", i).as_bytes()))
+            .count();
+        assert!(false_positives < 200, "false positives {}", false_positives);
+        assert!(!filter.add(b"seen:1").unwrap());
+
+        let mut fixed = BloomFilter::new(0.01, 2, 0).unwrap();
+        fixed.add(b"a").unwrap();
+        fixed.add(b"b").unwrap();
+        assert!(fixed.add(b"c").is_err());
+        assert!(BloomFilter::new(1.5, 10, 2).is_err());
+        // 位数组超出上限时拒绝创建
+        assert!(BloomFilter::new(0.01, 1 << 60, 2).is_err());
+        assert!(BloomFilter::new(1e-300, 1 << 30, 2).is_err());
+    }
+
+    #[test]
+    fn decode_test() {
+        let filter = BloomFilter::new(0.01, 100, 2).unwrap();
+        let decoded = BloomFilter::decode(0.01, 2, filter.layers.clone()).unwrap();
+        assert_eq!(decoded, filter);
+        let mut layer = filter.layers[0].clone();
+        layer.bits = 0;
+        assert!(BloomFilter::decode(0.01, 2, vec![layer]).is_err());
+        let mut layer = filter.layers[0].clone();
+        layer.data.truncate(1);
+        assert!(BloomFilter::decode(0.01, 2, vec![layer]).is_err());
+        assert!(BloomFilter::decode(0.01, 2, Vec::new()).is_err());
+    }
+}
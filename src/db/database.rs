@@ -1,36 +1,1115 @@
 use ahash::AHashMap;
+use anyhow::anyhow;
 use log::Level::Debug;
 use log::{debug, error, info, log_enabled};
+use rhai::AST;
+use std::ops::Bound;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::time::interval;
 use tokio::{select, sync::mpsc, task::JoinHandle};
 use tokio_context::context::{Context, RefContext};
 
+use super::blocking::{self, BlockedClient, BlockedClients};
 use super::dbvalue::DBValue;
-use crate::command::Command;
-use crate::postman::{AsAny, LetterMessage};
+use super::eviction::{self, Candidate, EvictionPolicy, KeyMeta};
+use super::index::{IndexDef, IndexValue};
+use super::lock::LockState;
+use super::processor::ProcessorRegistry;
+use super::scan::MemberSnapshots;
+use super::script::ScriptEngine;
+use super::session::{ClientSession, SessionTable};
+use super::namespace::{normalize_namespace, Keyspace, DEFAULT_NAMESPACE};
+use crate::command::{BlockedError, Command, ExecutableCommand, ProposalCommand};
+use crate::postman::LetterMessage;
+use crate::proto::{
+    ActiveExpireCmd, BlockTimeoutCmd, EvictCmd, SessionExpireCmd, WatchEvent, WatchOp,
+};
 use crate::runtime::Runtime;
+use crate::until;
 
 pub struct Database {
-    pub db: AHashMap<String, DBValue>,
+    // 当前命令所在命名空间的键空间
+    space: Keyspace,
+    // 当前命名空间名称
+    namespace: String,
+    // 其它命名空间的键空间
+    spaces: AHashMap<String, Keyspace>,
+    // 当前命名空间是否已经创建，未创建的命名空间在切换走时丢弃
+    created: bool,
+    // 阻塞等待数据的客户端
+    pub blocked: BlockedClients,
+    // hash成员全部过期后是否删除hash本身
+    pub remove_empty_hash: bool,
+    // 所有命名空间的内存上限(字节)，0表示不限制
+    pub max_memory: usize,
+    pub eviction_policy: EvictionPolicy,
+    // 当前执行命令的时间戳(毫秒)
+    clock: u128,
+    // 当前执行命令的raft日志索引
+    index: u64,
+    // 当前执行命令的客户端会话
+    session: String,
+    // 最近一次分配的锁令牌，所有命名空间共享，保证单调递增
+    lock_token: u64,
+    // 获取锁未指定租约时使用的默认租约(毫秒)
+    pub lock_lease_ms: u64,
+    // 所有命名空间共享的客户端会话
+    sessions: SessionTable,
+    // 创建会话未指定超时时间时使用的默认超时时间(毫秒)
+    pub session_timeout_ms: u64,
+    // 是否记录key的变更事件
+    pub watch_enabled: bool,
+    // 最近一次变更事件的版本号，按照命令应用的顺序递增，各副本一致
+    revision: u64,
+    // 通过get_mut修改的key在修改前的值，命令执行完成后生成变更事件
+    snapshots: AHashMap<String, Option<DBValue>>,
+    // 尚未投递的变更事件
+    events: Vec<WatchEvent>,
+    // 已注册的处理器，启动数据库前注册，所有节点必须一致
+    pub processors: ProcessorRegistry,
+    // 已加载的脚本，经raft复制加载，各副本一致
+    pub scripts: ScriptEngine,
+    // 遍历集合成员时生成的成员快照，只在本节点有效
+    member_snapshots: MemberSnapshots,
 }
 
 impl Database {
     pub fn new() -> Self {
         Database {
-            db: AHashMap::new(),
+            space: Keyspace::default(),
+            namespace: String::from(DEFAULT_NAMESPACE),
+            spaces: AHashMap::new(),
+            created: true,
+            blocked: BlockedClients::default(),
+            remove_empty_hash: true,
+            max_memory: 0,
+            eviction_policy: EvictionPolicy::NoEviction,
+            clock: 0,
+            index: 0,
+            session: String::new(),
+            lock_token: 0,
+            lock_lease_ms: 30_000,
+            sessions: SessionTable::default(),
+            session_timeout_ms: 30_000,
+            watch_enabled: false,
+            revision: 0,
+            snapshots: AHashMap::new(),
+            events: Vec::new(),
+            processors: ProcessorRegistry::default(),
+            scripts: ScriptEngine::default(),
+            member_snapshots: MemberSnapshots::default(),
         }
     }
 
-    pub fn set(&mut self, key: String, value: DBValue) {
-        self.db.insert(key, value);
+    /// 设置当前执行命令的时间戳，命令执行过程中所有时间相关的逻辑都以该时间为准
+    pub fn set_clock(&mut self, ts: u128) {
+        self.clock = ts;
     }
 
-    pub fn get(&mut self, key: &str) -> Option<&DBValue> {
-        self.db.get(key)
+    /// 设置当前执行命令的raft日志索引
+    pub fn set_index(&mut self, index: u64) {
+        self.index = index;
+    }
+
+    pub fn index(&self) -> u64 {
+        self.index
+    }
+
+    /// 设置当前执行命令的客户端会话
+    pub fn set_session(&mut self, session: &str) {
+        if self.session != session {
+            self.session = String::from(session);
+        }
+    }
+
+    pub fn session(&self) -> &str {
+        &self.session
+    }
+
+    pub fn now(&self) -> u128 {
+        if self.clock > 0 {
+            self.clock
+        } else {
+            until::now_ts().unwrap_or(0)
+        }
+    }
+
+    /// 切换到命令所在的命名空间，空字符串表示默认命名空间
+    pub fn select(&mut self, namespace: &str) {
+        let namespace = normalize_namespace(namespace);
+        if self.namespace == namespace {
+            return;
+        }
+        self.resolve_snapshots();
+        self.flush_dirty();
+        let (space, created) = match self.spaces.remove(namespace) {
+            Some(space) => (space, true),
+            None => (Keyspace::default(), namespace == DEFAULT_NAMESPACE),
+        };
+        let previous = std::mem::replace(&mut self.space, space);
+        let previous_name = std::mem::replace(&mut self.namespace, String::from(namespace));
+        if std::mem::replace(&mut self.created, created) {
+            self.spaces.insert(previous_name, previous);
+        }
+    }
+
+    /// 创建当前命名空间，只读命令访问不存在的命名空间时不创建
+    pub fn create_namespace(&mut self) {
+        self.created = true;
+    }
+
+    pub fn namespace(&self) -> &str {
+        &self.namespace
+    }
+
+    /// 所有命名空间名称，按名称排序
+    pub fn namespaces(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.spaces.keys().cloned().collect();
+        if self.created {
+            names.push(self.namespace.clone());
+        }
+        names.sort();
+        names
+    }
+
+    pub fn keyspace(&self) -> &Keyspace {
+        &self.space
+    }
+
+    pub fn keyspace_mut(&mut self) -> &mut Keyspace {
+        &mut self.space
+    }
+
+    /// 当前命名空间中保存的key数量，包含已过期但尚未删除的key
+    pub fn dbsize(&self) -> usize {
+        self.space.db.len()
+    }
+
+    /// 清空当前命名空间
+    pub fn flush(&mut self) {
+        self.snapshots.clear();
+        self.space.flush();
+        self.blocked.signal_namespace(&self.namespace);
+        self.record(WatchOp::Flush, "", None, None);
+    }
+
+    /// 交换当前命名空间与另一个命名空间的数据
+    pub fn swap(&mut self, other: &str) {
+        let other = normalize_namespace(other);
+        if self.namespace == other {
+            return;
+        }
+        self.resolve_snapshots();
+        self.flush_dirty();
+        let mut space = self.spaces.remove(other).unwrap_or_default();
+        self.space.swap_data(&mut space);
+        self.spaces.insert(String::from(other), space);
+        // 两个命名空间的数据都发生了变化，唤醒等待的客户端重新检查
+        self.blocked.signal_namespace(&self.namespace);
+        self.blocked.signal_namespace(other);
+        let current = self.namespace.clone();
+        self.push_event(current.clone(), WatchOp::Swap, other, None, None);
+        self.push_event(String::from(other), WatchOp::Swap, &current, None, None);
+    }
+
+    /// 通知key有新数据写入，唤醒等待该key的阻塞客户端
+    pub fn signal_ready(&mut self, key: &str) {
+        if !self.blocked.is_empty() {
+            self.blocked
+                .signal_ready(&blocking::qualified_key(&self.namespace, key));
+        }
+    }
+
+    /// 写入key会清除原有的过期时间，命名空间配置了默认过期时间时使用默认过期时间
+    pub fn set(&mut self, key: String, value: DBValue) -> Option<DBValue> {
+        self.expire_if_needed(&key);
+        match self.space.config.default_ttl_ms {
+            0 => self.space.expires.remove(&key),
+            ttl => self.space.expires.insert(key.clone(), self.now() + ttl as u128),
+        };
+        self.space.field_expires.remove_key(&key);
+        // 整体覆盖的key不再属于原来的会话
+        self.space.ephemeral.remove(&key);
+        self.space.ephemeral_fields.remove(&key);
+        self.put(key, value)
+    }
+
+    /// 修改已有key的值，保留过期时间和所属会话，key不存在或已过期时与set相同
+    pub fn replace(&mut self, key: String, value: DBValue) -> Option<DBValue> {
+        if self.expire_if_needed(&key) || !self.space.db.contains_key(&key) {
+            return self.set(key, value);
+        }
+        self.put(key, value)
+    }
+
+    fn put(&mut self, key: String, value: DBValue) -> Option<DBValue> {
+        self.created = true;
+        let new = self.watch_enabled.then(|| value.clone());
+        let old = self.space.db.insert(key.clone(), value);
+        if old.is_none() {
+            self.space.keys.insert(key.clone());
+            self.space.sampler.insert(&key);
+        }
+        self.account(&key);
+        if self.watch_enabled {
+            // 同一命令中先修改后覆盖时，以修改前的值作为旧值
+            let before = match self.snapshots.remove(&key) {
+                Some(snapshot) => snapshot,
+                None => old.clone(),
+            };
+            self.record(WatchOp::Put, &key, before, new);
+        }
+        old
+    }
+
+    pub fn get(&self, key: &str) -> Option<&DBValue> {
+        if self.is_expired(key) {
+            return None;
+        }
+        if let Some(meta) = self.space.meta.get(key) {
+            meta.touch(self.now());
+        }
+        self.space.db.get(key)
     }
 
     pub fn get_mut(&mut self, key: &str) -> Option<&mut DBValue> {
-        self.db.get_mut(key)
+        self.snapshot(key);
+        self.expire_if_needed(key);
+        self.expire_fields_if_needed(key);
+        if let Some(meta) = self.space.meta.get(key) {
+            meta.touch(self.now());
+            self.space.dirty.insert(String::from(key));
+        }
+        self.space.db.get_mut(key)
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<DBValue> {
+        if self.expire_if_needed(key) {
+            return None;
+        }
+        self.drop_key(key, WatchOp::Delete)
+    }
+
+    /// 因内存超限淘汰key
+    pub fn evict(&mut self, key: &str) -> Option<DBValue> {
+        if self.expire_if_needed(key) {
+            return None;
+        }
+        self.drop_key(key, WatchOp::Evict)
+    }
+
+    // 删除key及其过期时间、内存统计，op为变更事件的操作类型
+    fn drop_key(&mut self, key: &str, op: WatchOp) -> Option<DBValue> {
+        self.space.expires.remove(key);
+        self.space.field_expires.remove_key(key);
+        self.space.ephemeral.remove(key);
+        self.space.ephemeral_fields.remove(key);
+        self.space.dirty.remove(key);
+        if let Some(meta) = self.space.meta.remove(key) {
+            self.space.used_memory -= meta.size;
+        }
+        self.space.keys.remove(key);
+        self.space.sampler.remove(key);
+        self.space.indexes.remove(key);
+        let snapshot = self.snapshots.remove(key);
+        let old = self.space.db.remove(key);
+        if self.watch_enabled && old.is_some() {
+            let before = match snapshot {
+                Some(snapshot) => snapshot,
+                None => old.clone(),
+            };
+            self.record(op, key, before, None);
+        }
+        old
+    }
+
+    // 记录key修改前的值，命令执行完成后与修改后的值比较生成变更事件
+    fn snapshot(&mut self, key: &str) {
+        if self.watch_enabled && !self.snapshots.contains_key(key) {
+            let old = self.space.db.get(key).cloned();
+            self.snapshots.insert(String::from(key), old);
+        }
+    }
+
+    // 比较通过get_mut修改的key前后的值，按key排序生成变更事件
+    fn resolve_snapshots(&mut self) {
+        if self.snapshots.is_empty() {
+            return;
+        }
+        let mut snapshots: Vec<(String, Option<DBValue>)> = self.snapshots.drain().collect();
+        snapshots.sort_by(|a, b| a.0.cmp(&b.0));
+        for (key, old) in snapshots {
+            let new = self.space.db.get(&key).cloned();
+            if new.is_some() && new != old {
+                self.record(WatchOp::Put, &key, old, new);
+            }
+        }
+    }
+
+    fn record(&mut self, op: WatchOp, key: &str, old: Option<DBValue>, new: Option<DBValue>) {
+        let namespace = self.namespace.clone();
+        self.push_event(namespace, op, key, old, new);
+    }
+
+    fn push_event(
+        &mut self,
+        namespace: String,
+        op: WatchOp,
+        key: &str,
+        old: Option<DBValue>,
+        new: Option<DBValue>,
+    ) {
+        if !self.watch_enabled {
+            return;
+        }
+        self.revision += 1;
+        self.events.push(WatchEvent {
+            revision: self.revision,
+            namespace,
+            op: op as i32,
+            key: String::from(key),
+            old_value: old.map(Into::into),
+            new_value: new.map(Into::into),
+        });
+    }
+
+    /// 最近一次变更事件的版本号
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// 取出命令执行过程中产生的变更事件
+    pub fn take_events(&mut self) -> Vec<WatchEvent> {
+        self.resolve_snapshots();
+        std::mem::take(&mut self.events)
+    }
+
+    /// 当前会话获取锁，成功返回令牌。新获取的锁使用raft日志索引作为令牌，
+    /// 未经raft复制的命令使用递增的计数，令牌在所有命名空间中单调递增
+    pub fn acquire_lock(&mut self, name: &str, lease_ms: u64) -> anyhow::Result<Option<u64>> {
+        if self.session.is_empty() {
+            return Err(anyhow!("Lock requires a client session"));
+        }
+        let lease_ms = if lease_ms == 0 {
+            self.lock_lease_ms
+        } else {
+            lease_ms
+        };
+        let now = self.now();
+        let next_token = self.index.max(self.lock_token + 1);
+        let token = self
+            .space
+            .locks
+            .acquire(name, &self.session, lease_ms, now, next_token);
+        if token == Some(next_token) {
+            self.lock_token = next_token;
+        }
+        Ok(token)
+    }
+
+    /// 当前会话释放一次锁，返回剩余的重入次数，锁完全释放时唤醒等待的客户端
+    pub fn release_lock(&mut self, name: &str) -> anyhow::Result<u32> {
+        let now = self.now();
+        let remaining = self.space.locks.release(name, &self.session, now)?;
+        if remaining == 0 {
+            self.signal_ready(name);
+        }
+        Ok(remaining)
+    }
+
+    /// 当前会话续约锁，锁不属于当前会话时返回false
+    pub fn renew_lock(&mut self, name: &str, lease_ms: u64) -> bool {
+        let lease_ms = if lease_ms == 0 {
+            self.lock_lease_ms
+        } else {
+            lease_ms
+        };
+        let now = self.now();
+        self.space.locks.renew(name, &self.session, lease_ms, now)
+    }
+
+    pub fn lock_holder(&self, name: &str) -> Option<&LockState> {
+        self.space.locks.holder(name, self.now())
+    }
+
+    /// 创建或者更新当前会话，返回会话到期时间
+    pub fn open_session(&mut self, timeout_ms: u64) -> anyhow::Result<u128> {
+        if self.session.is_empty() {
+            return Err(anyhow!("Command has no client session"));
+        }
+        let timeout_ms = if timeout_ms == 0 {
+            self.session_timeout_ms
+        } else {
+            timeout_ms
+        };
+        let now = self.now();
+        Ok(self.sessions.open(&self.session, timeout_ms, now))
+    }
+
+    /// 当前会话的心跳，返回会话新的到期时间，会话不存在或者已经过期时返回错误
+    pub fn heartbeat_session(&mut self) -> anyhow::Result<u128> {
+        let now = self.now();
+        self.sessions
+            .heartbeat(&self.session, now)
+            .ok_or_else(|| anyhow!("Session {} is expired or not opened", &self.session))
+    }
+
+    pub fn client_session(&self, id: &str) -> Option<&ClientSession> {
+        self.sessions.get(id)
+    }
+
+    /// 在给定时间点已经过期的会话，按会话ID排序
+    pub fn expired_sessions(&self, now: u128) -> Vec<String> {
+        self.sessions.expired(now)
+    }
+
+    /// 会话在当前时间点是否已经过期
+    pub fn is_session_expired(&self, id: &str) -> bool {
+        self.sessions.get(id).is_some() && !self.sessions.is_alive(id, self.now())
+    }
+
+    /// 关闭会话，删除所有命名空间中属于该会话的临时数据并释放会话持有的锁
+    pub fn close_session(&mut self, id: &str) -> bool {
+        if !self.sessions.remove(id) {
+            return false;
+        }
+        let current = self.namespace.clone();
+        for namespace in self.namespaces() {
+            self.select(&namespace);
+            self.remove_ephemeral(id);
+        }
+        self.select(&current);
+        true
+    }
+
+    // 删除当前命名空间中属于会话的临时key、临时hash成员和锁，按key排序保证各副本一致
+    fn remove_ephemeral(&mut self, id: &str) {
+        let mut keys: Vec<String> = self
+            .space
+            .ephemeral
+            .iter()
+            .filter(|(_, owner)| owner.as_str() == id)
+            .map(|(key, _)| key.clone())
+            .collect();
+        keys.sort();
+        for key in keys.iter() {
+            self.remove(key);
+        }
+
+        let mut fields: Vec<(String, String)> = Vec::new();
+        for (key, members) in self.space.ephemeral_fields.iter() {
+            for (member, owner) in members.iter() {
+                if owner == id {
+                    fields.push((key.clone(), member.clone()));
+                }
+            }
+        }
+        fields.sort();
+        let remove_empty_hash = self.remove_empty_hash;
+        for (key, member) in fields.iter() {
+            self.set_ephemeral_field(key, member, None);
+            self.persist_field(key, member);
+            let mut remove_key = false;
+            if let Some(DBValue::Hash(hash)) = self.get_mut(key) {
+                hash.remove(member);
+                remove_key = hash.is_empty() && remove_empty_hash;
+            }
+            if remove_key {
+                self.remove(key);
+            }
+        }
+
+        for name in self.space.locks.release_owner(id) {
+            self.signal_ready(&name);
+        }
+    }
+
+    /// 写入临时数据前检查当前会话是否存活
+    pub fn require_session(&self) -> anyhow::Result<()> {
+        if self.sessions.is_alive(&self.session, self.now()) {
+            Ok(())
+        } else {
+            Err(anyhow!("Ephemeral write requires an open session"))
+        }
+    }
+
+    /// 将key标记为当前会话的临时key
+    pub fn mark_ephemeral(&mut self, key: &str) {
+        self.space
+            .ephemeral
+            .insert(String::from(key), self.session.clone());
+    }
+
+    /// 将hash成员标记为当前会话的临时成员，ephemeral为false时取消标记
+    pub fn mark_ephemeral_field(&mut self, key: &str, member: &str, ephemeral: bool) {
+        let owner = ephemeral.then(|| self.session.clone());
+        self.set_ephemeral_field(key, member, owner.as_deref());
+    }
+
+    fn set_ephemeral_field(&mut self, key: &str, member: &str, owner: Option<&str>) {
+        match owner {
+            Some(owner) => {
+                self.space
+                    .ephemeral_fields
+                    .entry(String::from(key))
+                    .or_default()
+                    .insert(String::from(member), String::from(owner));
+            }
+            None => {
+                if let Some(members) = self.space.ephemeral_fields.get_mut(key) {
+                    members.remove(member);
+                    if members.is_empty() {
+                        self.space.ephemeral_fields.remove(key);
+                    }
+                }
+            }
+        }
+    }
+
+    /// 创建二级索引并根据已有数据生成索引，返回已索引的key数量
+    pub fn create_index(&mut self, name: &str, def: IndexDef) -> anyhow::Result<usize> {
+        if !self.space.indexes.create(name, def) {
+            return Err(anyhow!("Index {} already exists", name));
+        }
+        self.flush_dirty();
+        let space = &mut self.space;
+        space.indexes.rebuild(name, &space.keys, &space.db);
+        Ok(space.indexes.get(name).map_or(0, |index| index.len()))
+    }
+
+    pub fn drop_index(&mut self, name: &str) -> bool {
+        self.space.indexes.drop(name)
+    }
+
+    /// 查询索引值在区间内的key，跳过已过期的key和成员，按照索引值和key排序，
+    /// rev为true时逆序，limit为0表示不限制数量
+    pub fn query_index(
+        &mut self,
+        name: &str,
+        min: Bound<IndexValue>,
+        max: Bound<IndexValue>,
+        rev: bool,
+        limit: usize,
+    ) -> anyhow::Result<Vec<String>> {
+        // 通过get_mut修改的key在查询前更新索引
+        self.flush_dirty();
+        let index = self
+            .space
+            .indexes
+            .get(name)
+            .ok_or_else(|| anyhow!("No such index {}", name))?;
+        let field = &index.def().field;
+        let live = |key: &&str| !self.is_expired(key) && !self.is_field_expired(key, field);
+        let limit = if limit == 0 { usize::MAX } else { limit };
+        let keys = index.range(min, max);
+        let keys: Vec<String> = if rev {
+            keys.rev().filter(live).take(limit).map(String::from).collect()
+        } else {
+            keys.filter(live).take(limit).map(String::from).collect()
+        };
+        Ok(keys)
+    }
+
+    /// 在key上执行处理器并返回处理器的结果，处理器返回错误时key保持不变。
+    /// 处理后的值为None时删除key，已存在的key保留过期时间
+    pub fn process_entry(
+        &mut self,
+        key: &str,
+        name: &str,
+        args: &[DBValue],
+    ) -> anyhow::Result<DBValue> {
+        let processor = self.processors.get(name)?;
+        self.expire_fields_if_needed(key);
+        let mut value = self.get(key).cloned().unwrap_or(DBValue::None);
+        let result = processor.process(key, &mut value, args)?;
+        self.write_back(key, value);
+        Ok(result)
+    }
+
+    /// 执行已加载的脚本，脚本只能读写声明的keys，执行失败时所有修改都被丢弃
+    pub fn eval_script(
+        &mut self,
+        sha: &str,
+        keys: &[String],
+        args: &[DBValue],
+    ) -> anyhow::Result<DBValue> {
+        let ast = self.scripts.get(sha)?;
+        self.run_script(&ast, keys, args)
+    }
+
+    /// 执行已编译的脚本，脚本只能读写keys中声明的key
+    pub fn run_script(
+        &mut self,
+        ast: &AST,
+        keys: &[String],
+        args: &[DBValue],
+    ) -> anyhow::Result<DBValue> {
+        let mut values = Vec::with_capacity(keys.len());
+        for key in keys {
+            self.expire_fields_if_needed(key);
+            values.push((key.clone(), self.get(key).cloned()));
+        }
+        let (result, writes) = self.scripts.run(ast, values, args, self.now())?;
+        for (key, value) in writes {
+            self.write_back(&key, value);
+            self.signal_ready(&key);
+        }
+        Ok(result)
+    }
+
+    // 写回修改后的值，值为None时删除key，已存在的key保留过期时间
+    fn write_back(&mut self, key: &str, value: DBValue) {
+        match (self.get(key), value) {
+            (Some(_), DBValue::None) => {
+                self.remove(key);
+            }
+            (None, DBValue::None) => {}
+            (Some(current), value) => {
+                if *current != value {
+                    if let Some(current) = self.get_mut(key) {
+                        *current = value;
+                    }
+                }
+            }
+            (None, value) => {
+                self.set(String::from(key), value);
+            }
+        }
+    }
+
+    /// 按key顺序从after之后遍历count个key，跳过已过期的key，
+    /// 返回遍历到的key以及下一次遍历的起点，遍历结束时起点为None
+    pub fn scan(&self, after: Option<&str>, count: usize) -> (Vec<&str>, Option<&str>) {
+        let start = match after {
+            Some(key) => Bound::Excluded(key),
+            None => Bound::Unbounded,
+        };
+        let mut iter = self.space.keys.range::<str, _>((start, Bound::Unbounded));
+        let mut keys = Vec::with_capacity(count);
+        let mut last = None;
+        for key in iter.by_ref().take(count) {
+            last = Some(key.as_str());
+            if !self.is_expired(key) {
+                keys.push(key.as_str());
+            }
+        }
+        match iter.next() {
+            Some(_) => (keys, last),
+            None => (keys, None),
+        }
+    }
+
+    /// 按成员字典序遍历key中Hash、Set类型的值，members从值中取出全部成员，只在生成快照时调用。
+    /// 返回的成员可能已经被删除，由调用方过滤
+    pub fn scan_members(
+        &mut self,
+        key: &str,
+        after: Option<&str>,
+        count: usize,
+        members: impl FnOnce(&DBValue) -> Vec<String>,
+    ) -> (Vec<String>, Option<String>) {
+        if self.is_expired(key) {
+            return (Vec::new(), None);
+        }
+        let qualified = blocking::qualified_key(&self.namespace, key);
+        let Some(value) = self.space.db.get(key) else {
+            return (Vec::new(), None);
+        };
+        self.member_snapshots
+            .page(&qualified, after, count, || members(value))
+    }
+
+    // 重新计算key的内存占用，同时更新覆盖该key的索引
+    fn account(&mut self, key: &str) {
+        if !self.space.indexes.is_empty() {
+            self.space.indexes.update(key, self.space.db.get(key));
+        }
+        let size = self.space.db.get(key).map(|value| eviction::key_size(key, value));
+        let now = self.now();
+        match (self.space.meta.get_mut(key), size) {
+            (Some(meta), Some(size)) => {
+                self.space.used_memory = self.space.used_memory - meta.size + size;
+                meta.size = size;
+                meta.touch(now);
+            }
+            (None, Some(size)) => {
+                self.space.used_memory += size;
+                self.space.meta.insert(String::from(key), KeyMeta::new(size, now));
+            }
+            (Some(_), None) => {
+                if let Some(meta) = self.space.meta.remove(key) {
+                    self.space.used_memory -= meta.size;
+                }
+            }
+            (None, None) => {}
+        }
+    }
+
+    fn flush_dirty(&mut self) {
+        let dirty: Vec<String> = self.space.dirty.drain().collect();
+        for key in dirty.iter() {
+            self.account(key);
+        }
+    }
+
+    /// 当前命名空间估算的内存占用(字节)
+    pub fn used_memory(&mut self) -> usize {
+        self.flush_dirty();
+        self.space.used_memory
+    }
+
+    /// 所有命名空间估算的内存占用(字节)
+    pub fn total_used_memory(&mut self) -> usize {
+        let others: usize = self.spaces.values().map(|space| space.used_memory).sum();
+        self.used_memory() + others
+    }
+
+    /// 内存超限且淘汰策略为noeviction时，拒绝可能增加内存的写命令
+    pub fn out_of_memory(&mut self) -> bool {
+        if self.eviction_policy != EvictionPolicy::NoEviction {
+            return false;
+        }
+        let quota = self.space.config.max_memory;
+        (quota > 0 && self.used_memory() > quota)
+            || (self.max_memory > 0 && self.total_used_memory() > self.max_memory)
+    }
+
+    /// 按照淘汰策略挑选需要淘汰的key，返回命名空间及其需要淘汰的key，内存未超限时返回空
+    pub fn eviction_plan(&mut self) -> Vec<(String, Vec<String>)> {
+        if self.eviction_policy == EvictionPolicy::NoEviction {
+            return vec![];
+        }
+        self.flush_dirty();
+        let now = self.now();
+        let policy = self.eviction_policy;
+        let mut spaces: Vec<(&str, &Keyspace)> = self
+            .spaces
+            .iter()
+            .map(|(name, space)| (name.as_str(), space))
+            .collect();
+        spaces.push((self.namespace.as_str(), &self.space));
+        spaces.sort_by(|a, b| a.0.cmp(b.0));
+
+        let mut plan: Vec<(String, Vec<String>)> = Vec::new();
+        // 超出配额的命名空间在自身范围内淘汰
+        for (name, space) in spaces.iter() {
+            let quota = space.config.max_memory;
+            if quota > 0 && space.used_memory > quota {
+                let victims = eviction::select_victims(
+                    policy,
+                    space.sampler.len(),
+                    |index| candidate(space, index, |key| String::from(key)),
+                    space.used_memory - quota,
+                    now,
+                );
+                if !victims.is_empty() {
+                    plan.push((String::from(*name), victims));
+                }
+            }
+        }
+        if !plan.is_empty() {
+            return plan;
+        }
+
+        // 超出全局上限时在所有命名空间中采样，命名空间作为key的前缀区分
+        let total: usize = spaces.iter().map(|(_, space)| space.used_memory).sum();
+        if self.max_memory == 0 || total <= self.max_memory {
+            return plan;
+        }
+        let population: usize = spaces.iter().map(|(_, space)| space.sampler.len()).sum();
+        let sample = |mut index: usize| {
+            for (name, space) in spaces.iter() {
+                if index < space.sampler.len() {
+                    return candidate(space, index, |key| blocking::qualified_key(name, key));
+                }
+                index -= space.sampler.len();
+            }
+            None
+        };
+        let victims =
+            eviction::select_victims(policy, population, sample, total - self.max_memory, now);
+        for victim in victims {
+            let (name, key) = blocking::split_qualified_key(&victim);
+            match plan.iter_mut().find(|(ns, _)| ns == name) {
+                Some((_, keys)) => keys.push(String::from(key)),
+                None => plan.push((String::from(name), vec![String::from(key)])),
+            }
+        }
+        plan
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        !self.is_expired(key) && self.space.db.contains_key(key)
+    }
+
+    /// 设置key的过期时间戳(毫秒)，key不存在时返回false，过期时间早于当前时间则直接删除
+    pub fn expire_at(&mut self, key: &str, deadline: u128) -> bool {
+        if !self.contains_key(key) {
+            return false;
+        }
+        if deadline <= self.now() {
+            self.remove(key);
+        } else {
+            self.space.expires.insert(String::from(key), deadline);
+        }
+        true
+    }
+
+    /// 清除key的过期时间，key不存在或者没有过期时间返回false
+    pub fn persist(&mut self, key: &str) -> bool {
+        if !self.contains_key(key) {
+            return false;
+        }
+        self.space.expires.remove(key).is_some()
+    }
+
+    /// key的过期时间戳(毫秒)，key不存在或者没有设置过期时间返回None
+    pub fn expire_time(&self, key: &str) -> Option<u128> {
+        if self.is_expired(key) {
+            return None;
+        }
+        self.space.expires.get(key)
+    }
+
+    fn is_expired(&self, key: &str) -> bool {
+        match self.space.expires.get(key) {
+            Some(deadline) => deadline <= self.now(),
+            None => false,
+        }
+    }
+
+    /// 惰性删除：访问时发现key已过期则删除
+    pub fn expire_if_needed(&mut self, key: &str) -> bool {
+        if self.is_expired(key) {
+            self.drop_key(key, WatchOp::Expire);
+            return true;
+        }
+        false
+    }
+
+    /// 设置hash成员的过期时间戳(毫秒)
+    pub fn set_field_expire(&mut self, key: &str, member: &str, deadline: u128) {
+        self.space.field_expires.insert(key, member, deadline);
+    }
+
+    /// 清除hash成员的过期时间，没有过期时间返回false
+    pub fn persist_field(&mut self, key: &str, member: &str) -> bool {
+        self.space.field_expires.remove(key, member).is_some()
+    }
+
+    /// hash成员的过期时间戳(毫秒)，没有设置过期时间或者已经过期返回None
+    pub fn field_expire_time(&self, key: &str, member: &str) -> Option<u128> {
+        if self.is_field_expired(key, member) {
+            return None;
+        }
+        self.space.field_expires.get(key, member)
+    }
+
+    pub fn is_field_expired(&self, key: &str, member: &str) -> bool {
+        match self.space.field_expires.get(key, member) {
+            Some(deadline) => deadline <= self.now(),
+            None => false,
+        }
+    }
+
+    /// 惰性删除hash中已经过期的成员，返回删除的成员数量
+    pub fn expire_fields_if_needed(&mut self, key: &str) -> usize {
+        let expired = self.space.field_expires.expired_members(key, self.now());
+        if expired.is_empty() {
+            return 0;
+        }
+        for member in expired.iter() {
+            self.space.field_expires.remove(key, member);
+        }
+        for member in expired.iter() {
+            self.set_ephemeral_field(key, member, None);
+        }
+        // 成员过期前的值，用于生成变更事件
+        let before = match self.snapshots.remove(key) {
+            Some(snapshot) => snapshot,
+            None if self.watch_enabled => self.space.db.get(key).cloned(),
+            None => None,
+        };
+        let mut remove_key = false;
+        if let Some(DBValue::Hash(hash)) = self.space.db.get_mut(key) {
+            for member in expired.iter() {
+                hash.remove(member);
+            }
+            remove_key = hash.is_empty() && self.remove_empty_hash;
+        }
+        if remove_key {
+            if self.watch_enabled {
+                self.snapshots.insert(String::from(key), before);
+            }
+            self.drop_key(key, WatchOp::Expire);
+        } else {
+            self.account(key);
+            if self.watch_enabled {
+                let after = self.space.db.get(key).cloned();
+                self.record(WatchOp::Expire, key, before, after.clone());
+                // 后续修改以成员过期后的值作为旧值
+                self.snapshots.insert(String::from(key), after);
+            }
+        }
+        expired.len()
+    }
+
+    /// 在给定时间点当前命名空间是否存在已经过期但尚未删除的key
+    pub fn has_expired_keys(&self, now: u128) -> bool {
+        self.space.has_expired_keys(now)
+    }
+
+    /// 在给定时间点存在已经过期但尚未删除的key的命名空间，按名称排序
+    pub fn expired_namespaces(&self, now: u128) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .spaces
+            .iter()
+            .filter(|(_, space)| space.has_expired_keys(now))
+            .map(|(name, _)| name.clone())
+            .collect();
+        if self.space.has_expired_keys(now) {
+            names.push(self.namespace.clone());
+        }
+        names.sort();
+        names
+    }
+
+    /// 主动删除最多limit个已过期的key或hash成员，按过期时间和key排序保证各副本删除的key一致，
+    /// 返回删除数量
+    pub fn active_expire(&mut self, limit: usize) -> usize {
+        let now = self.now();
+        let expired: Vec<String> = self
+            .space
+            .expires
+            .expired(now)
+            .take(limit)
+            .map(String::from)
+            .collect();
+        for key in expired.iter() {
+            self.drop_key(key, WatchOp::Expire);
+        }
+        let mut removed = expired.len();
+        self.space.stats.expired_keys += removed as u64;
+
+        // 剩余额度用于删除hash中过期的成员
+        let hashes = self.space.field_expires.expired_keys(now);
+        for key in hashes.iter() {
+            if removed >= limit {
+                break;
+            }
+            removed += self.expire_fields_if_needed(key);
+        }
+
+        // 租约到期的锁直接释放，不占用删除额度，唤醒等待锁的客户端
+        for name in self.space.locks.expire(now) {
+            self.signal_ready(&name);
+        }
+        removed
+    }
+}
+
+// 命名空间中第index个采样key作为淘汰候选，name生成淘汰结果中的key名称
+fn candidate<'a>(
+    space: &'a Keyspace,
+    index: usize,
+    name: impl Fn(&str) -> String,
+) -> Option<Candidate<'a>> {
+    let key = space.sampler.get(index)?;
+    Some(Candidate {
+        key: name(key),
+        meta: space.meta.get(key)?,
+        deadline: space.expires.get(key),
+    })
+}
+
+// 定时任务发起的提案类型
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum CronProposal {
+    BlockTimeout,
+    ActiveExpire,
+    SessionExpire,
+    Evict,
+}
+
+impl CronProposal {
+    fn of(cmd: &dyn ExecutableCommand) -> Option<CronProposal> {
+        let cmd = ExecutableCommand::as_any(cmd);
+        if cmd.is::<BlockTimeoutCmd>() {
+            Some(CronProposal::BlockTimeout)
+        } else if cmd.is::<ActiveExpireCmd>() {
+            Some(CronProposal::ActiveExpire)
+        } else if cmd.is::<SessionExpireCmd>() {
+            Some(CronProposal::SessionExpire)
+        } else if cmd.is::<EvictCmd>() {
+            Some(CronProposal::Evict)
+        } else {
+            None
+        }
+    }
+}
+
+// leader发起但尚未应用的定时任务提案，同一类提案全部应用之前不再发起新的提案，
+// 提案丢失时超时后重新发起。只在本节点有效，不参与复制
+struct PendingProposals {
+    timeout: Duration,
+    // 提案类型到(发起时间, 尚未应用的提案数量)
+    pending: AHashMap<CronProposal, (Instant, usize)>,
+}
+
+impl PendingProposals {
+    fn new(timeout: Duration) -> Self {
+        PendingProposals {
+            timeout,
+            pending: AHashMap::new(),
+        }
+    }
+
+    fn is_pending(&mut self, kind: CronProposal) -> bool {
+        match self.pending.get(&kind) {
+            Some((since, _)) if since.elapsed() < self.timeout => true,
+            Some(_) => {
+                self.pending.remove(&kind);
+                false
+            }
+            None => false,
+        }
+    }
+
+    // 应用了一条定时任务的提案
+    fn complete(&mut self, cmd: &dyn ExecutableCommand) {
+        let Some(kind) = CronProposal::of(cmd) else {
+            return;
+        };
+        if let Some((_, count)) = self.pending.get_mut(&kind) {
+            *count -= 1;
+            if *count == 0 {
+                self.pending.remove(&kind);
+            }
+        }
+    }
+
+    fn clear(&mut self) {
+        self.pending.clear();
+    }
+
+    // 发起一组同类提案，使用try_send避免数据库线程与集群线程互相等待对方的通道
+    async fn propose(&mut self, app: &Runtime, kind: CronProposal, commands: Vec<Command>) {
+        let mut proposed = 0;
+        for command in commands {
+            match app
+                .postman
+                .try_send(Box::new(ProposalCommand(command)))
+                .await
+            {
+                Ok(true) => proposed += 1,
+                Ok(false) => {}
+                Err(err) => error!("Propose {:?} error: {:?}", kind, err),
+            }
+        }
+        if proposed > 0 {
+            self.pending.insert(kind, (Instant::now(), proposed));
+        }
     }
 }
 
@@ -43,15 +1122,35 @@ pub fn start_db_cmd_channel(
     let hander = tokio::spawn(async move {
         info!("Database channel thread startup");
         let (mut done_ctx, _handler) = Context::with_parent(&ctx, None);
+        let mut ticker = interval(app.cfg.db_cron_interval);
+        let mut pending = PendingProposals::new(app.cfg.cron_proposal_timeout);
         loop {
             select! {
                 _ = done_ctx.done() => {
                     info!("Database channel loop stop");
                     break;
                 },
+                _ = ticker.tick() => {
+                    expire_blocked_clients(&mut db).await;
+                    // 定时任务的提案只由leader发起，避免各节点重复提案
+                    if app.is_leader() {
+                        propose_block_timeout(app.as_ref(), &db, &mut pending).await;
+                        propose_active_expire(app.as_ref(), &db, &mut pending).await;
+                        propose_session_expire(app.as_ref(), &db, &mut pending).await;
+                        propose_eviction(app.as_ref(), &mut db, &mut pending).await;
+                    } else {
+                        pending.clear();
+                    }
+                },
                 Some(command) = db_recv.recv() => {
                     if let Some(command ) = command.as_any().downcast_ref::<Command>() {
-                        match command.execute_and_send(Some(app.as_ref()), Some(&mut db)).await  {
+                        db.set_clock(command.ts());
+                        db.set_index(command.index());
+                        let result = execute_command(app.as_ref(), &mut db, command).await;
+                        pending.complete(command.inner_ref().as_ref());
+                        serve_blocked_clients(app.as_ref(), &mut db).await;
+                        dispatch_events(app.as_ref(), &mut db).await;
+                        match result {
                             Ok(_) => {
                                 // // 集群广播
                                 // if let Err(err) = cluster::broadcast(&conn_manager, &command).await {
@@ -70,3 +1169,329 @@ pub fn start_db_cmd_channel(
     });
     Ok(hander)
 }
+
+// 执行命令并回复结果，阻塞类命令在数据未就绪时挂起客户端
+pub(crate) async fn execute_command(
+    app: &Runtime,
+    db: &mut Database,
+    command: &Command,
+) -> anyhow::Result<()> {
+    db.select(command.namespace());
+    db.set_session(command.session());
+    if command.inner_ref().is_write_type() {
+        db.create_namespace();
+        db.keyspace_mut().stats.writes += 1;
+    } else {
+        db.keyspace_mut().stats.reads += 1;
+    }
+    // 内存统计在各副本上一致，拒绝结果也一致
+    if command.inner_ref().may_grow_memory() && db.out_of_memory() {
+        let err = anyhow!("OOM command not allowed when used memory > max memory");
+        return command.send(Err(err)).await;
+    }
+    match command.execute(Some(app), Some(db)).await {
+        Err(err) => match err.downcast::<BlockedError>() {
+            Ok(blocked) => {
+                let deadline = if blocked.timeout_ms == 0 {
+                    None
+                } else {
+                    Some(db.now() + blocked.timeout_ms as u128)
+                };
+                let keys = blocked
+                    .keys
+                    .iter()
+                    .map(|key| blocking::qualified_key(db.namespace(), key))
+                    .collect();
+                let command = match blocked.retry {
+                    Some(retry) => command.try_clone()?.with_cmd(retry)?,
+                    None => command.try_clone()?,
+                };
+                db.blocked.block(BlockedClient {
+                    command,
+                    keys,
+                    deadline,
+                });
+                Ok(())
+            }
+            Err(err) => command.send(Err(err)).await,
+        },
+        result => command.send(result).await,
+    }
+}
+
+// 重新执行等待已就绪key的阻塞客户端
+pub(crate) async fn serve_blocked_clients(app: &Runtime, db: &mut Database) {
+    while db.blocked.has_ready() {
+        let mut still_blocked = Vec::new();
+        for client in db.blocked.take_ready() {
+            db.select(client.command.namespace());
+            db.set_session(client.command.session());
+            match client.command.execute(Some(app), Some(db)).await {
+                Err(err) if err.is::<BlockedError>() => still_blocked.push(client),
+                result => {
+                    if let Err(err) = client.command.send(result).await {
+                        error!("Reply blocked client error: {:?}", err);
+                    }
+                }
+            }
+        }
+        db.blocked.restore(still_blocked);
+    }
+}
+
+// 命令应用后将产生的变更事件投递给监听者，raft提交的命令在各副本上产生相同的事件
+async fn dispatch_events(app: &Runtime, db: &mut Database) {
+    let events = db.take_events();
+    if !events.is_empty() {
+        app.watch.dispatch(events).await;
+    }
+}
+
+// 超时的本地阻塞客户端返回空结果
+pub(crate) async fn expire_blocked_clients(db: &mut Database) {
+    if db.blocked.is_empty() {
+        return;
+    }
+    let now = until::now_ts().unwrap_or(0);
+    for client in db.blocked.take_expired(now) {
+        if let Err(err) = client.command.send(Ok(None)).await {
+            error!("Reply blocked client error: {:?}", err);
+        }
+    }
+}
+
+// 经raft复制的阻塞客户端在各副本上都存在，由leader发起超时提案，
+// 各副本在相同的日志位置移除客户端，避免按照各自的时钟超时导致唤醒结果不一致
+async fn propose_block_timeout(app: &Runtime, db: &Database, pending: &mut PendingProposals) {
+    if db.blocked.is_empty() || pending.is_pending(CronProposal::BlockTimeout) {
+        return;
+    }
+    let now = until::now_ts().unwrap_or(0);
+    let indexes = db.blocked.timed_out(now);
+    if indexes.is_empty() {
+        return;
+    }
+    let command = Command::new(Box::new(BlockTimeoutCmd { indexes }), None).with_ts(now);
+    pending
+        .propose(app, CronProposal::BlockTimeout, vec![command])
+        .await;
+}
+
+// 主动过期：存在过期key时发起删除提案，删除在raft提交后按照提案时间戳执行，保证各副本一致
+async fn propose_active_expire(app: &Runtime, db: &Database, pending: &mut PendingProposals) {
+    if pending.is_pending(CronProposal::ActiveExpire) {
+        return;
+    }
+    let now = until::now_ts().unwrap_or(0);
+    let commands = db
+        .expired_namespaces(now)
+        .into_iter()
+        .map(|namespace| {
+            let sweep = ActiveExpireCmd {
+                limit: app.cfg.active_expire_limit,
+            };
+            Command::new(Box::new(sweep), None)
+                .with_ts(now)
+                .with_namespace(namespace)
+        })
+        .collect();
+    pending
+        .propose(app, CronProposal::ActiveExpire, commands)
+        .await;
+}
+
+// 存在过期会话时发起关闭提案，raft提交后各副本删除相同的临时数据
+async fn propose_session_expire(app: &Runtime, db: &Database, pending: &mut PendingProposals) {
+    if pending.is_pending(CronProposal::SessionExpire) {
+        return;
+    }
+    let now = until::now_ts().unwrap_or(0);
+    let sessions = db.expired_sessions(now);
+    if sessions.is_empty() {
+        return;
+    }
+    let command = Command::new(Box::new(SessionExpireCmd { sessions }), None).with_ts(now);
+    pending
+        .propose(app, CronProposal::SessionExpire, vec![command])
+        .await;
+}
+
+// 内存超限时由leader挑选淘汰的key并发起提案，raft提交后各副本删除相同的key
+async fn propose_eviction(app: &Runtime, db: &mut Database, pending: &mut PendingProposals) {
+    if pending.is_pending(CronProposal::Evict) {
+        return;
+    }
+    let commands = db
+        .eviction_plan()
+        .into_iter()
+        .map(|(namespace, keys)| {
+            Command::new(Box::new(EvictCmd { keys }), None).with_namespace(namespace)
+        })
+        .collect();
+    pending.propose(app, CronProposal::Evict, commands).await;
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        execute_command, expire_blocked_clients, serve_blocked_clients, CronProposal, Database,
+        PendingProposals,
+    };
+    use crate::command::Command;
+    use crate::db::dbvalue::DBValue;
+    use crate::db::eviction::EvictionPolicy;
+    use crate::proto::{
+        BlPopCmd, BlockTimeoutCmd, LPushCmd, LatchAwaitCmd, LatchCountDownCmd, LatchTrySetCountCmd,
+        SetCmd,
+    };
+    use crate::runtime::Runtime;
+    use std::time::{Duration, Instant};
+    use tokio::sync::mpsc;
+
+    #[tokio::test]
+    async fn blocked_pop_wakeup_test() {
+        let app = Runtime::new_with_default_config();
+        let mut db = Database::new();
+        let (tx, mut rx) = mpsc::channel(1);
+        let blpop = Command::new(
+            Box::new(BlPopCmd {
+                keys: vec![String::from("queue")],
+                timeout_ms: 0,
+            }),
+            Some(tx),
+        );
+        execute_command(&app, &mut db, &blpop).await.unwrap();
+        assert_eq!(db.blocked.len(), 1);
+        assert!(rx.try_recv().is_err());
+
+        let lpush = Command::new(
+            Box::new(LPushCmd {
+                key: String::from("queue"),
+                values: vec![DBValue::String(String::from("job")).into()],
+            }),
+            None,
+        );
+        execute_command(&app, &mut db, &lpush).await.unwrap();
+        serve_blocked_clients(&app, &mut db).await;
+        assert!(db.blocked.is_empty());
+        match rx.recv().await {
+            Some(Ok(Some(DBValue::List(values)))) => {
+                assert!(values[0] == DBValue::String(String::from("queue")));
+                assert!(values[1] == DBValue::String(String::from("job")));
+            }
+            _ => panic!("blocked client should receive popped value"),
+        }
+        assert!(!db.contains_key("queue"));
+    }
+
+    #[tokio::test]
+    async fn replicated_block_timeout_test() {
+        let app = Runtime::new_with_default_config();
+        let mut db = Database::new();
+        db.set_clock(1_000);
+        let (tx, mut rx) = mpsc::channel(1);
+        let blpop = Command::new(
+            Box::new(BlPopCmd {
+                keys: vec![String::from("queue")],
+                timeout_ms: 100,
+            }),
+            Some(tx),
+        )
+        .with_index(5);
+        execute_command(&app, &mut db, &blpop).await.unwrap();
+        // 经raft复制的客户端不按本节点的时钟超时
+        expire_blocked_clients(&mut db).await;
+        assert_eq!(db.blocked.len(), 1);
+        assert!(db.blocked.timed_out(1_099).is_empty());
+        assert_eq!(db.blocked.timed_out(1_100), vec![5]);
+
+        let mut pending = PendingProposals::new(Duration::from_secs(10));
+        pending
+            .pending
+            .insert(CronProposal::BlockTimeout, (Instant::now(), 1));
+        assert!(pending.is_pending(CronProposal::BlockTimeout));
+        let timeout = Command::new(Box::new(BlockTimeoutCmd { indexes: vec![5] }), None);
+        execute_command(&app, &mut db, &timeout).await.unwrap();
+        pending.complete(timeout.inner_ref().as_ref());
+        assert!(!pending.is_pending(CronProposal::BlockTimeout));
+        assert!(db.blocked.is_empty());
+        assert!(matches!(rx.recv().await, Some(Ok(None))));
+    }
+
+    #[tokio::test]
+    async fn latch_await_wakeup_test() {
+        let app = Runtime::new_with_default_config();
+        let mut db = Database::new();
+        let name = String::from("workers");
+        let set_count = Command::new(
+            Box::new(LatchTrySetCountCmd {
+                name: name.clone(),
+                count: 2,
+            }),
+            None,
+        );
+        execute_command(&app, &mut db, &set_count).await.unwrap();
+        let (tx, mut rx) = mpsc::channel(1);
+        let wait = Command::new(
+            Box::new(LatchAwaitCmd {
+                name: name.clone(),
+                timeout_ms: 0,
+            }),
+            Some(tx),
+        );
+        execute_command(&app, &mut db, &wait).await.unwrap();
+        assert_eq!(db.blocked.len(), 1);
+
+        let count_down = Command::new(Box::new(LatchCountDownCmd { name }), None);
+        for remaining in [1, 0] {
+            execute_command(&app, &mut db, &count_down).await.unwrap();
+            serve_blocked_clients(&app, &mut db).await;
+            assert_eq!(db.blocked.len(), remaining);
+        }
+        assert!(matches!(rx.recv().await, Some(Ok(Some(DBValue::Boolean(true))))));
+    }
+
+    #[tokio::test]
+    async fn memory_limit_test() {
+        let app = Runtime::new_with_default_config();
+        let mut db = Database::new();
+        db.set_clock(1_000);
+        db.set(String::from("a"), DBValue::String(String::from("hello")));
+        db.set_clock(2_000);
+        db.set(String::from("b"), DBValue::List(Default::default()));
+        let used = db.used_memory();
+        assert!(used > 0);
+
+        // 修改集合后重新统计内存
+        if let Some(DBValue::List(list)) = db.get_mut("b") {
+            list.push_back(DBValue::String(String::from("x").repeat(100)));
+        }
+        assert!(db.used_memory() >= used + 100);
+
+        db.max_memory = used;
+        assert!(db.out_of_memory());
+        let (tx, mut rx) = mpsc::channel(1);
+        let set = Command::new(
+            Box::new(SetCmd {
+                key: String::from("c"),
+                value: Some(DBValue::String(String::from("v")).into()),
+                expire_ms: 0,
+                ephemeral: false,
+            }),
+            Some(tx),
+        );
+        execute_command(&app, &mut db, &set).await.unwrap();
+        assert!(matches!(rx.recv().await, Some(Err(_))));
+        assert!(!db.contains_key("c"));
+
+        db.eviction_policy = EvictionPolicy::AllKeysLru;
+        assert!(!db.out_of_memory());
+        // 最久未访问的key优先淘汰
+        let plan = db.eviction_plan();
+        assert_eq!(plan[0].0, "default");
+        assert_eq!(plan[0].1[0], "a");
+        db.remove("a");
+        db.remove("b");
+        assert_eq!(db.used_memory(), 0);
+    }
+}
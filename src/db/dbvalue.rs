@@ -1,19 +1,45 @@
 use std::fmt::{Display, Formatter};
 
 use crate::proto::db_value::Value as DbValueEnum;
+use crate::proto::BloomFilter as PBloomFilter;
+use crate::proto::BloomLayer as PBloomLayer;
 use crate::proto::DbValue as PDbValue;
 use crate::proto::Hash as PHash;
+use crate::proto::HyperLogLog as PHyperLogLog;
 use crate::proto::List as PList;
-use ahash::AHashMap;
+use crate::proto::Set as PSet;
+use crate::proto::SortedSet as PSortedSet;
+use crate::proto::SortedSetEntry as PSortedSetEntry;
+use crate::proto::Stream as PStream;
+use crate::proto::StreamEntry as PStreamEntry;
+use crate::proto::StreamField as PStreamField;
+use crate::proto::StreamGroup as PStreamGroup;
+use crate::proto::StreamPendingEntry as PStreamPendingEntry;
+use ahash::{AHashMap, AHashSet};
+use prost_types::Timestamp;
+use std::collections::VecDeque;
 
-#[derive(Clone)]
+use super::bloom::{BloomFilter, BloomLayer};
+use super::hyperloglog::HyperLogLog;
+use super::sorted_set::SortedSet;
+use super::stream::{ConsumerGroup, PendingEntry, Stream, StreamId};
+
+#[derive(Clone, PartialEq)]
 pub enum DBValue {
     None,
     Boolean(bool),
     String(String),
     Bytes(Vec<u8>),
-    List(Vec<DBValue>),
+    List(VecDeque<DBValue>),
     Hash(AHashMap<String, DBValue>),
+    Set(AHashSet<String>),
+    SortedSet(SortedSet),
+    Int64(i64),
+    Float64(f64),
+    Timestamp(Timestamp),
+    Stream(Stream),
+    HyperLogLog(HyperLogLog),
+    BloomFilter(BloomFilter),
 }
 
 impl Display for DBValue {
@@ -53,6 +79,56 @@ impl Display for DBValue {
                 }
                 write!(f, ")")?;
             }
+            Self::Set(s) => {
+                write!(f, "DBValue::Set(")?;
+                let mut iter = s.iter();
+                if let Some(first) = iter.next() {
+                    write!(f, "{}", first)?;
+                    for item in iter {
+                        write!(f, ",{}", item)?;
+                    }
+                }
+                write!(f, ")")?;
+            }
+            Self::SortedSet(z) => {
+                write!(f, "DBValue::SortedSet(")?;
+                let mut iter = z.iter();
+                if let Some((member, score)) = iter.next() {
+                    write!(f, "{}={}", member, score)?;
+                    for (member, score) in iter {
+                        write!(f, ",{}={}", member, score)?;
+                    }
+                }
+                write!(f, ")")?;
+            }
+            Self::Int64(v) => {
+                write!(f, "DBValue::Int64({})", v)?;
+            }
+            Self::Float64(v) => {
+                write!(f, "DBValue::Float64({})", v)?;
+            }
+            Self::Timestamp(v) => {
+                write!(f, "DBValue::Timestamp({})", v)?;
+            }
+            Self::Stream(v) => {
+                write!(
+                    f,
+                    "DBValue::Stream({} entries, last_id={})",
+                    v.len(),
+                    v.last_id()
+                )?;
+            }
+            Self::HyperLogLog(v) => {
+                write!(f, "DBValue::HyperLogLog(count={})", v.count())?;
+            }
+            Self::BloomFilter(v) => {
+                write!(
+                    f,
+                    "DBValue::BloomFilter({} items, capacity={})",
+                    v.len(),
+                    v.capacity()
+                )?;
+            }
         };
         Ok(())
     }
@@ -96,6 +172,47 @@ impl Display for PDbValue {
                     }
                     write!(f, ")")?;
                 }
+                DbValueEnum::Set(v) => {
+                    write!(f, "DBValue::Set({})", v.members.join(","))?;
+                }
+                DbValueEnum::SortedSet(v) => {
+                    write!(f, "DBValue::SortedSet(")?;
+                    let mut iter = v.entries.iter();
+                    if let Some(entry) = iter.next() {
+                        write!(f, "{}={}", entry.member, entry.score)?;
+                        for entry in iter {
+                            write!(f, ",{}={}", entry.member, entry.score)?;
+                        }
+                    }
+                    write!(f, ")")?;
+                }
+                DbValueEnum::Int64(v) => {
+                    write!(f, "DBValue::Int64({})", v)?;
+                }
+                DbValueEnum::Float64(v) => {
+                    write!(f, "DBValue::Float64({})", v)?;
+                }
+                DbValueEnum::Timestamp(v) => {
+                    write!(f, "DBValue::Timestamp({})", v)?;
+                }
+                DbValueEnum::Stream(v) => {
+                    write!(
+                        f,
+                        "DBValue::Stream({} entries, last_id={})",
+                        v.entries.len(),
+                        v.last_id
+                    )?;
+                }
+                DbValueEnum::HyperLogLog(v) => {
+                    write!(
+                        f,
+                        "DBValue::HyperLogLog({} bytes)",
+                        v.sparse.len() + v.dense.len()
+                    )?;
+                }
+                DbValueEnum::BloomFilter(v) => {
+                    write!(f, "DBValue::BloomFilter({} layers)", v.layers.len())?;
+                }
             }
         }
         Ok(())
@@ -109,6 +226,26 @@ impl From<DBValue> for PDbValue {
 }
 
 impl DBValue {
+    // 值类型名称，用于TYPE命令
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            DBValue::None => "none",
+            DBValue::Boolean(_) => "boolean",
+            DBValue::String(_) => "string",
+            DBValue::Bytes(_) => "bytes",
+            DBValue::List(_) => "list",
+            DBValue::Hash(_) => "hash",
+            DBValue::Set(_) => "set",
+            DBValue::SortedSet(_) => "zset",
+            DBValue::Int64(_) => "int64",
+            DBValue::Float64(_) => "float64",
+            DBValue::Timestamp(_) => "timestamp",
+            DBValue::Stream(_) => "stream",
+            DBValue::HyperLogLog(_) => "hyperloglog",
+            DBValue::BloomFilter(_) => "bloom",
+        }
+    }
+
     pub fn to_protobuf(&self) -> PDbValue {
         let value = match self {
             DBValue::None => Some(DbValueEnum::None(false)),
@@ -124,6 +261,27 @@ impl DBValue {
                     .map(|(k, v)| (k.clone(), v.to_protobuf()))
                     .collect(),
             })),
+            DBValue::Set(members) => Some(DbValueEnum::Set(PSet {
+                members: members.iter().cloned().collect(),
+            })),
+            DBValue::SortedSet(zset) => Some(DbValueEnum::SortedSet(PSortedSet {
+                entries: zset
+                    .iter()
+                    .map(|(member, score)| PSortedSetEntry {
+                        member: String::from(member),
+                        score,
+                    })
+                    .collect(),
+            })),
+            DBValue::Int64(v) => Some(DbValueEnum::Int64(*v)),
+            DBValue::Float64(v) => Some(DbValueEnum::Float64(*v)),
+            DBValue::Timestamp(v) => Some(DbValueEnum::Timestamp(*v)),
+            DBValue::Stream(stream) => Some(DbValueEnum::Stream(stream_to_protobuf(stream))),
+            DBValue::HyperLogLog(hll) => {
+                let (sparse, dense) = hll.encode();
+                Some(DbValueEnum::HyperLogLog(PHyperLogLog { sparse, dense }))
+            }
+            DBValue::BloomFilter(bloom) => Some(DbValueEnum::BloomFilter(bloom_to_protobuf(bloom))),
         };
         PDbValue { value }
     }
@@ -141,7 +299,7 @@ impl From<PDbValue> for DBValue {
             DbValueEnum::String(s) => DBValue::String(s),
             DbValueEnum::Bytes(b) => DBValue::Bytes(b),
             DbValueEnum::List(l) => {
-                let l: Vec<DBValue> = l.value.into_iter().map(|x| x.into()).collect();
+                let l: VecDeque<DBValue> = l.value.into_iter().map(|x| x.into()).collect();
                 DBValue::List(l)
             }
             DbValueEnum::Hash(h) => {
@@ -149,10 +307,142 @@ impl From<PDbValue> for DBValue {
                     h.values.into_iter().map(|(k, v)| (k, v.into())).collect();
                 DBValue::Hash(h)
             }
+            DbValueEnum::Set(s) => DBValue::Set(s.members.into_iter().collect()),
+            DbValueEnum::SortedSet(z) => {
+                let mut zset = SortedSet::new();
+                for entry in z.entries {
+                    zset.insert(entry.member, entry.score);
+                }
+                DBValue::SortedSet(zset)
+            }
+            DbValueEnum::Int64(v) => DBValue::Int64(v),
+            DbValueEnum::Float64(v) => DBValue::Float64(v),
+            DbValueEnum::Timestamp(v) => DBValue::Timestamp(v),
+            DbValueEnum::Stream(s) => DBValue::Stream(stream_from_protobuf(s)),
+            // 损坏的数据解码为空的HyperLogLog
+            DbValueEnum::HyperLogLog(h) => {
+                DBValue::HyperLogLog(HyperLogLog::decode(&h.sparse, &h.dense).unwrap_or_default())
+            }
+            // 损坏的数据解码为空的布隆过滤器
+            DbValueEnum::BloomFilter(b) => DBValue::BloomFilter(bloom_from_protobuf(b)),
         }
     }
 }
 
+fn bloom_to_protobuf(bloom: &BloomFilter) -> PBloomFilter {
+    PBloomFilter {
+        error_rate: bloom.error_rate,
+        expansion: bloom.expansion,
+        layers: bloom
+            .layers
+            .iter()
+            .map(|layer| PBloomLayer {
+                capacity: layer.capacity,
+                count: layer.count,
+                error_rate: layer.error_rate,
+                hashes: layer.hashes,
+                bits: layer.bits,
+                data: layer.data.clone(),
+            })
+            .collect(),
+    }
+}
+
+fn bloom_from_protobuf(bloom: PBloomFilter) -> BloomFilter {
+    let layers = bloom
+        .layers
+        .into_iter()
+        .map(|layer| BloomLayer {
+            capacity: layer.capacity,
+            count: layer.count,
+            error_rate: layer.error_rate,
+            hashes: layer.hashes,
+            bits: layer.bits,
+            data: layer.data,
+        })
+        .collect();
+    BloomFilter::decode(bloom.error_rate, bloom.expansion, layers).unwrap_or_default()
+}
+
+fn stream_fields_to_protobuf(fields: &[(String, DBValue)]) -> Vec<PStreamField> {
+    fields
+        .iter()
+        .map(|(name, value)| PStreamField {
+            name: name.clone(),
+            value: Some(value.to_protobuf()),
+        })
+        .collect()
+}
+
+fn stream_to_protobuf(stream: &Stream) -> PStream {
+    PStream {
+        entries: stream
+            .iter()
+            .map(|(id, fields)| PStreamEntry {
+                id: id.to_string(),
+                fields: stream_fields_to_protobuf(fields),
+            })
+            .collect(),
+        last_id: stream.last_id().to_string(),
+        groups: stream
+            .groups()
+            .map(|(name, group)| PStreamGroup {
+                name: name.clone(),
+                last_delivered: group.last_delivered.to_string(),
+                pending: group
+                    .pending
+                    .iter()
+                    .map(|(id, pending)| PStreamPendingEntry {
+                        id: id.to_string(),
+                        consumer: pending.consumer.clone(),
+                        delivered_ms: pending.delivered_at as u64,
+                        deliveries: pending.deliveries,
+                    })
+                    .collect(),
+            })
+            .collect(),
+    }
+}
+
+// 流的ID都由服务端生成，解析失败的ID使用0-0
+fn stream_from_protobuf(value: PStream) -> Stream {
+    let parse = |id: &str| StreamId::parse(id).unwrap_or_default();
+    let mut stream = Stream::new();
+    for entry in value.entries {
+        let fields = entry
+            .fields
+            .into_iter()
+            .map(|field| (field.name, field.value.map_or(DBValue::None, DBValue::from)))
+            .collect();
+        // 按照ID升序写入，不会失败
+        let _ = stream.add(parse(&entry.id), fields);
+    }
+    stream.set_last_id(parse(&value.last_id));
+    for group in value.groups {
+        let pending = group
+            .pending
+            .into_iter()
+            .map(|pending| {
+                let entry = PendingEntry {
+                    consumer: pending.consumer,
+                    delivered_at: pending.delivered_ms as u128,
+                    deliveries: pending.deliveries,
+                };
+                (parse(&pending.id), entry)
+            })
+            .collect();
+        let last_delivered = parse(&group.last_delivered);
+        stream.insert_group(
+            group.name,
+            ConsumerGroup {
+                last_delivered,
+                pending,
+            },
+        );
+    }
+    stream
+}
+
 impl From<PDbValue> for DbValueEnum {
     fn from(value: PDbValue) -> Self {
         if let None = value.value {
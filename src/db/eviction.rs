@@ -0,0 +1,300 @@
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::str::FromStr;
+
+use ahash::AHashMap;
+use anyhow::anyhow;
+
+use super::dbvalue::DBValue;
+use crate::until::SplitMix64;
+
+// 每个key在哈希表中的固定开销(估算值)
+const ENTRY_OVERHEAD: usize = 48;
+// 访问频率每闲置该时长(毫秒)减半
+const LFU_DECAY_MS: u128 = 60_000;
+// 每挑选一个淘汰key随机采样的key数量，与Redis的maxmemory-samples相同
+const EVICTION_SAMPLES: usize = 5;
+// 单次淘汰计划最多挑选的key数量，未释放足够内存时在下次定时任务中继续淘汰
+const MAX_VICTIMS: usize = 128;
+
+/// 内存超限时的淘汰策略
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EvictionPolicy {
+    // 不淘汰，拒绝可能增加内存的写命令
+    NoEviction,
+    // 淘汰最久未访问的key
+    AllKeysLru,
+    // 淘汰访问频率最低的key
+    AllKeysLfu,
+    // 淘汰最先过期的key，只考虑设置了过期时间的key
+    VolatileTtl,
+    // 随机淘汰
+    Random,
+}
+
+impl FromStr for EvictionPolicy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "noeviction" => Ok(EvictionPolicy::NoEviction),
+            "allkeys-lru" => Ok(EvictionPolicy::AllKeysLru),
+            "allkeys-lfu" => Ok(EvictionPolicy::AllKeysLfu),
+            "volatile-ttl" => Ok(EvictionPolicy::VolatileTtl),
+            "random" => Ok(EvictionPolicy::Random),
+            _ => Err(anyhow!("Unknown eviction policy {}", s)),
+        }
+    }
+}
+
+/// key的内存占用和访问统计，访问统计只在本地用于挑选淘汰的key，淘汰结果经raft复制
+pub struct KeyMeta {
+    pub size: usize,
+    // 读命令只持有数据库的不可变引用，访问统计使用原子变量更新
+    last_access: AtomicU64,
+    hits: AtomicU32,
+}
+
+impl KeyMeta {
+    pub fn new(size: usize, now: u128) -> Self {
+        KeyMeta {
+            size,
+            last_access: AtomicU64::new(now as u64),
+            hits: AtomicU32::new(1),
+        }
+    }
+
+    pub fn touch(&self, now: u128) {
+        let hits = self.frequency(now).saturating_add(1);
+        self.hits.store(hits, Ordering::Relaxed);
+        self.last_access.fetch_max(now as u64, Ordering::Relaxed);
+    }
+
+    pub fn last_access(&self) -> u128 {
+        self.last_access.load(Ordering::Relaxed) as u128
+    }
+
+    /// 按闲置时长衰减后的访问频率
+    pub fn frequency(&self, now: u128) -> u32 {
+        let periods = now.saturating_sub(self.last_access()) / LFU_DECAY_MS;
+        if periods >= 32 {
+            0
+        } else {
+            self.hits.load(Ordering::Relaxed) >> periods
+        }
+    }
+}
+
+/// 估算key和值占用的内存字节数
+pub fn key_size(key: &str, value: &DBValue) -> usize {
+    ENTRY_OVERHEAD + key.len() + value_size(value)
+}
+
+fn value_size(value: &DBValue) -> usize {
+    match value {
+        DBValue::None | DBValue::Boolean(_) | DBValue::Int64(_) | DBValue::Float64(_) => 8,
+        DBValue::Timestamp(_) => 16,
+        DBValue::String(s) => 24 + s.len(),
+        DBValue::Bytes(b) => 24 + b.len(),
+        DBValue::List(list) => 32 + list.iter().map(value_size).sum::<usize>(),
+        DBValue::Hash(hash) => {
+            32 + hash
+                .iter()
+                .map(|(k, v)| 24 + k.len() + value_size(v))
+                .sum::<usize>()
+        }
+        DBValue::Set(set) => 32 + set.iter().map(|m| 24 + m.len()).sum::<usize>(),
+        // 成员同时保存在哈希表和有序索引中
+        DBValue::SortedSet(zset) => {
+            64 + zset
+                .iter()
+                .map(|(m, _)| 2 * (24 + m.len()) + 16)
+                .sum::<usize>()
+        }
+        // 待确认消息按照每条固定大小估算
+        DBValue::Stream(stream) => {
+            64 + stream
+                .iter()
+                .map(|(_, fields)| {
+                    32 + fields
+                        .iter()
+                        .map(|(name, value)| 24 + name.len() + value_size(value))
+                        .sum::<usize>()
+                })
+                .sum::<usize>()
+                + stream
+                    .groups()
+                    .map(|(name, group)| 64 + name.len() + 48 * group.pending.len())
+                    .sum::<usize>()
+        }
+        DBValue::HyperLogLog(hll) => hll.mem_size(),
+        DBValue::BloomFilter(bloom) => bloom.mem_size(),
+    }
+}
+
+/// 可随机访问的key集合，用于淘汰时随机采样
+#[derive(Default)]
+pub struct KeySampler {
+    keys: Vec<String>,
+    positions: AHashMap<String, usize>,
+}
+
+impl KeySampler {
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> Option<&str> {
+        self.keys.get(index).map(String::as_str)
+    }
+
+    pub fn insert(&mut self, key: &str) {
+        if !self.positions.contains_key(key) {
+            self.positions.insert(String::from(key), self.keys.len());
+            self.keys.push(String::from(key));
+        }
+    }
+
+    pub fn remove(&mut self, key: &str) {
+        if let Some(index) = self.positions.remove(key) {
+            self.keys.swap_remove(index);
+            if let Some(moved) = self.keys.get(index) {
+                self.positions.insert(moved.clone(), index);
+            }
+        }
+    }
+}
+
+/// 淘汰候选key
+pub struct Candidate<'a> {
+    pub key: String,
+    pub meta: &'a KeyMeta,
+    pub deadline: Option<u128>,
+}
+
+// 按照淘汰策略candidate是否比current更应该被淘汰，分值相同时按key比较保证结果稳定
+fn prefer(policy: EvictionPolicy, candidate: &Candidate, current: &Candidate, now: u128) -> bool {
+    let order = match policy {
+        EvictionPolicy::AllKeysLru => candidate
+            .meta
+            .last_access()
+            .cmp(&current.meta.last_access()),
+        EvictionPolicy::AllKeysLfu => (candidate.meta.frequency(now), candidate.meta.last_access())
+            .cmp(&(current.meta.frequency(now), current.meta.last_access())),
+        EvictionPolicy::VolatileTtl => candidate.deadline.cmp(&current.deadline),
+        // 随机淘汰时采样的第一个key即为淘汰的key
+        EvictionPolicy::Random | EvictionPolicy::NoEviction => return false,
+    };
+    order.then_with(|| candidate.key.cmp(&current.key)) == std::cmp::Ordering::Less
+}
+
+/// 按照淘汰策略挑选需要淘汰的key，直到释放的内存不少于need。
+/// 每个淘汰的key从population个key中随机采样EVICTION_SAMPLES个后挑选，sample按下标返回候选key
+pub fn select_victims<'a>(
+    policy: EvictionPolicy,
+    population: usize,
+    mut sample: impl FnMut(usize) -> Option<Candidate<'a>>,
+    need: usize,
+    now: u128,
+) -> Vec<String> {
+    if policy == EvictionPolicy::NoEviction || population == 0 {
+        return vec![];
+    }
+    let mut rng = SplitMix64::new(now as u64);
+    let mut freed = 0;
+    let mut victims: Vec<String> = Vec::new();
+    let limit = MAX_VICTIMS.min(population);
+    // 采样可能全部命中已经挑选的key，限制轮数避免key较少时反复采样
+    for _ in 0..2 * MAX_VICTIMS {
+        if freed >= need || victims.len() >= limit {
+            break;
+        }
+        let mut best: Option<Candidate> = None;
+        for _ in 0..EVICTION_SAMPLES {
+            let Some(candidate) = sample(rng.next_below(population)) else {
+                continue;
+            };
+            if victims.contains(&candidate.key) {
+                continue;
+            }
+            if policy == EvictionPolicy::VolatileTtl && candidate.deadline.is_none() {
+                continue;
+            }
+            match &best {
+                Some(current) if !prefer(policy, &candidate, current, now) => {}
+                _ => best = Some(candidate),
+            }
+        }
+        if let Some(victim) = best {
+            freed += victim.meta.size;
+            victims.push(victim.key);
+        }
+    }
+    victims
+}
+
+#[cfg(test)]
+mod test {
+    use super::{select_victims, Candidate, EvictionPolicy, KeyMeta, KeySampler};
+
+    #[test]
+    fn select_victims_test() {
+        let old = KeyMeta::new(100, 1_000);
+        let hot = KeyMeta::new(100, 2_000);
+        for _ in 0..10 {
+            hot.touch(2_000);
+        }
+        let recent = KeyMeta::new(100, 3_000);
+        let keys = [
+            ("old", &old, None),
+            ("hot", &hot, Some(9_000)),
+            ("recent", &recent, Some(5_000)),
+        ];
+        let sample = |i: usize| {
+            let (key, meta, deadline) = keys[i];
+            Some(Candidate {
+                key: String::from(key),
+                meta,
+                deadline,
+            })
+        };
+        assert_eq!(
+            select_victims(EvictionPolicy::AllKeysLru, 3, sample, 50, 4_000),
+            vec!["old"]
+        );
+        assert_eq!(
+            select_victims(EvictionPolicy::AllKeysLfu, 3, sample, 50, 4_000),
+            vec!["old"]
+        );
+        assert_eq!(
+            select_victims(EvictionPolicy::VolatileTtl, 3, sample, 50, 4_000),
+            vec!["recent"]
+        );
+        let mut all = select_victims(EvictionPolicy::Random, 3, sample, 300, 4_000);
+        all.sort();
+        assert_eq!(all, vec!["hot", "old", "recent"]);
+        assert!(select_victims(EvictionPolicy::NoEviction, 3, sample, 300, 4_000).is_empty());
+        assert!("allkeys-lfu".parse::<EvictionPolicy>().is_ok());
+        assert!("volatile-lru".parse::<EvictionPolicy>().is_err());
+    }
+
+    #[test]
+    fn key_sampler_test() {
+        let mut sampler = KeySampler::default();
+        for key in ["a", "b", "c"] {
+            sampler.insert(key);
+        }
+        sampler.insert("a");
+        sampler.remove("a");
+        assert_eq!(sampler.len(), 2);
+        let mut keys: Vec<&str> = (0..sampler.len()).filter_map(|i| sampler.get(i)).collect();
+        keys.sort();
+        assert_eq!(keys, vec!["b", "c"]);
+        sampler.remove("c");
+        sampler.remove("missing");
+        assert_eq!(sampler.get(0), Some("b"));
+    }
+}
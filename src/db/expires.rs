@@ -0,0 +1,160 @@
+use std::collections::BTreeSet;
+
+use ahash::AHashMap;
+
+/// key的过期时间戳(毫秒)，按照(过期时间, key)排序的索引用于直接找到已经过期的key
+#[derive(Default)]
+pub struct KeyExpires {
+    deadlines: AHashMap<String, u128>,
+    order: BTreeSet<(u128, String)>,
+}
+
+impl KeyExpires {
+    pub fn len(&self) -> usize {
+        self.deadlines.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.deadlines.is_empty()
+    }
+
+    pub fn get(&self, key: &str) -> Option<u128> {
+        self.deadlines.get(key).copied()
+    }
+
+    /// 设置过期时间，返回原来的过期时间
+    pub fn insert(&mut self, key: String, deadline: u128) -> Option<u128> {
+        let old = self.deadlines.insert(key.clone(), deadline);
+        if let Some(old) = old {
+            self.order.remove(&(old, key.clone()));
+        }
+        self.order.insert((deadline, key));
+        old
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<u128> {
+        let old = self.deadlines.remove(key)?;
+        self.order.remove(&(old, String::from(key)));
+        Some(old)
+    }
+
+    /// 在给定时间点是否存在已经过期的key
+    pub fn has_expired(&self, now: u128) -> bool {
+        matches!(self.order.first(), Some((deadline, _)) if *deadline <= now)
+    }
+
+    /// 已经过期的key，按照过期时间和key排序，各副本的顺序一致
+    pub fn expired(&self, now: u128) -> impl Iterator<Item = &str> {
+        self.order
+            .iter()
+            .take_while(move |(deadline, _)| *deadline <= now)
+            .map(|(_, key)| key.as_str())
+    }
+}
+
+/// hash成员的过期时间戳(毫秒)，key -> member -> deadline，
+/// 按照(过期时间, key, 成员)排序的索引用于直接找到存在过期成员的hash
+#[derive(Default)]
+pub struct FieldExpires {
+    deadlines: AHashMap<String, AHashMap<String, u128>>,
+    order: BTreeSet<(u128, String, String)>,
+}
+
+impl FieldExpires {
+    pub fn get(&self, key: &str, member: &str) -> Option<u128> {
+        self.deadlines.get(key)?.get(member).copied()
+    }
+
+    pub fn insert(&mut self, key: &str, member: &str, deadline: u128) {
+        let fields = self.deadlines.entry(String::from(key)).or_default();
+        if let Some(old) = fields.insert(String::from(member), deadline) {
+            self.order
+                .remove(&(old, String::from(key), String::from(member)));
+        }
+        self.order
+            .insert((deadline, String::from(key), String::from(member)));
+    }
+
+    pub fn remove(&mut self, key: &str, member: &str) -> Option<u128> {
+        let fields = self.deadlines.get_mut(key)?;
+        let old = fields.remove(member)?;
+        if fields.is_empty() {
+            self.deadlines.remove(key);
+        }
+        self.order
+            .remove(&(old, String::from(key), String::from(member)));
+        Some(old)
+    }
+
+    /// 删除hash所有成员的过期时间
+    pub fn remove_key(&mut self, key: &str) {
+        if let Some(fields) = self.deadlines.remove(key) {
+            for (member, deadline) in fields {
+                self.order.remove(&(deadline, String::from(key), member));
+            }
+        }
+    }
+
+    /// hash中已经过期的成员，按成员排序
+    pub fn expired_members(&self, key: &str, now: u128) -> Vec<String> {
+        let Some(fields) = self.deadlines.get(key) else {
+            return Vec::new();
+        };
+        let mut expired: Vec<String> = fields
+            .iter()
+            .filter(|(_, deadline)| **deadline <= now)
+            .map(|(member, _)| member.clone())
+            .collect();
+        expired.sort();
+        expired
+    }
+
+    pub fn has_expired(&self, now: u128) -> bool {
+        matches!(self.order.first(), Some((deadline, _, _)) if *deadline <= now)
+    }
+
+    /// 存在过期成员的hash，按照最早过期的成员排序并去重
+    pub fn expired_keys(&self, now: u128) -> Vec<String> {
+        let mut keys: Vec<String> = Vec::new();
+        for (_, key, _) in self
+            .order
+            .iter()
+            .take_while(|(deadline, _, _)| *deadline <= now)
+        {
+            if !keys.contains(key) {
+                keys.push(key.clone());
+            }
+        }
+        keys
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{FieldExpires, KeyExpires};
+
+    #[test]
+    fn key_expires_test() {
+        let mut expires = KeyExpires::default();
+        expires.insert(String::from("b"), 20);
+        expires.insert(String::from("a"), 30);
+        assert!(!expires.has_expired(19));
+        assert_eq!(expires.insert(String::from("a"), 10), Some(30));
+        let expired: Vec<&str> = expires.expired(20).collect();
+        assert_eq!(expired, vec!["a", "b"]);
+        assert_eq!(expires.remove("a"), Some(10));
+        assert_eq!(expires.expired(20).count(), 1);
+        assert_eq!(expires.len(), 1);
+
+        let mut fields = FieldExpires::default();
+        fields.insert("h", "x", 10);
+        fields.insert("h", "y", 15);
+        fields.insert("g", "x", 12);
+        assert_eq!(fields.expired_keys(15), vec!["h", "g"]);
+        assert_eq!(fields.expired_members("h", 12), vec!["x"]);
+        fields.remove_key("h");
+        assert_eq!(fields.expired_keys(15), vec!["g"]);
+        assert_eq!(fields.remove("g", "x"), Some(12));
+        assert!(!fields.has_expired(u128::MAX));
+    }
+}
@@ -0,0 +1,282 @@
+use std::collections::BTreeMap;
+
+use anyhow::anyhow;
+
+use crate::until::murmur64a;
+
+// 寄存器下标的位数，共2^14个寄存器，标准误差约0.81%
+const P: u32 = 14;
+const REGISTERS: usize = 1 << P;
+// 哈希剩余的位数，寄存器的最大值为Q+1
+const Q: u32 = 64 - P;
+const HASH_SEED: u64 = 0xadc83b19;
+// 稀疏表示的寄存器数量超过该值时转换为稠密表示
+const SPARSE_LIMIT: usize = 2048;
+
+#[derive(Clone, Debug, PartialEq)]
+enum Registers {
+    // 只保存非零的寄存器，基数较小时占用内存少
+    Sparse(BTreeMap<u16, u8>),
+    Dense(Vec<u8>),
+}
+
+/// HyperLogLog基数估计
+#[derive(Clone, Debug, PartialEq)]
+pub struct HyperLogLog {
+    registers: Registers,
+}
+
+impl Default for HyperLogLog {
+    fn default() -> Self {
+        HyperLogLog::new()
+    }
+}
+
+impl HyperLogLog {
+    pub fn new() -> Self {
+        HyperLogLog {
+            registers: Registers::Sparse(BTreeMap::new()),
+        }
+    }
+
+    pub fn is_sparse(&self) -> bool {
+        matches!(self.registers, Registers::Sparse(_))
+    }
+
+    /// 添加元素，返回估计值是否可能发生变化
+    pub fn add(&mut self, element: &[u8]) -> bool {
+        let hash = murmur64a(element, HASH_SEED);
+        let index = (hash & (REGISTERS as u64 - 1)) as u16;
+        let rank = ((hash >> P) | (1 << Q)).trailing_zeros() as u8 + 1;
+        self.update(index, rank)
+    }
+
+    fn update(&mut self, index: u16, rank: u8) -> bool {
+        match &mut self.registers {
+            Registers::Sparse(registers) => {
+                let current = registers.entry(index).or_insert(0);
+                if *current >= rank {
+                    return false;
+                }
+                *current = rank;
+                if registers.len() > SPARSE_LIMIT {
+                    self.make_dense();
+                }
+                true
+            }
+            Registers::Dense(registers) => {
+                let current = &mut registers[index as usize];
+                if *current >= rank {
+                    return false;
+                }
+                *current = rank;
+                true
+            }
+        }
+    }
+
+    fn make_dense(&mut self) {
+        if let Registers::Sparse(sparse) = &self.registers {
+            let mut dense = vec![0u8; REGISTERS];
+            for (index, rank) in sparse {
+                dense[*index as usize] = *rank;
+            }
+            self.registers = Registers::Dense(dense);
+        }
+    }
+
+    fn histogram(&self) -> [u32; Q as usize + 2] {
+        let mut histogram = [0u32; Q as usize + 2];
+        match &self.registers {
+            Registers::Sparse(registers) => {
+                histogram[0] = (REGISTERS - registers.len()) as u32;
+                for rank in registers.values() {
+                    histogram[*rank as usize] += 1;
+                }
+            }
+            Registers::Dense(registers) => {
+                for rank in registers {
+                    histogram[*rank as usize] += 1;
+                }
+            }
+        }
+        histogram
+    }
+
+    /// 估计基数，使用Ertl提出的改进估计方法，在小基数和大基数时都不需要修正
+    pub fn count(&self) -> u64 {
+        let histogram = self.histogram();
+        let m = REGISTERS as f64;
+        let mut z = m * tau((m - histogram[Q as usize + 1] as f64) / m);
+        for k in (1..=Q as usize).rev() {
+            z += histogram[k] as f64;
+            z *= 0.5;
+        }
+        z += m * sigma(histogram[0] as f64 / m);
+        let alpha = 0.5 / std::f64::consts::LN_2;
+        (alpha * m * m / z).round() as u64
+    }
+
+    /// 合并另一个HyperLogLog，合并后的估计值为两者并集的基数
+    pub fn merge(&mut self, other: &HyperLogLog) {
+        match &other.registers {
+            Registers::Sparse(registers) => {
+                for (index, rank) in registers {
+                    self.update(*index, *rank);
+                }
+            }
+            Registers::Dense(registers) => {
+                self.make_dense();
+                for (index, rank) in registers.iter().enumerate() {
+                    if *rank > 0 {
+                        self.update(index as u16, *rank);
+                    }
+                }
+            }
+        }
+    }
+
+    /// 估算占用的内存字节数
+    pub fn mem_size(&self) -> usize {
+        match &self.registers {
+            Registers::Sparse(registers) => 32 + registers.len() * 8,
+            Registers::Dense(_) => 32 + REGISTERS,
+        }
+    }
+
+    /// 编码为紧凑的字节数组，返回(稀疏表示, 稠密表示)，其中一个为空。
+    /// 稀疏表示每个寄存器3字节：下标(大端2字节)和值；稠密表示每个寄存器6位
+    pub fn encode(&self) -> (Vec<u8>, Vec<u8>) {
+        match &self.registers {
+            Registers::Sparse(registers) => {
+                let mut sparse = Vec::with_capacity(registers.len() * 3);
+                for (index, rank) in registers {
+                    sparse.extend_from_slice(&index.to_be_bytes());
+                    sparse.push(*rank);
+                }
+                (sparse, Vec::new())
+            }
+            Registers::Dense(registers) => {
+                let mut dense = vec![0u8; REGISTERS * 6 / 8];
+                for (i, rank) in registers.iter().enumerate() {
+                    let bit = i * 6;
+                    let value = (*rank as u16) << (bit % 8);
+                    dense[bit / 8] |= value as u8;
+                    if bit % 8 > 2 {
+                        dense[bit / 8 + 1] |= (value >> 8) as u8;
+                    }
+                }
+                (Vec::new(), dense)
+            }
+        }
+    }
+
+    pub fn decode(sparse: &[u8], dense: &[u8]) -> anyhow::Result<Self> {
+        if !dense.is_empty() {
+            if dense.len() != REGISTERS * 6 / 8 {
+                return Err(anyhow!("Invalid HyperLogLog dense registers"));
+            }
+            let mut registers = vec![0u8; REGISTERS];
+            for (i, rank) in registers.iter_mut().enumerate() {
+                let bit = i * 6;
+                let mut value = (dense[bit / 8] as u16) >> (bit % 8);
+                if bit % 8 > 2 {
+                    value |= (dense[bit / 8 + 1] as u16) << (8 - bit % 8);
+                }
+                *rank = (value & 0x3f) as u8;
+            }
+            return Ok(HyperLogLog {
+                registers: Registers::Dense(registers),
+            });
+        }
+        if !sparse.len().is_multiple_of(3) {
+            return Err(anyhow!("Invalid HyperLogLog sparse registers"));
+        }
+        let mut registers = BTreeMap::new();
+        for chunk in sparse.chunks_exact(3) {
+            let index = u16::from_be_bytes([chunk[0], chunk[1]]);
+            if index as usize >= REGISTERS || chunk[2] as u32 > Q + 1 {
+                return Err(anyhow!("Invalid HyperLogLog sparse registers"));
+            }
+            registers.insert(index, chunk[2]);
+        }
+        Ok(HyperLogLog {
+            registers: Registers::Sparse(registers),
+        })
+    }
+}
+
+fn sigma(mut x: f64) -> f64 {
+    if x == 1.0 {
+        return f64::INFINITY;
+    }
+    let mut y = 1.0;
+    let mut z = x;
+    loop {
+        x *= x;
+        let previous = z;
+        z += x * y;
+        y += y;
+        if previous == z {
+            return z;
+        }
+    }
+}
+
+fn tau(mut x: f64) -> f64 {
+    if x == 0.0 || x == 1.0 {
+        return 0.0;
+    }
+    let mut y = 1.0;
+    let mut z = 1.0 - x;
+    loop {
+        x = x.sqrt();
+        let previous = z;
+        y *= 0.5;
+        z -= (1.0 - x).powi(2) * y;
+        if previous == z {
+            return z / 3.0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::HyperLogLog;
+
+    fn assert_close(estimate: u64, actual: u64) {
+        let error = (estimate as f64 - actual as f64).abs() / actual as f64;
+        assert!(error < 0.02, "estimate {} actual {}", estimate, actual);
+    }
+
+    #[test]
+    fn count_and_merge_test() {
+        let mut hll = HyperLogLog::new();
+        assert_eq!(hll.count(), 0);
+        assert!(hll.add(b"visitor:1"));
+        assert!(!hll.add(b"visitor:1"));
+        assert_eq!(hll.count(), 1);
+        for i in 0..1000 {
+            hll.add(format!("visitor:{}", i).as_bytes());
+        }
+        assert!(hll.is_sparse());
+        assert_close(hll.count(), 1000);
+
+        let mut other = HyperLogLog::new();
+        for i in 500..50000 {
+            other.add(format!("visitor:{}", i).as_bytes());
+        }
+        assert!(!other.is_sparse());
+        assert_close(other.count(), 49500);
+        hll.merge(&other);
+        assert_close(hll.count(), 50000);
+
+        // 编解码后估计值不变
+        for value in [HyperLogLog::new(), other, hll] {
+            let (sparse, dense) = value.encode();
+            let decoded = HyperLogLog::decode(&sparse, &dense).unwrap();
+            assert!(decoded == value);
+        }
+        assert!(HyperLogLog::decode(&[0, 1], &[]).is_err());
+    }
+}
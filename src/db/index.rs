@@ -0,0 +1,318 @@
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet};
+use std::ops::Bound;
+
+use ahash::AHashMap;
+
+use super::dbvalue::DBValue;
+
+/// 索引值，数值排在字符串之前，数值按照大小排序，字符串按照字典序排序。
+/// 整数单独保存，超过2^53的整数转换为浮点数后会相互冲突
+#[derive(Clone, Debug)]
+pub enum IndexValue {
+    Int64(i64),
+    Float64(f64),
+    String(String),
+}
+
+impl IndexValue {
+    /// 只有数值和字符串可以被索引
+    pub fn from_value(value: &DBValue) -> Option<Self> {
+        match value {
+            DBValue::Int64(v) => Some(IndexValue::Int64(*v)),
+            DBValue::Float64(v) if !v.is_nan() => Some(IndexValue::Float64(*v)),
+            DBValue::String(v) => Some(IndexValue::String(v.clone())),
+            _ => None,
+        }
+    }
+}
+
+// 精确比较整数和浮点数，浮点数不为NaN
+fn cmp_int_float(i: i64, f: f64) -> Ordering {
+    // 2^63，超出i64范围的浮点数
+    const LIMIT: f64 = 9_223_372_036_854_775_808.0;
+    if f >= LIMIT {
+        return Ordering::Less;
+    }
+    if f < -LIMIT {
+        return Ordering::Greater;
+    }
+    let trunc = f.trunc();
+    match i.cmp(&(trunc as i64)) {
+        Ordering::Equal => 0.0_f64.partial_cmp(&(f - trunc)).unwrap_or(Ordering::Equal),
+        ordering => ordering,
+    }
+}
+
+impl PartialEq for IndexValue {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for IndexValue {}
+
+impl PartialOrd for IndexValue {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for IndexValue {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (IndexValue::Int64(a), IndexValue::Int64(b)) => a.cmp(b),
+            // -0.0与0.0相等，与整数0的比较结果一致
+            (IndexValue::Float64(a), IndexValue::Float64(b)) => {
+                a.partial_cmp(b).unwrap_or(Ordering::Equal)
+            }
+            (IndexValue::Int64(a), IndexValue::Float64(b)) => cmp_int_float(*a, *b),
+            (IndexValue::Float64(a), IndexValue::Int64(b)) => cmp_int_float(*b, *a).reverse(),
+            (IndexValue::String(a), IndexValue::String(b)) => a.cmp(b),
+            (IndexValue::String(_), _) => Ordering::Greater,
+            (_, IndexValue::String(_)) => Ordering::Less,
+        }
+    }
+}
+
+/// 索引定义：前缀匹配的hash的某个成员
+#[derive(Clone, Debug, PartialEq)]
+pub struct IndexDef {
+    pub prefix: String,
+    pub field: String,
+}
+
+/// 二级索引，索引数据不持久化，总是根据键空间中的数据生成
+pub struct SecondaryIndex {
+    def: IndexDef,
+    // 索引值 -> key
+    entries: BTreeMap<IndexValue, BTreeSet<String>>,
+    // key -> 索引值，用于更新和删除
+    values: AHashMap<String, IndexValue>,
+}
+
+impl SecondaryIndex {
+    pub fn new(def: IndexDef) -> Self {
+        SecondaryIndex {
+            def,
+            entries: BTreeMap::new(),
+            values: AHashMap::new(),
+        }
+    }
+
+    pub fn def(&self) -> &IndexDef {
+        &self.def
+    }
+
+    /// 已索引的key数量
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn covers(&self, key: &str) -> bool {
+        key.starts_with(self.def.prefix.as_str())
+    }
+
+    /// 根据key的最新值更新索引，值不是hash或者成员不可索引时从索引中删除
+    pub fn update(&mut self, key: &str, value: Option<&DBValue>) {
+        let indexed = match value {
+            Some(DBValue::Hash(hash)) => hash.get(&self.def.field).and_then(IndexValue::from_value),
+            _ => None,
+        };
+        if self.values.get(key) == indexed.as_ref() {
+            return;
+        }
+        self.remove(key);
+        if let Some(indexed) = indexed {
+            self.entries
+                .entry(indexed.clone())
+                .or_default()
+                .insert(String::from(key));
+            self.values.insert(String::from(key), indexed);
+        }
+    }
+
+    pub fn remove(&mut self, key: &str) {
+        let Some(old) = self.values.remove(key) else {
+            return;
+        };
+        if let Some(keys) = self.entries.get_mut(&old) {
+            keys.remove(key);
+            if keys.is_empty() {
+                self.entries.remove(&old);
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.values.clear();
+    }
+
+    /// 索引值在区间内的key，按照索引值和key排序
+    pub fn range(
+        &self,
+        min: Bound<IndexValue>,
+        max: Bound<IndexValue>,
+    ) -> impl DoubleEndedIterator<Item = &str> {
+        let empty = match (&min, &max) {
+            (Bound::Included(a), Bound::Included(b)) => a > b,
+            (Bound::Included(a) | Bound::Excluded(a), Bound::Excluded(b))
+            | (Bound::Excluded(a), Bound::Included(b)) => a >= b,
+            _ => false,
+        };
+        let range = (!empty).then(|| self.entries.range((min, max)));
+        range
+            .into_iter()
+            .flatten()
+            .flat_map(|(_, keys)| keys.iter().map(String::as_str))
+    }
+}
+
+/// 命名空间中的二级索引
+#[derive(Default)]
+pub struct IndexTable {
+    indexes: BTreeMap<String, SecondaryIndex>,
+}
+
+impl IndexTable {
+    pub fn is_empty(&self) -> bool {
+        self.indexes.is_empty()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&SecondaryIndex> {
+        self.indexes.get(name)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &SecondaryIndex)> {
+        self.indexes.iter()
+    }
+
+    /// 创建索引，索引已经存在时返回false，创建后需要根据已有数据生成索引
+    pub fn create(&mut self, name: &str, def: IndexDef) -> bool {
+        if self.indexes.contains_key(name) {
+            return false;
+        }
+        self.indexes
+            .insert(String::from(name), SecondaryIndex::new(def));
+        true
+    }
+
+    pub fn drop(&mut self, name: &str) -> bool {
+        self.indexes.remove(name).is_some()
+    }
+
+    /// 更新所有覆盖该key的索引
+    pub fn update(&mut self, key: &str, value: Option<&DBValue>) {
+        for index in self.indexes.values_mut() {
+            if index.covers(key) {
+                index.update(key, value);
+            }
+        }
+    }
+
+    pub fn remove(&mut self, key: &str) {
+        for index in self.indexes.values_mut() {
+            index.remove(key);
+        }
+    }
+
+    /// 清空索引数据，保留索引定义
+    pub fn clear(&mut self) {
+        for index in self.indexes.values_mut() {
+            index.clear();
+        }
+    }
+
+    /// 根据数据重新生成指定索引，keys为有序的key索引
+    pub fn rebuild(&mut self, name: &str, keys: &BTreeSet<String>, db: &AHashMap<String, DBValue>) {
+        let Some(index) = self.indexes.get_mut(name) else {
+            return;
+        };
+        index.clear();
+        let prefix = index.def.prefix.clone();
+        for key in keys
+            .range(prefix.clone()..)
+            .take_while(|key| key.starts_with(prefix.as_str()))
+        {
+            index.update(key, db.get(key));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{IndexDef, IndexValue, SecondaryIndex};
+    use crate::db::dbvalue::DBValue;
+    use ahash::AHashMap;
+    use std::ops::Bound;
+
+    fn user(region: &str, age: i64) -> DBValue {
+        let mut hash = AHashMap::new();
+        hash.insert(
+            String::from("region"),
+            DBValue::String(String::from(region)),
+        );
+        hash.insert(String::from("age"), DBValue::Int64(age));
+        DBValue::Hash(hash)
+    }
+
+    #[test]
+    fn update_and_range_test() {
+        let def = IndexDef {
+            prefix: String::from("user:"),
+            field: String::from("age"),
+        };
+        let mut index = SecondaryIndex::new(def);
+        index.update("user:1", Some(&user("eu", 30)));
+        index.update("user:2", Some(&user("us", 25)));
+        index.update("user:3", Some(&user("eu", 40)));
+        index.update("user:2", Some(&user("us", 35)));
+        let keys: Vec<&str> = index
+            .range(
+                Bound::Included(IndexValue::Int64(30)),
+                Bound::Excluded(IndexValue::Float64(40.0)),
+            )
+            .collect();
+        assert_eq!(keys, vec!["user:1", "user:2"]);
+
+        // 成员不存在或者key被删除时从索引中删除
+        index.update("user:1", Some(&DBValue::Hash(AHashMap::new())));
+        index.remove("user:3");
+        assert_eq!(index.len(), 1);
+        let empty = index.range(
+            Bound::Included(IndexValue::Int64(50)),
+            Bound::Included(IndexValue::Int64(10)),
+        );
+        assert_eq!(empty.count(), 0);
+    }
+
+    #[test]
+    fn large_int_test() {
+        let def = IndexDef {
+            prefix: String::from("user:"),
+            field: String::from("age"),
+        };
+        let mut index = SecondaryIndex::new(def);
+        // 两个整数转换为浮点数后相等
+        let big = 1_i64 << 53;
+        index.update("user:1", Some(&user("eu", big)));
+        index.update("user:2", Some(&user("eu", big + 1)));
+        let exact = |v: i64| {
+            let (min, max) = (IndexValue::Int64(v), IndexValue::Int64(v));
+            index
+                .range(Bound::Included(min), Bound::Included(max))
+                .collect::<Vec<&str>>()
+        };
+        assert_eq!(exact(big), vec!["user:1"]);
+        assert_eq!(exact(big + 1), vec!["user:2"]);
+        assert!(IndexValue::Int64(big + 1) > IndexValue::Float64(big as f64));
+        assert!(IndexValue::Int64(0) == IndexValue::Float64(-0.0));
+        assert!(IndexValue::Int64(i64::MAX) < IndexValue::Float64(i64::MAX as f64));
+        assert!(IndexValue::Int64(-1) < IndexValue::Float64(-0.5));
+    }
+}
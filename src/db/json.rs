@@ -0,0 +1,535 @@
+use ahash::{AHashMap, AHashSet};
+use anyhow::anyhow;
+use prost_types::Timestamp;
+use serde_json::{Map, Number, Value};
+
+use super::dbvalue::DBValue;
+use super::sorted_set::SortedSet;
+
+// JSON中没有对应类型的值使用只有一个标记字段的对象表示，保证与JSON之间的转换无损
+const BYTES_TAG: &str = "$bytes";
+const SET_TAG: &str = "$set";
+const SORTED_SET_TAG: &str = "$zset";
+const TIMESTAMP_TAG: &str = "$timestamp";
+
+// hash中与标记同名或者以$$开头的字段名在前面再加一个$，避免只有一个字段的hash被当作标记对象
+fn escape_field(field: &str) -> String {
+    let tag = matches!(field, BYTES_TAG | SET_TAG | SORTED_SET_TAG | TIMESTAMP_TAG);
+    if tag || field.starts_with("$$") {
+        format!("${}", field)
+    } else {
+        String::from(field)
+    }
+}
+
+fn unescape_field(field: String) -> String {
+    match field.strip_prefix('$') {
+        Some(rest) if rest.starts_with('$') => String::from(rest),
+        _ => field,
+    }
+}
+
+/// 转换为JSON，集合按照成员排序，流不支持转换
+pub fn to_json(value: &DBValue) -> anyhow::Result<Value> {
+    let json = match value {
+        DBValue::None => Value::Null,
+        DBValue::Boolean(v) => Value::Bool(*v),
+        DBValue::String(v) => Value::String(v.clone()),
+        DBValue::Int64(v) => Value::Number(Number::from(*v)),
+        DBValue::Float64(v) => Number::from_f64(*v)
+            .map(Value::Number)
+            .ok_or_else(|| anyhow!("Float {} can not be converted to JSON", v))?,
+        DBValue::List(list) => Value::Array(list.iter().map(to_json).collect::<Result<_, _>>()?),
+        DBValue::Hash(hash) => {
+            let mut object = Map::new();
+            for (field, value) in hash {
+                object.insert(escape_field(field), to_json(value)?);
+            }
+            Value::Object(object)
+        }
+        DBValue::Bytes(bytes) => {
+            let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+            tagged(BYTES_TAG, Value::String(hex))
+        }
+        DBValue::Set(set) => {
+            let mut members: Vec<&String> = set.iter().collect();
+            members.sort();
+            let members = members.into_iter().map(|m| Value::String(m.clone()));
+            tagged(SET_TAG, Value::Array(members.collect()))
+        }
+        DBValue::SortedSet(zset) => {
+            let mut entries = Vec::with_capacity(zset.len());
+            for (member, score) in zset.iter() {
+                let score = to_json(&DBValue::Float64(score))?;
+                entries.push(Value::Array(vec![
+                    Value::String(String::from(member)),
+                    score,
+                ]));
+            }
+            tagged(SORTED_SET_TAG, Value::Array(entries))
+        }
+        DBValue::Timestamp(ts) => {
+            let parts = vec![Value::from(ts.seconds), Value::from(ts.nanos)];
+            tagged(TIMESTAMP_TAG, Value::Array(parts))
+        }
+        DBValue::Stream(_) | DBValue::HyperLogLog(_) | DBValue::BloomFilter(_) => {
+            return Err(anyhow!("{} can not be converted to JSON", value.type_name()));
+        }
+    };
+    Ok(json)
+}
+
+fn tagged(tag: &str, value: Value) -> Value {
+    let mut object = Map::new();
+    object.insert(String::from(tag), value);
+    Value::Object(object)
+}
+
+/// 从JSON转换，整数使用Int64，超出范围的整数和小数使用Float64
+pub fn from_json(value: Value) -> DBValue {
+    match value {
+        Value::Null => DBValue::None,
+        Value::Bool(v) => DBValue::Boolean(v),
+        Value::String(v) => DBValue::String(v),
+        Value::Number(n) => match n.as_i64() {
+            Some(v) => DBValue::Int64(v),
+            None => DBValue::Float64(n.as_f64().unwrap_or(f64::NAN)),
+        },
+        Value::Array(array) => DBValue::List(array.into_iter().map(from_json).collect()),
+        Value::Object(object) => {
+            if object.len() == 1 {
+                if let Some(value) = from_tagged(&object) {
+                    return value;
+                }
+            }
+            let hash: AHashMap<String, DBValue> = object
+                .into_iter()
+                .map(|(field, value)| (unescape_field(field), from_json(value)))
+                .collect();
+            DBValue::Hash(hash)
+        }
+    }
+}
+
+// 标记对象的内容不合法时按照普通对象处理
+fn from_tagged(object: &Map<String, Value>) -> Option<DBValue> {
+    let (tag, value) = object.iter().next()?;
+    match (tag.as_str(), value) {
+        (BYTES_TAG, Value::String(hex)) => {
+            if hex.len() % 2 != 0 || !hex.is_ascii() {
+                return None;
+            }
+            let bytes = (0..hex.len())
+                .step_by(2)
+                .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+                .collect::<Option<Vec<u8>>>()?;
+            Some(DBValue::Bytes(bytes))
+        }
+        (SET_TAG, Value::Array(members)) => {
+            let set = members
+                .iter()
+                .map(|m| m.as_str().map(String::from))
+                .collect::<Option<AHashSet<String>>>()?;
+            Some(DBValue::Set(set))
+        }
+        (SORTED_SET_TAG, Value::Array(entries)) => {
+            let mut zset = SortedSet::new();
+            for entry in entries {
+                match entry.as_array()?.as_slice() {
+                    [Value::String(member), score] => {
+                        zset.insert(member.clone(), score.as_f64()?);
+                    }
+                    _ => return None,
+                }
+            }
+            Some(DBValue::SortedSet(zset))
+        }
+        (TIMESTAMP_TAG, Value::Array(parts)) => match parts.as_slice() {
+            [seconds, nanos] => Some(DBValue::Timestamp(Timestamp {
+                seconds: seconds.as_i64()?,
+                nanos: i32::try_from(nanos.as_i64()?).ok()?,
+            })),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// JSONPath的一段
+#[derive(Clone, Debug, PartialEq)]
+pub enum Segment {
+    // .name 或者 ['name']
+    Field(String),
+    // [n]，负数表示从列表末尾开始
+    Index(i64),
+    // .* 或者 [*]
+    Wildcard,
+}
+
+/// 解析JSONPath子集：$、.name、.*、[n]、[*]、['name']
+pub fn parse_path(path: &str) -> anyhow::Result<Vec<Segment>> {
+    let invalid = || anyhow!("Invalid JSON path {}", path);
+    let rest = match path {
+        "" => return Ok(Vec::new()),
+        _ => path.strip_prefix('$').ok_or_else(invalid)?,
+    };
+    let chars: Vec<char> = rest.chars().collect();
+    let mut segments = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '.' => {
+                let start = i + 1;
+                i = start;
+                while i < chars.len() && chars[i] != '.' && chars[i] != '[' {
+                    i += 1;
+                }
+                let name: String = chars[start..i].iter().collect();
+                match name.as_str() {
+                    "" => return Err(invalid()),
+                    "*" => segments.push(Segment::Wildcard),
+                    _ => segments.push(Segment::Field(name)),
+                }
+            }
+            '[' => {
+                let start = i + 1;
+                let end = start
+                    + chars[start..]
+                        .iter()
+                        .position(|c| *c == ']')
+                        .ok_or_else(invalid)?;
+                let inner: String = chars[start..end].iter().collect();
+                let quoted = inner.len() >= 2
+                    && ((inner.starts_with('\'') && inner.ends_with('\''))
+                        || (inner.starts_with('"') && inner.ends_with('"')));
+                if quoted {
+                    segments.push(Segment::Field(String::from(&inner[1..inner.len() - 1])));
+                } else if inner == "*" {
+                    segments.push(Segment::Wildcard);
+                } else {
+                    segments.push(Segment::Index(inner.trim().parse().map_err(|_| invalid())?));
+                }
+                i = end + 1;
+            }
+            _ => return Err(invalid()),
+        }
+    }
+    Ok(segments)
+}
+
+fn list_index(index: i64, len: usize) -> Option<usize> {
+    let index = if index < 0 { len as i64 + index } else { index };
+    (index >= 0 && index < len as i64).then_some(index as usize)
+}
+
+// 对象的字段按名称排序，保证通配符匹配的顺序在各副本上一致
+fn sorted_fields(hash: &AHashMap<String, DBValue>) -> Vec<String> {
+    let mut fields: Vec<String> = hash.keys().cloned().collect();
+    fields.sort();
+    fields
+}
+
+/// 路径匹配的所有值
+pub fn select<'a>(value: &'a DBValue, path: &[Segment]) -> Vec<&'a DBValue> {
+    let Some((segment, rest)) = path.split_first() else {
+        return vec![value];
+    };
+    let children: Vec<&DBValue> = match (segment, value) {
+        (Segment::Field(field), DBValue::Hash(hash)) => hash.get(field).into_iter().collect(),
+        (Segment::Index(index), DBValue::List(list)) => list_index(*index, list.len())
+            .map(|i| &list[i])
+            .into_iter()
+            .collect(),
+        (Segment::Wildcard, DBValue::List(list)) => list.iter().collect(),
+        (Segment::Wildcard, DBValue::Hash(hash)) => sorted_fields(hash)
+            .iter()
+            .filter_map(|f| hash.get(f))
+            .collect(),
+        _ => Vec::new(),
+    };
+    children
+        .into_iter()
+        .flat_map(|child| select(child, rest))
+        .collect()
+}
+
+// 依次访问路径匹配的所有值
+fn visit_mut(
+    value: &mut DBValue,
+    path: &[Segment],
+    f: &mut dyn FnMut(&mut DBValue) -> anyhow::Result<()>,
+) -> anyhow::Result<()> {
+    let Some((segment, rest)) = path.split_first() else {
+        return f(value);
+    };
+    match (segment, value) {
+        (Segment::Field(field), DBValue::Hash(hash)) => {
+            if let Some(child) = hash.get_mut(field) {
+                visit_mut(child, rest, f)?;
+            }
+        }
+        (Segment::Index(index), DBValue::List(list)) => {
+            if let Some(i) = list_index(*index, list.len()) {
+                visit_mut(&mut list[i], rest, f)?;
+            }
+        }
+        (Segment::Wildcard, DBValue::List(list)) => {
+            for child in list.iter_mut() {
+                visit_mut(child, rest, f)?;
+            }
+        }
+        (Segment::Wildcard, DBValue::Hash(hash)) => {
+            for field in sorted_fields(hash) {
+                if let Some(child) = hash.get_mut(&field) {
+                    visit_mut(child, rest, f)?;
+                }
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// 设置路径匹配的值，路径最后一段是对象中不存在的字段时新增字段。
+/// nx为true时只新增，xx为true时只修改，返回设置的数量
+pub fn set(
+    root: &mut DBValue,
+    path: &[Segment],
+    new: &DBValue,
+    nx: bool,
+    xx: bool,
+) -> anyhow::Result<usize> {
+    let Some((last, parent)) = path.split_last() else {
+        return Err(anyhow!("Root path can not be set in place"));
+    };
+    let mut count = 0;
+    visit_mut(root, parent, &mut |value| {
+        match (last, value) {
+            (Segment::Field(field), DBValue::Hash(hash)) => {
+                let exists = hash.contains_key(field);
+                if (exists && !nx) || (!exists && !xx) {
+                    hash.insert(field.clone(), new.clone());
+                    count += 1;
+                }
+            }
+            (Segment::Index(index), DBValue::List(list)) if !nx => {
+                if let Some(i) = list_index(*index, list.len()) {
+                    list[i] = new.clone();
+                    count += 1;
+                }
+            }
+            (Segment::Wildcard, DBValue::List(list)) if !nx => {
+                for child in list.iter_mut() {
+                    *child = new.clone();
+                    count += 1;
+                }
+            }
+            (Segment::Wildcard, DBValue::Hash(hash)) if !nx => {
+                for child in hash.values_mut() {
+                    *child = new.clone();
+                    count += 1;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    })?;
+    Ok(count)
+}
+
+/// 删除路径匹配的值，返回删除的数量
+pub fn delete(root: &mut DBValue, path: &[Segment]) -> anyhow::Result<usize> {
+    let Some((last, parent)) = path.split_last() else {
+        return Err(anyhow!("Root path can not be deleted in place"));
+    };
+    let mut count = 0;
+    visit_mut(root, parent, &mut |value| {
+        match (last, value) {
+            (Segment::Field(field), DBValue::Hash(hash)) => {
+                count += hash.remove(field).map_or(0, |_| 1);
+            }
+            (Segment::Index(index), DBValue::List(list)) => {
+                if let Some(i) = list_index(*index, list.len()) {
+                    list.remove(i);
+                    count += 1;
+                }
+            }
+            (Segment::Wildcard, DBValue::List(list)) => {
+                count += list.len();
+                list.clear();
+            }
+            (Segment::Wildcard, DBValue::Hash(hash)) => {
+                count += hash.len();
+                hash.clear();
+            }
+            _ => {}
+        }
+        Ok(())
+    })?;
+    Ok(count)
+}
+
+/// 向路径匹配的列表末尾追加值，返回每个匹配值追加后的长度，匹配值不是列表时为None
+pub fn arr_append(
+    root: &mut DBValue,
+    path: &[Segment],
+    values: &[DBValue],
+) -> anyhow::Result<Vec<Option<usize>>> {
+    let mut lens = Vec::new();
+    visit_mut(root, path, &mut |value| {
+        match value {
+            DBValue::List(list) => {
+                list.extend(values.iter().cloned());
+                lens.push(Some(list.len()));
+            }
+            _ => lens.push(None),
+        }
+        Ok(())
+    })?;
+    Ok(lens)
+}
+
+/// 路径匹配的数值增加delta，返回每个匹配值增加后的值，匹配值不是数值时为None
+pub fn num_incr_by(
+    root: &mut DBValue,
+    path: &[Segment],
+    delta: &DBValue,
+) -> anyhow::Result<Vec<Option<DBValue>>> {
+    let mut results = Vec::new();
+    visit_mut(root, path, &mut |value| {
+        let result = match (&*value, delta) {
+            (DBValue::Int64(v), DBValue::Int64(d)) => DBValue::Int64(
+                v.checked_add(*d)
+                    .ok_or_else(|| anyhow!("Increment or decrement would overflow"))?,
+            ),
+            (DBValue::Int64(v), DBValue::Float64(d)) => DBValue::Float64(*v as f64 + d),
+            (DBValue::Float64(v), DBValue::Int64(d)) => DBValue::Float64(v + *d as f64),
+            (DBValue::Float64(v), DBValue::Float64(d)) => DBValue::Float64(v + d),
+            _ => {
+                results.push(None);
+                return Ok(());
+            }
+        };
+        if let DBValue::Float64(v) = result {
+            if !v.is_finite() {
+                return Err(anyhow!("Increment would produce NaN or Infinity"));
+            }
+        }
+        *value = result.clone();
+        results.push(Some(result));
+        Ok(())
+    })?;
+    Ok(results)
+}
+
+/// 将列表转换为JSON数组
+pub fn to_json_array<'a>(values: impl Iterator<Item = &'a DBValue>) -> anyhow::Result<Value> {
+    Ok(Value::Array(values.map(to_json).collect::<Result<_, _>>()?))
+}
+
+/// 解析JSON文本
+pub fn parse_json(text: &str) -> anyhow::Result<DBValue> {
+    let value: Value =
+        serde_json::from_str(text).map_err(|err| anyhow!("Invalid JSON value: {}", err))?;
+    Ok(from_json(value))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{arr_append, delete, from_json, parse_json, parse_path, select, set, to_json};
+    use super::{num_incr_by, Segment};
+    use crate::db::dbvalue::DBValue;
+    use crate::db::sorted_set::SortedSet;
+    use ahash::{AHashMap, AHashSet};
+
+    #[test]
+    fn lossless_conversion_test() {
+        let text = r#"{"name":"alice","age":30,"score":1.0,"tags":["a",null,true]}"#;
+        let value = parse_json(text).unwrap();
+        let json = to_json(&value).unwrap();
+        assert_eq!(
+            json,
+            serde_json::from_str::<serde_json::Value>(text).unwrap()
+        );
+
+        let mut zset = SortedSet::new();
+        zset.insert(String::from("m"), 1.5);
+        let values = vec![
+            DBValue::Bytes(vec![0, 255, 16]),
+            DBValue::Set(AHashSet::from_iter([String::from("x"), String::from("y")])),
+            DBValue::SortedSet(zset),
+            DBValue::Float64(2.0),
+        ];
+        for value in values {
+            assert!(from_json(to_json(&value).unwrap()) == value);
+        }
+
+        // 与标记同名的hash字段转义后仍然是hash
+        let hash = |field: &str, value: DBValue| {
+            DBValue::Hash(AHashMap::from_iter([(String::from(field), value)]))
+        };
+        let values = vec![
+            hash("$bytes", DBValue::String(String::from("00ff"))),
+            hash(
+                "$set",
+                DBValue::List(vec![DBValue::String(String::from("x"))].into()),
+            ),
+            hash(
+                "$timestamp",
+                DBValue::List(vec![DBValue::Int64(1), DBValue::Int64(0)].into()),
+            ),
+            hash("$$zset", DBValue::List(Default::default())),
+            hash("$ref", DBValue::Int64(1)),
+        ];
+        for value in values {
+            assert!(from_json(to_json(&value).unwrap()) == value);
+        }
+        let json = to_json(&hash("$bytes", DBValue::Int64(1))).unwrap();
+        assert_eq!(json.to_string(), r#"{"$$bytes":1}"#);
+        let json = to_json(&hash("$ref", DBValue::Int64(1))).unwrap();
+        assert_eq!(json.to_string(), r#"{"$ref":1}"#);
+    }
+
+    #[test]
+    fn parse_path_test() {
+        assert!(parse_path("$").unwrap().is_empty());
+        assert_eq!(
+            parse_path("$.user['first name'][-1].*").unwrap(),
+            vec![
+                Segment::Field(String::from("user")),
+                Segment::Field(String::from("first name")),
+                Segment::Index(-1),
+                Segment::Wildcard,
+            ]
+        );
+        assert!(parse_path("user").is_err());
+        assert!(parse_path("$.a[x]").is_err());
+    }
+
+    #[test]
+    fn modify_in_place_test() {
+        let mut doc = parse_json(r#"{"items":[{"n":1},{"n":2.5}],"tags":[]}"#).unwrap();
+        let path = parse_path("$.items[*].n").unwrap();
+        assert_eq!(select(&doc, &path).len(), 2);
+
+        let results = num_incr_by(&mut doc, &path, &DBValue::Int64(2)).unwrap();
+        assert!(results[0] == Some(DBValue::Int64(3)));
+        assert!(results[1] == Some(DBValue::Float64(4.5)));
+
+        let tags = parse_path("$.tags").unwrap();
+        let lens = arr_append(&mut doc, &tags, &[DBValue::String(String::from("a"))]).unwrap();
+        assert_eq!(lens, vec![Some(1)]);
+
+        // nx只新增不存在的字段
+        let owner = parse_path("$.owner").unwrap();
+        let bob = DBValue::String(String::from("bob"));
+        assert_eq!(set(&mut doc, &owner, &bob, true, false).unwrap(), 1);
+        assert_eq!(set(&mut doc, &owner, &bob, true, false).unwrap(), 0);
+        assert_eq!(
+            delete(&mut doc, &parse_path("$.items[0]").unwrap()).unwrap(),
+            1
+        );
+        let items = select(&doc, &parse_path("$.items").unwrap());
+        assert!(matches!(items[0], DBValue::List(list) if list.len() == 1));
+    }
+}
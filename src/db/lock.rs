@@ -0,0 +1,161 @@
+use ahash::AHashMap;
+use anyhow::anyhow;
+
+/// 被持有的锁
+#[derive(Clone, Debug, PartialEq)]
+pub struct LockState {
+    // 持有锁的客户端会话
+    pub owner: String,
+    // 重入次数
+    pub count: u32,
+    // 获取锁时分配的令牌，重入不会改变令牌
+    pub token: u64,
+    // 租约到期时间戳(毫秒)
+    pub deadline: u128,
+}
+
+/// 命名空间中的锁，租约到期的锁视为已释放
+#[derive(Clone, Default)]
+pub struct LockTable {
+    locks: AHashMap<String, LockState>,
+}
+
+impl LockTable {
+    pub fn len(&self) -> usize {
+        self.locks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.locks.is_empty()
+    }
+
+    /// 锁的持有者，锁未被持有或者租约已经到期返回None
+    pub fn holder(&self, name: &str, now: u128) -> Option<&LockState> {
+        self.locks.get(name).filter(|lock| lock.deadline > now)
+    }
+
+    /// 获取锁，同一会话重入时增加重入次数并延长租约，被其它会话持有时返回None，
+    /// 新获取锁时使用next_token作为令牌
+    pub fn acquire(
+        &mut self,
+        name: &str,
+        session: &str,
+        lease_ms: u64,
+        now: u128,
+        next_token: u64,
+    ) -> Option<u64> {
+        let deadline = now + lease_ms as u128;
+        match self.holder(name, now) {
+            Some(lock) if lock.owner != session => None,
+            Some(_) => {
+                let lock = self.locks.get_mut(name)?;
+                lock.count += 1;
+                lock.deadline = deadline;
+                Some(lock.token)
+            }
+            None => {
+                let lock = LockState {
+                    owner: String::from(session),
+                    count: 1,
+                    token: next_token,
+                    deadline,
+                };
+                self.locks.insert(String::from(name), lock);
+                Some(next_token)
+            }
+        }
+    }
+
+    /// 释放一次锁，返回剩余的重入次数，锁不属于该会话时返回错误
+    pub fn release(&mut self, name: &str, session: &str, now: u128) -> anyhow::Result<u32> {
+        match self.holder(name, now) {
+            Some(lock) if lock.owner == session => {}
+            _ => return Err(anyhow!("Lock {} is not held by current session", name)),
+        }
+        let Some(lock) = self.locks.get_mut(name) else {
+            return Ok(0);
+        };
+        lock.count -= 1;
+        let remaining = lock.count;
+        if remaining == 0 {
+            self.locks.remove(name);
+        }
+        Ok(remaining)
+    }
+
+    /// 续约锁的租约，锁不属于该会话时返回false
+    pub fn renew(&mut self, name: &str, session: &str, lease_ms: u64, now: u128) -> bool {
+        match self.locks.get_mut(name) {
+            Some(lock) if lock.owner == session && lock.deadline > now => {
+                lock.deadline = now + lease_ms as u128;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// 释放会话持有的全部锁，返回按名称排序的锁名称
+    pub fn release_owner(&mut self, session: &str) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .locks
+            .iter()
+            .filter(|(_, lock)| lock.owner == session)
+            .map(|(name, _)| name.clone())
+            .collect();
+        names.sort();
+        for name in names.iter() {
+            self.locks.remove(name);
+        }
+        names
+    }
+
+    pub fn has_expired(&self, now: u128) -> bool {
+        self.locks.values().any(|lock| lock.deadline <= now)
+    }
+
+    /// 删除租约到期的锁，返回按名称排序的锁名称
+    pub fn expire(&mut self, now: u128) -> Vec<String> {
+        let mut expired: Vec<String> = self
+            .locks
+            .iter()
+            .filter(|(_, lock)| lock.deadline <= now)
+            .map(|(name, _)| name.clone())
+            .collect();
+        expired.sort();
+        for name in expired.iter() {
+            self.locks.remove(name);
+        }
+        expired
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::LockTable;
+
+    #[test]
+    fn reentrant_lock_test() {
+        let mut locks = LockTable::default();
+        assert_eq!(locks.acquire("job", "s1", 1_000, 0, 7), Some(7));
+        // 同一会话重入返回相同的令牌
+        assert_eq!(locks.acquire("job", "s1", 1_000, 10, 8), Some(7));
+        assert_eq!(locks.acquire("job", "s2", 1_000, 20, 9), None);
+        assert!(locks.release("job", "s2", 30).is_err());
+        assert_eq!(locks.release("job", "s1", 30).unwrap(), 1);
+        assert_eq!(locks.release("job", "s1", 40).unwrap(), 0);
+        assert!(locks.is_empty());
+    }
+
+    #[test]
+    fn lease_expire_test() {
+        let mut locks = LockTable::default();
+        locks.acquire("job", "s1", 100, 0, 1);
+        assert!(locks.renew("job", "s1", 100, 50));
+        assert!(!locks.has_expired(149));
+        // 租约到期后其它会话可以获取
+        assert_eq!(locks.acquire("job", "s2", 100, 150, 2), Some(2));
+        assert!(!locks.renew("job", "s1", 100, 160));
+        assert_eq!(locks.expire(250), vec![String::from("job")]);
+        assert!(locks.holder("job", 250).is_none());
+    }
+}
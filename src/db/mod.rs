@@ -1,2 +1,20 @@
+pub mod bitmap;
+pub mod blocking;
+pub mod bloom;
 pub mod database;
 pub mod dbvalue;
+pub mod eviction;
+pub mod expires;
+pub mod hyperloglog;
+pub mod index;
+pub mod json;
+pub mod lock;
+pub mod namespace;
+pub mod primitive;
+pub mod processor;
+pub mod query;
+pub mod scan;
+pub mod script;
+pub mod session;
+pub mod sorted_set;
+pub mod stream;
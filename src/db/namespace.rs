@@ -0,0 +1,119 @@
+use std::collections::BTreeSet;
+
+use ahash::{AHashMap, AHashSet};
+
+use super::dbvalue::DBValue;
+use super::eviction::{KeyMeta, KeySampler};
+use super::expires::{FieldExpires, KeyExpires};
+use super::index::IndexTable;
+use super::lock::LockTable;
+use super::primitive::Primitives;
+
+// 命令未指定命名空间时使用的默认命名空间
+pub const DEFAULT_NAMESPACE: &str = "default";
+
+pub fn normalize_namespace(namespace: &str) -> &str {
+    if namespace.is_empty() {
+        DEFAULT_NAMESPACE
+    } else {
+        namespace
+    }
+}
+
+/// 命名空间级别的配置
+#[derive(Clone, Default)]
+pub struct NamespaceConfig {
+    // 新写入key的默认过期时间(毫秒)，0表示不过期
+    pub default_ttl_ms: u64,
+    // 命名空间内存配额(字节)，0表示不限制
+    pub max_memory: usize,
+}
+
+/// 命名空间级别的统计
+#[derive(Clone, Default)]
+pub struct NamespaceStats {
+    pub reads: u64,
+    pub writes: u64,
+    pub expired_keys: u64,
+    pub evicted_keys: u64,
+}
+
+/// 一个命名空间独立的键空间
+#[derive(Default)]
+pub struct Keyspace {
+    pub db: AHashMap<String, DBValue>,
+    // 有序的key索引，用于游标遍历
+    pub keys: BTreeSet<String>,
+    // key的过期时间戳(毫秒)
+    pub expires: KeyExpires,
+    // hash成员的过期时间戳(毫秒)
+    pub field_expires: FieldExpires,
+    // key的内存占用和访问统计
+    pub meta: AHashMap<String, KeyMeta>,
+    // 可随机访问的key集合，用于淘汰时采样
+    pub sampler: KeySampler,
+    // 通过get_mut修改过、需要重新计算内存占用的key
+    pub dirty: AHashSet<String>,
+    // 估算的内存占用(字节)
+    pub used_memory: usize,
+    pub config: NamespaceConfig,
+    pub stats: NamespaceStats,
+    // 临时key所属的会话
+    pub ephemeral: AHashMap<String, String>,
+    // 临时hash成员所属的会话，key -> member -> session
+    pub ephemeral_fields: AHashMap<String, AHashMap<String, String>>,
+    // 命名空间中的锁，锁名称与key相互独立
+    pub locks: LockTable,
+    // 命名空间中的原子变量、倒计数器和信号量
+    pub primitives: Primitives,
+    // hash成员的二级索引
+    pub indexes: IndexTable,
+}
+
+impl Keyspace {
+    /// 在给定时间点是否存在已经过期但尚未删除的key、hash成员或者租约到期的锁
+    pub fn has_expired_keys(&self, now: u128) -> bool {
+        self.locks.has_expired(now)
+            || self.expires.has_expired(now)
+            || self.field_expires.has_expired(now)
+    }
+
+    /// 清空数据，保留配置、统计、锁、并发原语和索引定义
+    pub fn flush(&mut self) {
+        let config = std::mem::take(&mut self.config);
+        let stats = std::mem::take(&mut self.stats);
+        let locks = std::mem::take(&mut self.locks);
+        let primitives = std::mem::take(&mut self.primitives);
+        let mut indexes = std::mem::take(&mut self.indexes);
+        indexes.clear();
+        *self = Keyspace {
+            config,
+            stats,
+            locks,
+            primitives,
+            indexes,
+            ..Default::default()
+        };
+    }
+
+    /// 交换两个命名空间的数据，配置、统计、锁、并发原语和索引定义保留在原命名空间，
+    /// 索引根据交换后的数据重新生成
+    pub fn swap_data(&mut self, other: &mut Keyspace) {
+        std::mem::swap(self, other);
+        std::mem::swap(&mut self.config, &mut other.config);
+        std::mem::swap(&mut self.stats, &mut other.stats);
+        std::mem::swap(&mut self.locks, &mut other.locks);
+        std::mem::swap(&mut self.primitives, &mut other.primitives);
+        std::mem::swap(&mut self.indexes, &mut other.indexes);
+        self.rebuild_indexes();
+        other.rebuild_indexes();
+    }
+
+    /// 根据数据重新生成所有索引
+    pub fn rebuild_indexes(&mut self) {
+        let names: Vec<String> = self.indexes.iter().map(|(name, _)| name.clone()).collect();
+        for name in names {
+            self.indexes.rebuild(&name, &self.keys, &self.db);
+        }
+    }
+}
@@ -0,0 +1,173 @@
+use ahash::AHashMap;
+use anyhow::anyhow;
+
+use super::dbvalue::DBValue;
+
+/// 命名空间中的并发原语，与key相互独立，不同类型的原语名称也相互独立
+#[derive(Clone, Default)]
+pub struct Primitives {
+    atomics: AHashMap<String, i64>,
+    references: AHashMap<String, DBValue>,
+    // 倒计数器的剩余计数，计数为0时删除
+    latches: AHashMap<String, u64>,
+    // 信号量的可用许可数量
+    semaphores: AHashMap<String, u64>,
+}
+
+impl Primitives {
+    pub fn atomic_get(&self, name: &str) -> i64 {
+        self.atomics.get(name).copied().unwrap_or(0)
+    }
+
+    /// 设置新值，返回旧值
+    pub fn atomic_get_and_set(&mut self, name: &str, value: i64) -> i64 {
+        self.atomics.insert(String::from(name), value).unwrap_or(0)
+    }
+
+    /// 增加delta，返回增加后的值，溢出时返回错误
+    pub fn atomic_add_and_get(&mut self, name: &str, delta: i64) -> anyhow::Result<i64> {
+        let value = self
+            .atomic_get(name)
+            .checked_add(delta)
+            .ok_or_else(|| anyhow!("Increment or decrement would overflow"))?;
+        self.atomics.insert(String::from(name), value);
+        Ok(value)
+    }
+
+    /// 当前值等于expect时设置为update，返回是否设置成功
+    pub fn atomic_compare_and_set(&mut self, name: &str, expect: i64, update: i64) -> bool {
+        if self.atomic_get(name) != expect {
+            return false;
+        }
+        self.atomics.insert(String::from(name), update);
+        true
+    }
+
+    pub fn reference_get(&self, name: &str) -> Option<&DBValue> {
+        self.references.get(name)
+    }
+
+    /// 设置新值，返回旧值，值为None时清除引用
+    pub fn reference_get_and_set(&mut self, name: &str, value: Option<DBValue>) -> Option<DBValue> {
+        match value {
+            Some(value) => self.references.insert(String::from(name), value),
+            None => self.references.remove(name),
+        }
+    }
+
+    /// 当前值等于expect时设置为update，expect为None表示期望引用未设置
+    pub fn reference_compare_and_set(
+        &mut self,
+        name: &str,
+        expect: Option<&DBValue>,
+        update: Option<DBValue>,
+    ) -> bool {
+        if self.references.get(name) != expect {
+            return false;
+        }
+        self.reference_get_and_set(name, update);
+        true
+    }
+
+    pub fn latch_count(&self, name: &str) -> u64 {
+        self.latches.get(name).copied().unwrap_or(0)
+    }
+
+    /// 计数为0时设置计数，返回是否设置成功
+    pub fn latch_try_set_count(&mut self, name: &str, count: u64) -> bool {
+        if count == 0 || self.latch_count(name) > 0 {
+            return false;
+        }
+        self.latches.insert(String::from(name), count);
+        true
+    }
+
+    /// 计数减1，返回剩余计数
+    pub fn latch_count_down(&mut self, name: &str) -> u64 {
+        let Some(count) = self.latches.get_mut(name) else {
+            return 0;
+        };
+        *count -= 1;
+        let remaining = *count;
+        if remaining == 0 {
+            self.latches.remove(name);
+        }
+        remaining
+    }
+
+    /// 信号量的可用许可，信号量未初始化时返回None
+    pub fn semaphore_available(&self, name: &str) -> Option<u64> {
+        self.semaphores.get(name).copied()
+    }
+
+    /// 初始化信号量，已经初始化时返回false
+    pub fn semaphore_init(&mut self, name: &str, permits: u64) -> bool {
+        if self.semaphores.contains_key(name) {
+            return false;
+        }
+        self.semaphores.insert(String::from(name), permits);
+        true
+    }
+
+    /// 获取许可，可用许可不足时返回false
+    pub fn semaphore_try_acquire(&mut self, name: &str, permits: u64) -> anyhow::Result<bool> {
+        let available = self
+            .semaphores
+            .get_mut(name)
+            .ok_or_else(|| anyhow!("Semaphore {} is not initialized", name))?;
+        if *available < permits {
+            return Ok(false);
+        }
+        *available -= permits;
+        Ok(true)
+    }
+
+    /// 释放许可，返回释放后的可用许可
+    pub fn semaphore_release(&mut self, name: &str, permits: u64) -> anyhow::Result<u64> {
+        let available = self
+            .semaphores
+            .get_mut(name)
+            .ok_or_else(|| anyhow!("Semaphore {} is not initialized", name))?;
+        *available = available
+            .checked_add(permits)
+            .ok_or_else(|| anyhow!("Semaphore {} permits overflow", name))?;
+        Ok(*available)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Primitives;
+    use crate::db::dbvalue::DBValue;
+
+    #[test]
+    fn atomic_test() {
+        let mut primitives = Primitives::default();
+        assert_eq!(primitives.atomic_add_and_get("counter", 5).unwrap(), 5);
+        assert_eq!(primitives.atomic_get_and_set("counter", 10), 5);
+        assert!(!primitives.atomic_compare_and_set("counter", 5, 20));
+        assert!(primitives.atomic_compare_and_set("counter", 10, i64::MAX));
+        assert!(primitives.atomic_add_and_get("counter", 1).is_err());
+
+        let leader = DBValue::String(String::from("node-1"));
+        assert!(primitives.reference_compare_and_set("leader", None, Some(leader.clone())));
+        assert!(!primitives.reference_compare_and_set("leader", None, None));
+        assert!(primitives.reference_get("leader") == Some(&leader));
+    }
+
+    #[test]
+    fn latch_and_semaphore_test() {
+        let mut primitives = Primitives::default();
+        assert!(primitives.latch_try_set_count("ready", 2));
+        assert!(!primitives.latch_try_set_count("ready", 3));
+        assert_eq!(primitives.latch_count_down("ready"), 1);
+        assert_eq!(primitives.latch_count_down("ready"), 0);
+        assert!(primitives.latch_try_set_count("ready", 1));
+
+        assert!(primitives.semaphore_try_acquire("slots", 1).is_err());
+        assert!(primitives.semaphore_init("slots", 2));
+        assert!(primitives.semaphore_try_acquire("slots", 2).unwrap());
+        assert!(!primitives.semaphore_try_acquire("slots", 1).unwrap());
+        assert_eq!(primitives.semaphore_release("slots", 1).unwrap(), 1);
+    }
+}
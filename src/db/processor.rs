@@ -0,0 +1,171 @@
+use std::sync::Arc;
+
+use ahash::AHashMap;
+use anyhow::anyhow;
+
+use super::dbvalue::DBValue;
+
+/// 在数据所在节点上原子修改一个key的函数。处理器随命令经raft复制后在每个副本上执行，
+/// 必须是确定性的：结果只能依赖key、当前值和参数，不能读取时钟、随机数或者外部状态。
+/// key不存在时value为DBValue::None，处理后value为DBValue::None时删除key
+pub trait EntryProcessor: Send + Sync {
+    fn process(&self, key: &str, value: &mut DBValue, args: &[DBValue]) -> anyhow::Result<DBValue>;
+}
+
+impl<F> EntryProcessor for F
+where
+    F: Fn(&str, &mut DBValue, &[DBValue]) -> anyhow::Result<DBValue> + Send + Sync,
+{
+    fn process(&self, key: &str, value: &mut DBValue, args: &[DBValue]) -> anyhow::Result<DBValue> {
+        self(key, value, args)
+    }
+}
+
+/// 已注册的处理器，所有节点必须注册相同的处理器
+#[derive(Clone)]
+pub struct ProcessorRegistry {
+    processors: AHashMap<String, Arc<dyn EntryProcessor>>,
+}
+
+impl Default for ProcessorRegistry {
+    fn default() -> Self {
+        let mut registry = ProcessorRegistry {
+            processors: AHashMap::new(),
+        };
+        registry.register("hash.incr", hash_incr);
+        registry.register("hash.cas", hash_compare_and_set);
+        registry.register("list.push_unique", list_push_unique);
+        registry
+    }
+}
+
+impl ProcessorRegistry {
+    /// 注册处理器，同名处理器会被替换
+    pub fn register(&mut self, name: &str, processor: impl EntryProcessor + 'static) {
+        self.processors
+            .insert(String::from(name), Arc::new(processor));
+    }
+
+    pub fn get(&self, name: &str) -> anyhow::Result<Arc<dyn EntryProcessor>> {
+        self.processors
+            .get(name)
+            .cloned()
+            .ok_or_else(|| anyhow!("No such entry processor {}", name))
+    }
+}
+
+fn arg<'a>(args: &'a [DBValue], index: usize, name: &str) -> anyhow::Result<&'a DBValue> {
+    args.get(index)
+        .ok_or_else(|| anyhow!("Entry processor requires argument {}", name))
+}
+
+fn field_arg(args: &[DBValue]) -> anyhow::Result<String> {
+    match arg(args, 0, "field")? {
+        DBValue::String(field) => Ok(field.clone()),
+        value => Err(anyhow!("Field must be a string, got {}", value)),
+    }
+}
+
+// key不存在时创建空hash
+fn hash_mut(value: &mut DBValue) -> anyhow::Result<&mut AHashMap<String, DBValue>> {
+    if let DBValue::None = value {
+        *value = DBValue::Hash(AHashMap::new());
+    }
+    match value {
+        DBValue::Hash(hash) => Ok(hash),
+        value => Err(anyhow!(
+            "Mismatch DBValue type, required Hash but got {}",
+            value
+        )),
+    }
+}
+
+/// hash成员增加delta，参数为[field, delta]，返回增加后的值
+fn hash_incr(_key: &str, value: &mut DBValue, args: &[DBValue]) -> anyhow::Result<DBValue> {
+    let field = field_arg(args)?;
+    let delta = arg(args, 1, "delta")?;
+    let hash = hash_mut(value)?;
+    let current = hash.get(&field).cloned().unwrap_or(DBValue::Int64(0));
+    let result = match (current, delta) {
+        (DBValue::Int64(v), DBValue::Int64(d)) => DBValue::Int64(
+            v.checked_add(*d)
+                .ok_or_else(|| anyhow!("Increment or decrement would overflow"))?,
+        ),
+        (DBValue::Int64(v), DBValue::Float64(d)) => DBValue::Float64(v as f64 + d),
+        (DBValue::Float64(v), DBValue::Int64(d)) => DBValue::Float64(v + *d as f64),
+        (DBValue::Float64(v), DBValue::Float64(d)) => DBValue::Float64(v + d),
+        (current, delta) => {
+            return Err(anyhow!("Can not increment {} by {}", current, delta));
+        }
+    };
+    hash.insert(field, result.clone());
+    Ok(result)
+}
+
+/// hash成员等于expect时设置为update，参数为[field, expect, update]，
+/// expect为None表示期望成员不存在，返回是否设置成功
+fn hash_compare_and_set(
+    _key: &str,
+    value: &mut DBValue,
+    args: &[DBValue],
+) -> anyhow::Result<DBValue> {
+    let field = field_arg(args)?;
+    let expect = arg(args, 1, "expect")?;
+    let update = arg(args, 2, "update")?;
+    let hash = hash_mut(value)?;
+    let matched = match hash.get(&field) {
+        Some(current) => current == expect,
+        None => matches!(expect, DBValue::None),
+    };
+    if matched {
+        hash.insert(field, update.clone());
+    }
+    if hash.is_empty() {
+        *value = DBValue::None;
+    }
+    Ok(DBValue::Boolean(matched))
+}
+
+/// 列表中不存在相同元素时追加到末尾，参数为[element]，返回是否追加
+fn list_push_unique(_key: &str, value: &mut DBValue, args: &[DBValue]) -> anyhow::Result<DBValue> {
+    let element = arg(args, 0, "element")?;
+    if let DBValue::None = value {
+        *value = DBValue::List(Default::default());
+    }
+    let DBValue::List(list) = value else {
+        return Err(anyhow!(
+            "Mismatch DBValue type, required List but got {}",
+            value
+        ));
+    };
+    if list.contains(element) {
+        return Ok(DBValue::Boolean(false));
+    }
+    list.push_back(element.clone());
+    Ok(DBValue::Boolean(true))
+}
+
+#[cfg(test)]
+mod test {
+    use super::ProcessorRegistry;
+    use crate::db::dbvalue::DBValue;
+
+    #[test]
+    fn builtin_processor_test() {
+        let registry = ProcessorRegistry::default();
+        let incr = registry.get("hash.incr").unwrap();
+        let mut value = DBValue::None;
+        let args = [DBValue::String(String::from("hits")), DBValue::Int64(2)];
+        assert!(incr.process("page", &mut value, &args).unwrap() == DBValue::Int64(2));
+        assert!(incr.process("page", &mut value, &args).unwrap() == DBValue::Int64(4));
+
+        let cas = registry.get("hash.cas").unwrap();
+        let args = [
+            DBValue::String(String::from("hits")),
+            DBValue::Int64(3),
+            DBValue::Int64(0),
+        ];
+        assert!(cas.process("page", &mut value, &args).unwrap() == DBValue::Boolean(false));
+        assert!(registry.get("missing").is_err());
+    }
+}
@@ -0,0 +1,511 @@
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+use anyhow::anyhow;
+
+use super::dbvalue::DBValue;
+use crate::until;
+
+/// 比较运算符
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+/// 查询条件中的常量
+#[derive(Clone, Debug, PartialEq)]
+pub enum Literal {
+    Number(f64),
+    String(String),
+    Boolean(bool),
+}
+
+/// hash成员上的查询条件
+#[derive(Clone, Debug, PartialEq)]
+pub enum Predicate {
+    // 空条件，匹配所有hash
+    All,
+    Compare(String, CompareOp, Literal),
+    // SQL风格的模式，%匹配任意字符串，_匹配单个字符
+    Like(String, String),
+    // 连续的AND、OR解析为一个节点，不会随条件数量加深
+    And(Vec<Predicate>),
+    Or(Vec<Predicate>),
+    Not(Box<Predicate>),
+}
+
+impl Predicate {
+    /// 解析查询条件，例如 region = 'eu' AND (age >= 18 OR NOT name LIKE 'test%')
+    pub fn parse(text: &str) -> anyhow::Result<Self> {
+        let tokens = tokenize(text)?;
+        if tokens.is_empty() {
+            return Ok(Predicate::All);
+        }
+        let mut parser = Parser {
+            tokens,
+            pos: 0,
+            depth: 0,
+        };
+        let predicate = parser.or()?;
+        match parser.tokens.get(parser.pos) {
+            None => Ok(predicate),
+            Some(token) => Err(anyhow!("Unexpected token {:?} in query", token)),
+        }
+    }
+
+    /// field返回hash成员的值，成员不存在或者类型不匹配时比较结果为false
+    pub fn eval<'a>(&self, field: &impl Fn(&str) -> Option<&'a DBValue>) -> bool {
+        match self {
+            Predicate::All => true,
+            Predicate::Compare(name, op, literal) => match field(name) {
+                Some(value) => compare(value, literal).is_some_and(|ord| op.accept(ord)),
+                None => false,
+            },
+            Predicate::Like(name, pattern) => match field(name) {
+                Some(DBValue::String(value)) => until::glob_match(pattern, value),
+                _ => false,
+            },
+            Predicate::And(all) => all.iter().all(|p| p.eval(field)),
+            Predicate::Or(any) => any.iter().any(|p| p.eval(field)),
+            Predicate::Not(a) => !a.eval(field),
+        }
+    }
+}
+
+impl CompareOp {
+    fn accept(&self, ord: Ordering) -> bool {
+        match self {
+            CompareOp::Eq => ord == Ordering::Equal,
+            CompareOp::Ne => ord != Ordering::Equal,
+            CompareOp::Lt => ord == Ordering::Less,
+            CompareOp::Le => ord != Ordering::Greater,
+            CompareOp::Gt => ord == Ordering::Greater,
+            CompareOp::Ge => ord != Ordering::Less,
+        }
+    }
+}
+
+// 数值之间按大小比较，字符串按字典序比较，布尔值false小于true，其它组合不可比较
+fn compare(value: &DBValue, literal: &Literal) -> Option<Ordering> {
+    match (value, literal) {
+        (DBValue::Int64(v), Literal::Number(n)) => (*v as f64).partial_cmp(n),
+        (DBValue::Float64(v), Literal::Number(n)) => v.partial_cmp(n),
+        (DBValue::String(v), Literal::String(s)) => Some(v.as_str().cmp(s.as_str())),
+        (DBValue::Boolean(v), Literal::Boolean(b)) => Some(v.cmp(b)),
+        _ => None,
+    }
+}
+
+// 将LIKE模式转换为glob模式
+fn like_to_glob(pattern: &str) -> String {
+    let mut glob = String::with_capacity(pattern.len());
+    for c in pattern.chars() {
+        match c {
+            '%' => glob.push('*'),
+            '_' => glob.push('?'),
+            '*' | '?' | '[' | ']' | '\\' => {
+                glob.push('\\');
+                glob.push(c);
+            }
+            _ => glob.push(c),
+        }
+    }
+    glob
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Ident(String),
+    String(String),
+    Number(f64),
+    Op(CompareOp),
+    LParen,
+    RParen,
+}
+
+fn tokenize(text: &str) -> anyhow::Result<Vec<Token>> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            c if c.is_whitespace() => i += 1,
+            '(' => {
+                tokens.push(Token::LParen);
+                i += 1;
+            }
+            ')' => {
+                tokens.push(Token::RParen);
+                i += 1;
+            }
+            '=' => {
+                tokens.push(Token::Op(CompareOp::Eq));
+                i += 1;
+            }
+            '!' | '<' | '>' => {
+                let next = chars.get(i + 1).copied();
+                let (op, len) = match (c, next) {
+                    ('!', Some('=')) => (CompareOp::Ne, 2),
+                    ('<', Some('>')) => (CompareOp::Ne, 2),
+                    ('<', Some('=')) => (CompareOp::Le, 2),
+                    ('>', Some('=')) => (CompareOp::Ge, 2),
+                    ('<', _) => (CompareOp::Lt, 1),
+                    ('>', _) => (CompareOp::Gt, 1),
+                    _ => return Err(anyhow!("Unexpected character {} in query", c)),
+                };
+                tokens.push(Token::Op(op));
+                i += len;
+            }
+            // 字符串常量，连续两个引号表示引号本身
+            '\'' | '"' => {
+                let mut value = String::new();
+                i += 1;
+                loop {
+                    match chars.get(i) {
+                        Some(q) if *q == c && chars.get(i + 1) == Some(&c) => {
+                            value.push(c);
+                            i += 2;
+                        }
+                        Some(q) if *q == c => {
+                            i += 1;
+                            break;
+                        }
+                        Some(q) => {
+                            value.push(*q);
+                            i += 1;
+                        }
+                        None => return Err(anyhow!("Unterminated string in query")),
+                    }
+                }
+                tokens.push(Token::String(value));
+            }
+            c if c.is_ascii_digit() || c == '-' => {
+                let start = i;
+                i += 1;
+                while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                    i += 1;
+                }
+                let number: String = chars[start..i].iter().collect();
+                let number = number
+                    .parse()
+                    .map_err(|_| anyhow!("Invalid number {} in query", number))?;
+                tokens.push(Token::Number(number));
+            }
+            c if c.is_alphanumeric() || c == '_' => {
+                let start = i;
+                while i < chars.len()
+                    && (chars[i].is_alphanumeric() || matches!(chars[i], '_' | ':' | '.'))
+                {
+                    i += 1;
+                }
+                tokens.push(Token::Ident(chars[start..i].iter().collect()));
+            }
+            _ => return Err(anyhow!("Unexpected character {} in query", c)),
+        }
+    }
+    Ok(tokens)
+}
+
+// 查询条件中括号的最大嵌套深度
+const MAX_QUERY_DEPTH: usize = 128;
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        match self.tokens.get(self.pos) {
+            Some(Token::Ident(ident)) => ident.eq_ignore_ascii_case(keyword),
+            _ => false,
+        }
+    }
+
+    fn next(&mut self) -> anyhow::Result<Token> {
+        let token = self
+            .tokens
+            .get(self.pos)
+            .cloned()
+            .ok_or_else(|| anyhow!("Unexpected end of query"))?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn or(&mut self) -> anyhow::Result<Predicate> {
+        let mut any = vec![self.and()?];
+        while self.peek_keyword("OR") {
+            self.pos += 1;
+            any.push(self.and()?);
+        }
+        Ok(if any.len() == 1 {
+            any.pop().unwrap()
+        } else {
+            Predicate::Or(any)
+        })
+    }
+
+    fn and(&mut self) -> anyhow::Result<Predicate> {
+        let mut all = vec![self.not()?];
+        while self.peek_keyword("AND") {
+            self.pos += 1;
+            all.push(self.not()?);
+        }
+        Ok(if all.len() == 1 {
+            all.pop().unwrap()
+        } else {
+            Predicate::And(all)
+        })
+    }
+
+    // 连续的NOT两两抵消，最多生成一层NOT
+    fn not(&mut self) -> anyhow::Result<Predicate> {
+        let mut negate = false;
+        while self.peek_keyword("NOT") {
+            self.pos += 1;
+            negate = !negate;
+        }
+        let predicate = self.primary()?;
+        Ok(if negate {
+            Predicate::Not(Box::new(predicate))
+        } else {
+            predicate
+        })
+    }
+
+    fn primary(&mut self) -> anyhow::Result<Predicate> {
+        let field = match self.next()? {
+            Token::LParen => {
+                self.depth += 1;
+                if self.depth > MAX_QUERY_DEPTH {
+                    return Err(anyhow!(
+                        "Query is nested too deeply, maximum depth is {}",
+                        MAX_QUERY_DEPTH
+                    ));
+                }
+                let predicate = self.or()?;
+                self.depth -= 1;
+                return match self.next()? {
+                    Token::RParen => Ok(predicate),
+                    token => Err(anyhow!("Expected ) but got {:?} in query", token)),
+                };
+            }
+            Token::Ident(field) => field,
+            token => return Err(anyhow!("Expected field but got {:?} in query", token)),
+        };
+        // field NOT LIKE 'pattern'
+        let negate = self.peek_keyword("NOT");
+        if negate {
+            self.pos += 1;
+        }
+        if self.peek_keyword("LIKE") {
+            self.pos += 1;
+            let like = match self.next()? {
+                Token::String(pattern) => Predicate::Like(field, like_to_glob(&pattern)),
+                token => return Err(anyhow!("LIKE requires a string but got {:?}", token)),
+            };
+            return Ok(if negate {
+                Predicate::Not(Box::new(like))
+            } else {
+                like
+            });
+        }
+        if negate {
+            return Err(anyhow!("Expected LIKE after NOT in query"));
+        }
+        let op = match self.next()? {
+            Token::Op(op) => op,
+            token => return Err(anyhow!("Expected operator but got {:?} in query", token)),
+        };
+        let literal = match self.next()? {
+            Token::Number(n) => Literal::Number(n),
+            Token::String(s) => Literal::String(s),
+            Token::Ident(b) if b.eq_ignore_ascii_case("true") => Literal::Boolean(true),
+            Token::Ident(b) if b.eq_ignore_ascii_case("false") => Literal::Boolean(false),
+            token => return Err(anyhow!("Expected value but got {:?} in query", token)),
+        };
+        Ok(Predicate::Compare(field, op, literal))
+    }
+}
+
+/// 聚合函数
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AggregateFunction {
+    Count,
+    Sum,
+    Avg,
+    Min,
+    Max,
+}
+
+/// 可以合并的部分聚合结果，各节点在本地数据上计算后由协调节点合并
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PartialAggregate {
+    pub count: u64,
+    // 参与计算的数值数量，成员不是数值的hash只参与COUNT
+    pub numbers: u64,
+    pub sum: f64,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+}
+
+impl PartialAggregate {
+    pub fn accumulate(&mut self, value: Option<&DBValue>) {
+        self.count += 1;
+        let number = match value {
+            Some(DBValue::Int64(v)) => *v as f64,
+            Some(DBValue::Float64(v)) if !v.is_nan() => *v,
+            _ => return,
+        };
+        self.numbers += 1;
+        self.sum += number;
+        self.min = Some(self.min.map_or(number, |min| min.min(number)));
+        self.max = Some(self.max.map_or(number, |max| max.max(number)));
+    }
+
+    /// 合并另一个节点上计算的部分结果
+    pub fn merge(&mut self, other: &PartialAggregate) {
+        self.count += other.count;
+        self.numbers += other.numbers;
+        self.sum += other.sum;
+        self.min = match (self.min, other.min) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        self.max = match (self.max, other.max) {
+            (Some(a), Some(b)) => Some(a.max(b)),
+            (a, b) => a.or(b),
+        };
+    }
+
+    /// 计算最终结果，没有数值时SUM为0，AVG、MIN和MAX为None
+    pub fn finish(&self, function: AggregateFunction) -> DBValue {
+        match function {
+            AggregateFunction::Count => DBValue::Int64(self.count as i64),
+            AggregateFunction::Sum => DBValue::Float64(self.sum),
+            AggregateFunction::Avg if self.numbers > 0 => {
+                DBValue::Float64(self.sum / self.numbers as f64)
+            }
+            AggregateFunction::Min => self.min.map_or(DBValue::None, DBValue::Float64),
+            AggregateFunction::Max => self.max.map_or(DBValue::None, DBValue::Float64),
+            AggregateFunction::Avg => DBValue::None,
+        }
+    }
+}
+
+/// 按分组成员的值分组的部分聚合结果，没有分组成员的hash归入空字符串分组
+pub type GroupedAggregate = BTreeMap<String, PartialAggregate>;
+
+/// 分组成员的值转换为分组名称
+pub fn group_name(value: Option<&DBValue>) -> String {
+    match value {
+        None | Some(DBValue::None) => String::new(),
+        Some(DBValue::String(v)) => v.clone(),
+        Some(DBValue::Int64(v)) => v.to_string(),
+        Some(DBValue::Float64(v)) => v.to_string(),
+        Some(DBValue::Boolean(v)) => v.to_string(),
+        Some(value) => value.to_string(),
+    }
+}
+
+/// 合并各节点的分组聚合结果
+pub fn merge_groups(target: &mut GroupedAggregate, other: &GroupedAggregate) {
+    for (group, partial) in other {
+        target.entry(group.clone()).or_default().merge(partial);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{merge_groups, AggregateFunction, GroupedAggregate, PartialAggregate, Predicate};
+    use crate::db::dbvalue::DBValue;
+    use ahash::AHashMap;
+
+    fn row(fields: &[(&str, DBValue)]) -> AHashMap<String, DBValue> {
+        fields
+            .iter()
+            .map(|(name, value)| (String::from(*name), value.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn predicate_test() {
+        let user = row(&[
+            ("region", DBValue::String(String::from("eu-west"))),
+            ("age", DBValue::Int64(30)),
+            ("active", DBValue::Boolean(true)),
+        ]);
+        let field = |name: &str| user.get(name);
+        let accept = |text: &str| Predicate::parse(text).unwrap().eval(&field);
+        assert!(accept(""));
+        assert!(accept("region LIKE 'eu%' AND age >= 18"));
+        assert!(accept("NOT (age < 18 OR active = false)"));
+        assert!(accept("region NOT LIKE 'us_%'"));
+        assert!(!accept("age = '30'"));
+        assert!(!accept("missing != 1"));
+        assert!(Predicate::parse("age >").is_err());
+        assert!(Predicate::parse("age = 1 extra").is_err());
+        // 嵌套过深的条件在解析时拒绝
+        let nested = format!("{}age = 1{}", "(".repeat(200), ")".repeat(200));
+        assert!(Predicate::parse(&nested).is_err());
+        // 很长的AND、OR链和连续的NOT不受嵌套深度限制
+        let mut chain = vec!["age = 1"; 1000];
+        chain.push("age = 30");
+        assert!(accept(&chain.join(" OR ")));
+        let chain = vec!["age = 30"; 1000].join(" AND ");
+        assert!(accept(&chain));
+        assert!(accept(&format!("{}age = 30", "NOT ".repeat(200))));
+        assert!(!accept(&format!("{}age = 30", "NOT ".repeat(201))));
+        let wide = vec!["(age = 1 AND age = 2)"; 100].join(" OR ");
+        assert!(Predicate::parse(&wide).is_ok());
+    }
+
+    #[test]
+    fn group_aggregate_test() {
+        let mut a = GroupedAggregate::new();
+        a.entry(String::from("eu"))
+            .or_default()
+            .accumulate(Some(&DBValue::Int64(10)));
+        a.entry(String::from("eu"))
+            .or_default()
+            .accumulate(Some(&DBValue::Float64(20.0)));
+        a.entry(String::from("us")).or_default().accumulate(None);
+        let eu = &a["eu"];
+        assert!(eu.finish(AggregateFunction::Avg) == DBValue::Float64(15.0));
+        assert!(eu.finish(AggregateFunction::Min) == DBValue::Float64(10.0));
+        assert!(a["us"].finish(AggregateFunction::Count) == DBValue::Int64(1));
+        assert!(a["us"].finish(AggregateFunction::Max) == DBValue::None);
+        assert!(
+            PartialAggregate::default().finish(AggregateFunction::Sum) == DBValue::Float64(0.0)
+        );
+    }
+
+    #[test]
+    fn merge_aggregate_test() {
+        // 两个节点各自计算部分结果后合并
+        let mut a = GroupedAggregate::new();
+        let mut b = GroupedAggregate::new();
+        a.entry(String::from("eu"))
+            .or_default()
+            .accumulate(Some(&DBValue::Int64(10)));
+        b.entry(String::from("eu"))
+            .or_default()
+            .accumulate(Some(&DBValue::Float64(30.0)));
+        b.entry(String::from("us")).or_default().accumulate(None);
+        merge_groups(&mut a, &b);
+        let eu = &a["eu"];
+        assert!(eu.finish(AggregateFunction::Count) == DBValue::Int64(2));
+        assert!(eu.finish(AggregateFunction::Avg) == DBValue::Float64(20.0));
+        assert!(eu.finish(AggregateFunction::Min) == DBValue::Float64(10.0));
+        assert!(eu.finish(AggregateFunction::Max) == DBValue::Float64(30.0));
+        assert!(a["us"].finish(AggregateFunction::Count) == DBValue::Int64(1));
+        assert!(a["us"].finish(AggregateFunction::Sum) == DBValue::Float64(0.0));
+    }
+}
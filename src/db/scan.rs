@@ -0,0 +1,120 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+
+use ahash::AHashMap;
+
+// 最多保留的成员快照数量，超出时丢弃最早生成的快照
+const MAX_SNAPSHOTS: usize = 64;
+
+// 一次遍历的成员快照，以最小堆保存尚未返回的成员
+struct Snapshot {
+    pending: BinaryHeap<Reverse<String>>,
+    // 上一页返回的最后一个成员，与游标不一致时重新生成快照
+    last: Option<String>,
+}
+
+/// Hash、Set的成员内部无序，遍历的第一页复制成员建立最小堆，之后每一页从堆中按字典序弹出成员。
+/// 建堆的耗时与集合大小成正比，排序的开销分摊到各页，每一页的耗时为O(count * log n)。
+/// 遍历期间一直存在的成员都在快照中，新增的成员可能不返回，已删除的成员由调用方在返回前过滤。
+/// 快照只在本节点有效，不参与复制
+#[derive(Default)]
+pub struct MemberSnapshots {
+    snapshots: AHashMap<String, Snapshot>,
+    // 快照生成的顺序
+    order: VecDeque<String>,
+}
+
+impl MemberSnapshots {
+    /// 返回after之后的count个成员以及下一次遍历的起点。after为None、快照已释放，
+    /// 或者after不是快照上一页的结尾(例如同一个key上的另一个遍历)时重新生成快照，
+    /// 只保留after之后的成员
+    pub fn page(
+        &mut self,
+        key: &str,
+        after: Option<&str>,
+        count: usize,
+        members: impl FnOnce() -> Vec<String>,
+    ) -> (Vec<String>, Option<String>) {
+        let reusable = after.is_some()
+            && self
+                .snapshots
+                .get(key)
+                .is_some_and(|snapshot| snapshot.last.as_deref() == after);
+        if !reusable {
+            let pending = members()
+                .into_iter()
+                .filter(|member| after.map_or(true, |after| member.as_str() > after))
+                .map(Reverse)
+                .collect();
+            self.insert(
+                key,
+                Snapshot {
+                    pending,
+                    last: None,
+                },
+            );
+        }
+        let Some(snapshot) = self.snapshots.get_mut(key) else {
+            return (Vec::new(), None);
+        };
+        let mut page = Vec::with_capacity(count.min(snapshot.pending.len()));
+        while page.len() < count {
+            match snapshot.pending.pop() {
+                Some(Reverse(member)) => page.push(member),
+                None => break,
+            }
+        }
+        if snapshot.pending.is_empty() {
+            // 遍历结束，释放快照
+            self.remove(key);
+            (page, None)
+        } else {
+            snapshot.last = page.last().cloned();
+            let next = snapshot.last.clone();
+            (page, next)
+        }
+    }
+
+    fn remove(&mut self, key: &str) {
+        if self.snapshots.remove(key).is_some() {
+            self.order.retain(|k| k != key);
+        }
+    }
+
+    fn insert(&mut self, key: &str, snapshot: Snapshot) {
+        if self.snapshots.insert(String::from(key), snapshot).is_none() {
+            self.order.push_back(String::from(key));
+        }
+        while self.order.len() > MAX_SNAPSHOTS {
+            if let Some(oldest) = self.order.pop_front() {
+                self.snapshots.remove(&oldest);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::MemberSnapshots;
+
+    #[test]
+    fn member_snapshot_page_test() {
+        let mut snapshots = MemberSnapshots::default();
+        let members = || ["d", "a", "e", "c", "b"].map(String::from).to_vec();
+        let (page, next) = snapshots.page("k", None, 2, members);
+        assert_eq!(page, vec!["a", "b"]);
+        assert_eq!(next.as_deref(), Some("b"));
+        // 后续页使用快照，不再读取成员
+        let (page, next) = snapshots.page("k", next.as_deref(), 2, || unreachable!());
+        assert_eq!(page, vec!["c", "d"]);
+        // 同一个key上游标不一致的遍历重新生成快照
+        let (page, _) = snapshots.page("k", Some("a"), 2, members);
+        assert_eq!(page, vec!["b", "c"]);
+        let (page, next) = snapshots.page("k", next.as_deref(), 2, members);
+        assert_eq!(page, vec!["e"]);
+        assert_eq!(next, None);
+        // 快照已经释放，游标之后的成员从新快照中查找
+        let (page, _) = snapshots.page("k", Some("c"), 10, members);
+        assert_eq!(page, vec!["d", "e"]);
+    }
+}
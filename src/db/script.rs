@@ -0,0 +1,356 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::{Arc, Mutex};
+
+use ahash::AHashMap;
+use anyhow::anyhow;
+use rhai::module_resolvers::DummyModuleResolver;
+use rhai::{Array, Blob, Dynamic, Engine, EvalAltResult, Map, Scope, AST};
+use sha1::{Digest, Sha1};
+
+use super::dbvalue::DBValue;
+
+/// 脚本的资源限制
+#[derive(Clone, Debug)]
+pub struct ScriptLimits {
+    // 单次执行最多执行的操作数
+    pub max_operations: u64,
+    // 单个字符串、数组或者对象的最大长度
+    pub max_value_size: usize,
+}
+
+impl Default for ScriptLimits {
+    fn default() -> Self {
+        ScriptLimits {
+            max_operations: 1_000_000,
+            max_value_size: 1 << 20,
+        }
+    }
+}
+
+// 脚本执行期间声明的key的值，脚本的修改在执行成功后才写回数据库
+#[derive(Default)]
+struct ScriptState {
+    values: BTreeMap<String, Option<DBValue>>,
+    written: BTreeSet<String>,
+}
+
+type ScriptResult<T> = Result<T, Box<EvalAltResult>>;
+
+impl ScriptState {
+    fn check(&self, key: &str) -> ScriptResult<()> {
+        if self.values.contains_key(key) {
+            Ok(())
+        } else {
+            Err(format!("Key {} is not declared in KEYS", key).into())
+        }
+    }
+}
+
+// 最多加载的脚本数量，超出时需要先清空
+const MAX_SCRIPTS: usize = 1024;
+
+/// 已加载的脚本，以脚本内容的SHA1作为标识
+#[derive(Default)]
+pub struct ScriptEngine {
+    pub limits: ScriptLimits,
+    scripts: AHashMap<String, Arc<AST>>,
+}
+
+/// 脚本内容的SHA1，十六进制小写
+pub fn script_sha(source: &str) -> String {
+    let digest = Sha1::digest(source.as_bytes());
+    digest.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+impl ScriptEngine {
+    /// 编译并缓存脚本，返回脚本的SHA1
+    pub fn load(&mut self, source: &str) -> anyhow::Result<String> {
+        let sha = script_sha(source);
+        if !self.scripts.contains_key(&sha) {
+            if self.scripts.len() >= MAX_SCRIPTS {
+                return Err(anyhow!(
+                    "Too many scripts loaded, maximum is {}, please use ScriptFlush",
+                    MAX_SCRIPTS
+                ));
+            }
+            let ast = self.compile(source)?;
+            self.scripts.insert(sha.clone(), Arc::new(ast));
+        }
+        Ok(sha)
+    }
+
+    /// 编译脚本但不缓存
+    pub fn compile(&self, source: &str) -> anyhow::Result<AST> {
+        self.sandbox()
+            .compile(source)
+            .map_err(|err| anyhow!("Compile script error: {}", err))
+    }
+
+    /// 清空已加载的脚本，返回清空的数量
+    pub fn flush(&mut self) -> usize {
+        let count = self.scripts.len();
+        self.scripts.clear();
+        count
+    }
+
+    pub fn exists(&self, sha: &str) -> bool {
+        self.scripts.contains_key(sha)
+    }
+
+    pub fn get(&self, sha: &str) -> anyhow::Result<Arc<AST>> {
+        self.scripts
+            .get(sha)
+            .cloned()
+            .ok_or_else(|| anyhow!("NOSCRIPT No matching script, please use ScriptLoad"))
+    }
+
+    // 沙箱引擎：不能加载模块、不能调用eval，输出被丢弃，并限制操作数和值的大小
+    fn sandbox(&self) -> Engine {
+        let mut engine = Engine::new();
+        engine.set_module_resolver(DummyModuleResolver::new());
+        engine.disable_symbol("eval");
+        engine.on_print(|_| {});
+        engine.on_debug(|_, _, _| {});
+        engine.set_max_operations(self.limits.max_operations);
+        engine.set_max_string_size(self.limits.max_value_size);
+        engine.set_max_array_size(self.limits.max_value_size);
+        engine.set_max_map_size(self.limits.max_value_size);
+        engine.set_max_call_levels(64);
+        engine.set_max_expr_depths(64, 64);
+        engine
+    }
+
+    /// 执行脚本，values为声明的key及其当前值，脚本中可以通过KEYS、ARGV访问key和参数，
+    /// 通过get/put/del/exists读写声明的key，now()返回命令时间戳。
+    /// 返回脚本的结果以及按key排序的修改，值为None表示删除
+    pub fn run(
+        &self,
+        ast: &AST,
+        values: Vec<(String, Option<DBValue>)>,
+        args: &[DBValue],
+        now: u128,
+    ) -> anyhow::Result<(DBValue, Vec<(String, DBValue)>)> {
+        let keys: Array = values
+            .iter()
+            .map(|(key, _)| Dynamic::from(key.clone()))
+            .collect();
+        let state = Arc::new(Mutex::new(ScriptState {
+            values: values.into_iter().collect(),
+            written: BTreeSet::new(),
+        }));
+        let mut engine = self.sandbox();
+        let reader = state.clone();
+        engine.register_fn("get", move |key: &str| -> ScriptResult<Dynamic> {
+            let state = reader.lock().map_err(|err| err.to_string())?;
+            state.check(key)?;
+            match state.values.get(key) {
+                Some(Some(value)) => to_dynamic(value).map_err(|err| err.to_string().into()),
+                _ => Ok(Dynamic::UNIT),
+            }
+        });
+        let checker = state.clone();
+        engine.register_fn("exists", move |key: &str| -> ScriptResult<bool> {
+            let state = checker.lock().map_err(|err| err.to_string())?;
+            state.check(key)?;
+            Ok(matches!(state.values.get(key), Some(Some(_))))
+        });
+        let writer = state.clone();
+        engine.register_fn(
+            "put",
+            move |key: &str, value: Dynamic| -> ScriptResult<()> {
+                let mut state = writer.lock().map_err(|err| err.to_string())?;
+                state.check(key)?;
+                let value = from_dynamic(value).map_err(|err| err.to_string())?;
+                let value = (!matches!(value, DBValue::None)).then_some(value);
+                state.values.insert(String::from(key), value);
+                state.written.insert(String::from(key));
+                Ok(())
+            },
+        );
+        let deleter = state.clone();
+        engine.register_fn("del", move |key: &str| -> ScriptResult<bool> {
+            let mut state = deleter.lock().map_err(|err| err.to_string())?;
+            state.check(key)?;
+            let existed = state
+                .values
+                .insert(String::from(key), None)
+                .flatten()
+                .is_some();
+            state.written.insert(String::from(key));
+            Ok(existed)
+        });
+        let now = now as i64;
+        engine.register_fn("now", move || now);
+
+        let argv: Array = args.iter().map(to_dynamic).collect::<Result<_, _>>()?;
+        let mut scope = Scope::new();
+        scope.push_constant("KEYS", keys);
+        scope.push_constant("ARGV", argv);
+        let result = engine
+            .eval_ast_with_scope::<Dynamic>(&mut scope, ast)
+            .map_err(|err| anyhow!("Script error: {}", err))?;
+        let result = from_dynamic(result)?;
+
+        let mut state = state
+            .lock()
+            .map_err(|_| anyhow!("Script state is poisoned"))?;
+        let written = std::mem::take(&mut state.written);
+        let writes = written
+            .into_iter()
+            .map(|key| {
+                let value = state.values.remove(&key).flatten();
+                (key, value.unwrap_or(DBValue::None))
+            })
+            .collect();
+        Ok((result, writes))
+    }
+}
+
+/// 转换为脚本中的值，集合转换为有序的数组，有序集合转换为成员到分数的对象
+pub fn to_dynamic(value: &DBValue) -> anyhow::Result<Dynamic> {
+    let dynamic = match value {
+        DBValue::None => Dynamic::UNIT,
+        DBValue::Boolean(v) => Dynamic::from(*v),
+        DBValue::String(v) => Dynamic::from(v.clone()),
+        DBValue::Bytes(v) => Dynamic::from_blob(v.clone()),
+        DBValue::Int64(v) => Dynamic::from(*v),
+        DBValue::Float64(v) => Dynamic::from(*v),
+        DBValue::List(list) => {
+            let array: Array = list.iter().map(to_dynamic).collect::<Result<_, _>>()?;
+            Dynamic::from_array(array)
+        }
+        DBValue::Hash(hash) => {
+            let mut map = Map::new();
+            for (field, value) in hash {
+                map.insert(field.as_str().into(), to_dynamic(value)?);
+            }
+            Dynamic::from_map(map)
+        }
+        DBValue::Set(set) => {
+            let mut members: Vec<&String> = set.iter().collect();
+            members.sort();
+            Dynamic::from_array(
+                members
+                    .into_iter()
+                    .map(|m| Dynamic::from(m.clone()))
+                    .collect(),
+            )
+        }
+        DBValue::SortedSet(zset) => {
+            let mut map = Map::new();
+            for (member, score) in zset.iter() {
+                map.insert(member.into(), Dynamic::from(score));
+            }
+            Dynamic::from_map(map)
+        }
+        DBValue::Timestamp(_)
+        | DBValue::Stream(_)
+        | DBValue::HyperLogLog(_)
+        | DBValue::BloomFilter(_) => {
+            return Err(anyhow!("{} can not be used in scripts", value.type_name()));
+        }
+    };
+    Ok(dynamic)
+}
+
+/// 从脚本中的值转换，()转换为None
+pub fn from_dynamic(value: Dynamic) -> anyhow::Result<DBValue> {
+    if value.is_unit() {
+        return Ok(DBValue::None);
+    }
+    if let Ok(v) = value.as_bool() {
+        return Ok(DBValue::Boolean(v));
+    }
+    if let Ok(v) = value.as_int() {
+        return Ok(DBValue::Int64(v));
+    }
+    if let Ok(v) = value.as_float() {
+        return Ok(DBValue::Float64(v));
+    }
+    if let Ok(v) = value.as_char() {
+        return Ok(DBValue::String(v.to_string()));
+    }
+    if value.is_string() {
+        let v = value
+            .into_string()
+            .map_err(|t| anyhow!("Invalid script value {}", t))?;
+        return Ok(DBValue::String(v));
+    }
+    if value.is_blob() {
+        return Ok(DBValue::Bytes(value.cast::<Blob>()));
+    }
+    if value.is_array() {
+        let list = value
+            .cast::<Array>()
+            .into_iter()
+            .map(from_dynamic)
+            .collect::<Result<_, _>>()?;
+        return Ok(DBValue::List(list));
+    }
+    if value.is_map() {
+        let mut hash = AHashMap::new();
+        for (field, value) in value.cast::<Map>() {
+            hash.insert(field.to_string(), from_dynamic(value)?);
+        }
+        return Ok(DBValue::Hash(hash));
+    }
+    Err(anyhow!(
+        "Script value of type {} can not be stored",
+        value.type_name()
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{script_sha, ScriptEngine, ScriptLimits};
+    use crate::db::dbvalue::DBValue;
+
+    #[test]
+    fn run_script_test() {
+        let mut engine = ScriptEngine::default();
+        let sha = engine
+            .load(
+                "let n = get(KEYS[0]); if n == () { n = 0 } put(KEYS[0], n + ARGV[0]); n + ARGV[0]",
+            )
+            .unwrap();
+        assert_eq!(
+            sha,
+            script_sha(
+                "let n = get(KEYS[0]); if n == () { n = 0 } put(KEYS[0], n + ARGV[0]); n + ARGV[0]"
+            )
+        );
+        let ast = engine.get(&sha).unwrap();
+        let values = vec![(String::from("counter"), Some(DBValue::Int64(5)))];
+        let (result, writes) = engine.run(&ast, values, &[DBValue::Int64(2)], 0).unwrap();
+        assert!(result == DBValue::Int64(7));
+        assert_eq!(writes.len(), 1);
+        assert!(writes[0].1 == DBValue::Int64(7));
+
+        // 未声明的key不能访问
+        let sha = engine.load(r#"get("other")"#).unwrap();
+        let ast = engine.get(&sha).unwrap();
+        assert!(engine.run(&ast, vec![], &[], 0).is_err());
+        assert!(engine.get("missing").is_err());
+    }
+
+    #[test]
+    fn operation_limit_test() {
+        let mut engine = ScriptEngine {
+            limits: ScriptLimits {
+                max_operations: 1_000,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let sha = engine.load("loop { }").unwrap();
+        let ast = engine.get(&sha).unwrap();
+        assert!(engine.run(&ast, vec![], &[], 0).is_err());
+        assert!(engine
+            .load(r#"import "fs" as fs;"#)
+            .and_then(|sha| {
+                let ast = engine.get(&sha)?;
+                engine.run(&ast, vec![], &[], 0)
+            })
+            .is_err());
+    }
+}
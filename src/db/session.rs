@@ -0,0 +1,91 @@
+use ahash::AHashMap;
+
+/// 客户端会话，超过超时时间没有心跳的会话视为已过期
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClientSession {
+    pub timeout_ms: u64,
+    // 会话到期时间戳(毫秒)
+    pub deadline: u128,
+}
+
+/// 所有命名空间共享的客户端会话
+#[derive(Default)]
+pub struct SessionTable {
+    sessions: AHashMap<String, ClientSession>,
+}
+
+impl SessionTable {
+    pub fn len(&self) -> usize {
+        self.sessions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sessions.is_empty()
+    }
+
+    pub fn get(&self, id: &str) -> Option<&ClientSession> {
+        self.sessions.get(id)
+    }
+
+    pub fn is_alive(&self, id: &str, now: u128) -> bool {
+        self.sessions
+            .get(id)
+            .is_some_and(|session| session.deadline > now)
+    }
+
+    /// 创建会话，会话已存在时更新超时时间，返回会话到期时间
+    pub fn open(&mut self, id: &str, timeout_ms: u64, now: u128) -> u128 {
+        let deadline = now + timeout_ms as u128;
+        let session = ClientSession {
+            timeout_ms,
+            deadline,
+        };
+        self.sessions.insert(String::from(id), session);
+        deadline
+    }
+
+    /// 心跳延长会话，会话不存在或者已经过期返回None
+    pub fn heartbeat(&mut self, id: &str, now: u128) -> Option<u128> {
+        let session = self
+            .sessions
+            .get_mut(id)
+            .filter(|session| session.deadline > now)?;
+        session.deadline = now + session.timeout_ms as u128;
+        Some(session.deadline)
+    }
+
+    pub fn remove(&mut self, id: &str) -> bool {
+        self.sessions.remove(id).is_some()
+    }
+
+    /// 已经过期的会话，按会话ID排序
+    pub fn expired(&self, now: u128) -> Vec<String> {
+        let mut expired: Vec<String> = self
+            .sessions
+            .iter()
+            .filter(|(_, session)| session.deadline <= now)
+            .map(|(id, _)| id.clone())
+            .collect();
+        expired.sort();
+        expired
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::SessionTable;
+
+    #[test]
+    fn heartbeat_test() {
+        let mut sessions = SessionTable::default();
+        assert_eq!(sessions.open("s1", 100, 0), 100);
+        assert_eq!(sessions.heartbeat("s1", 50), Some(150));
+        assert!(sessions.is_alive("s1", 149));
+        assert!(sessions.expired(149).is_empty());
+        // 过期后心跳无效，需要重新创建会话
+        assert_eq!(sessions.heartbeat("s1", 150), None);
+        assert_eq!(sessions.expired(150), vec![String::from("s1")]);
+        assert!(sessions.remove("s1"));
+        assert!(sessions.is_empty());
+    }
+}
@@ -0,0 +1,333 @@
+use std::cmp::Ordering;
+use std::collections::BTreeSet;
+use std::ops::Bound;
+
+use ahash::AHashMap;
+use anyhow::anyhow;
+
+// 分数，使用total_cmp实现全序，保证BTreeSet中的排列在所有副本上一致
+#[derive(Clone, Copy, Debug)]
+pub struct Score(pub f64);
+
+impl PartialEq for Score {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.total_cmp(&other.0) == Ordering::Equal
+    }
+}
+
+impl Eq for Score {}
+
+impl PartialOrd for Score {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Score {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+// -0.0与0.0在total_cmp中排序不同，统一为0.0
+fn normalize(score: f64) -> f64 {
+    if score == 0.0 {
+        0.0
+    } else {
+        score
+    }
+}
+
+// 分数大于score的最小分数，score为+inf时不存在
+fn next_score(score: f64) -> Option<f64> {
+    if score == f64::INFINITY {
+        None
+    } else {
+        Some(score.next_up())
+    }
+}
+
+/// 有序集合：按分数排序，分数相同时按成员字典序排序
+#[derive(Clone, Default)]
+pub struct SortedSet {
+    scores: AHashMap<String, f64>,
+    index: BTreeSet<(Score, String)>,
+}
+
+impl PartialEq for SortedSet {
+    fn eq(&self, other: &Self) -> bool {
+        self.index == other.index
+    }
+}
+
+impl SortedSet {
+    pub fn new() -> Self {
+        SortedSet::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.scores.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.scores.is_empty()
+    }
+
+    pub fn score(&self, member: &str) -> Option<f64> {
+        self.scores.get(member).copied()
+    }
+
+    /// 插入或更新成员，返回是否为新增成员
+    pub fn insert(&mut self, member: String, score: f64) -> bool {
+        let score = normalize(score);
+        match self.scores.insert(member.clone(), score) {
+            Some(old) => {
+                self.index.remove(&(Score(old), member.clone()));
+                self.index.insert((Score(score), member));
+                false
+            }
+            None => {
+                self.index.insert((Score(score), member));
+                true
+            }
+        }
+    }
+
+    pub fn remove(&mut self, member: &str) -> bool {
+        match self.scores.remove(member) {
+            Some(score) => {
+                self.index.remove(&(Score(score), String::from(member)));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 成员的排名，从0开始。BTreeSet不记录子树大小，需要计数排在前面的成员，耗时O(n)
+    pub fn rank(&self, member: &str) -> Option<usize> {
+        let score = self.score(member)?;
+        let key = (Score(score), String::from(member));
+        Some(self.index.range(..key).count())
+    }
+
+    /// 按照排名升序遍历
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (&str, f64)> {
+        self.index.iter().map(|(score, member)| (member.as_str(), score.0))
+    }
+
+    /// 从(score, member)之后按照排名升序遍历，after为None时从头开始
+    pub fn iter_after(&self, after: Option<(f64, &str)>) -> impl Iterator<Item = (&str, f64)> {
+        let start = match after {
+            Some((score, member)) => {
+                Bound::Excluded((Score(normalize(score)), String::from(member)))
+            }
+            None => Bound::Unbounded,
+        };
+        self.index
+            .range((start, Bound::Unbounded))
+            .map(|(score, member)| (member.as_str(), score.0))
+    }
+
+    /// 分数在区间内的成员，按照排名升序
+    pub fn range_by_score(
+        &self,
+        min: ScoreBound,
+        max: ScoreBound,
+    ) -> impl DoubleEndedIterator<Item = (&str, f64)> {
+        // 分数相同的成员中空字符串排在最前，(score, "")即为该分数的下界
+        let start = if min.exclusive {
+            next_score(min.value)
+        } else {
+            Some(min.value)
+        };
+        let end = if max.exclusive {
+            Some(max.value)
+        } else {
+            next_score(max.value)
+        };
+        let range = match (start, end) {
+            (Some(start), Some(end)) if start < end => Some((
+                Bound::Included((Score(start), String::new())),
+                Bound::Excluded((Score(end), String::new())),
+            )),
+            (Some(start), None) => Some((
+                Bound::Included((Score(start), String::new())),
+                Bound::Unbounded,
+            )),
+            _ => None,
+        };
+        range
+            .map(|range| self.index.range(range))
+            .into_iter()
+            .flatten()
+            .map(|(score, member)| (member.as_str(), score.0))
+    }
+
+    /// 成员在字典序区间内的成员，按照排名升序
+    pub fn range_by_lex(
+        &self,
+        min: LexBound,
+        max: LexBound,
+    ) -> impl DoubleEndedIterator<Item = (&str, f64)> {
+        self.iter()
+            .filter(move |(member, _)| min.accept_min(member) && max.accept_max(member))
+    }
+}
+
+/// 分数区间边界，支持 -inf / +inf / (1.5 开区间 / 1.5 闭区间
+#[derive(Clone, Debug)]
+pub struct ScoreBound {
+    value: f64,
+    exclusive: bool,
+}
+
+impl ScoreBound {
+    pub fn parse(bound: &str) -> anyhow::Result<Self> {
+        let (exclusive, raw) = match bound.strip_prefix('(') {
+            Some(raw) => (true, raw),
+            None => (false, bound),
+        };
+        // f64的parse支持inf/+inf/-inf
+        let value: f64 = raw
+            .parse()
+            .map_err(|_| anyhow!("Invalid score bound {}", bound))?;
+        if value.is_nan() {
+            return Err(anyhow!("Invalid score bound {}", bound));
+        }
+        Ok(ScoreBound {
+            value: normalize(value),
+            exclusive,
+        })
+    }
+}
+
+/// 字典序区间边界，支持 - / + / [a 闭区间 / (a 开区间
+#[derive(Clone, Debug)]
+pub enum LexBound {
+    Min,
+    Max,
+    Included(String),
+    Excluded(String),
+}
+
+impl LexBound {
+    pub fn parse(bound: &str) -> anyhow::Result<Self> {
+        if bound == "-" {
+            return Ok(LexBound::Min);
+        }
+        if bound == "+" {
+            return Ok(LexBound::Max);
+        }
+        if let Some(v) = bound.strip_prefix('[') {
+            return Ok(LexBound::Included(String::from(v)));
+        }
+        if let Some(v) = bound.strip_prefix('(') {
+            return Ok(LexBound::Excluded(String::from(v)));
+        }
+        Err(anyhow!("Invalid lex bound {}, must start with ( or [", bound))
+    }
+
+    pub fn accept_min(&self, member: &str) -> bool {
+        match self {
+            LexBound::Min => true,
+            LexBound::Max => false,
+            LexBound::Included(v) => member >= v.as_str(),
+            LexBound::Excluded(v) => member > v.as_str(),
+        }
+    }
+
+    pub fn accept_max(&self, member: &str) -> bool {
+        match self {
+            LexBound::Min => false,
+            LexBound::Max => true,
+            LexBound::Included(v) => member <= v.as_str(),
+            LexBound::Excluded(v) => member < v.as_str(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{LexBound, ScoreBound, SortedSet};
+
+    fn leaderboard() -> SortedSet {
+        let mut zset = SortedSet::new();
+        zset.insert(String::from("alice"), 30.0);
+        zset.insert(String::from("bob"), 10.0);
+        zset.insert(String::from("carol"), 20.0);
+        zset.insert(String::from("dave"), 20.0);
+        zset
+    }
+
+    #[test]
+    fn order_and_rank_test() {
+        let mut zset = leaderboard();
+        let members: Vec<&str> = zset.iter().map(|(m, _)| m).collect();
+        assert_eq!(members, vec!["bob", "carol", "dave", "alice"]);
+        assert_eq!(zset.rank("dave"), Some(2));
+
+        // 更新分数后排序随之变化
+        assert!(!zset.insert(String::from("bob"), 40.0));
+        assert_eq!(zset.rank("bob"), Some(3));
+        assert!(zset.remove("alice"));
+        assert_eq!(zset.len(), 3);
+        assert_eq!(zset.rank("alice"), None);
+    }
+
+    #[test]
+    fn range_by_score_test() {
+        let zset = leaderboard();
+        let min = ScoreBound::parse("(10").unwrap();
+        let max = ScoreBound::parse("+inf").unwrap();
+        let members: Vec<&str> = zset.range_by_score(min, max).map(|(m, _)| m).collect();
+        assert_eq!(members, vec!["carol", "dave", "alice"]);
+
+        let min = ScoreBound::parse("-inf").unwrap();
+        let max = ScoreBound::parse("20").unwrap();
+        assert_eq!(zset.range_by_score(min, max).count(), 3);
+
+        let min = ScoreBound::parse("+inf").unwrap();
+        let max = ScoreBound::parse("-inf").unwrap();
+        assert_eq!(zset.range_by_score(min, max).count(), 0);
+        assert!(ScoreBound::parse("abc").is_err());
+
+        let min = ScoreBound::parse("(20").unwrap();
+        let max = ScoreBound::parse("(20").unwrap();
+        assert_eq!(zset.range_by_score(min, max).count(), 0);
+        let min = ScoreBound::parse("20").unwrap();
+        let max = ScoreBound::parse("20").unwrap();
+        let members: Vec<&str> = zset.range_by_score(min, max).map(|(m, _)| m).collect();
+        assert_eq!(members, vec!["carol", "dave"]);
+    }
+
+    #[test]
+    fn negative_zero_score_test() {
+        let mut zset = SortedSet::new();
+        zset.insert(String::from("a"), -0.0);
+        zset.insert(String::from("b"), 0.0);
+        zset.insert(String::from("c"), f64::INFINITY);
+        let members: Vec<&str> = zset.iter().map(|(m, _)| m).collect();
+        assert_eq!(members, vec!["a", "b", "c"]);
+        assert!(zset.score("a").unwrap().is_sign_positive());
+
+        let min = ScoreBound::parse("-0").unwrap();
+        let max = ScoreBound::parse("0").unwrap();
+        assert_eq!(zset.range_by_score(min, max).count(), 2);
+        let min = ScoreBound::parse("(0").unwrap();
+        let max = ScoreBound::parse("+inf").unwrap();
+        let members: Vec<&str> = zset.range_by_score(min, max).map(|(m, _)| m).collect();
+        assert_eq!(members, vec!["c"]);
+        let min = ScoreBound::parse("(+inf").unwrap();
+        let max = ScoreBound::parse("+inf").unwrap();
+        assert_eq!(zset.range_by_score(min, max).count(), 0);
+    }
+
+    #[test]
+    fn range_by_lex_test() {
+        let zset = leaderboard();
+        let min = LexBound::parse("[b").unwrap();
+        let max = LexBound::parse("(d").unwrap();
+        let members: Vec<&str> = zset.range_by_lex(min, max).map(|(m, _)| m).collect();
+        assert_eq!(members, vec!["bob", "carol"]);
+    }
+}
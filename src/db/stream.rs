@@ -0,0 +1,401 @@
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::ops::Bound;
+
+use anyhow::anyhow;
+
+use super::dbvalue::DBValue;
+
+/// 流消息ID，由毫秒时间戳和序号组成，按照时间戳和序号排序
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct StreamId {
+    pub ms: u64,
+    pub seq: u64,
+}
+
+impl StreamId {
+    pub const MIN: StreamId = StreamId { ms: 0, seq: 0 };
+    pub const MAX: StreamId = StreamId {
+        ms: u64::MAX,
+        seq: u64::MAX,
+    };
+
+    pub fn new(ms: u64, seq: u64) -> Self {
+        StreamId { ms, seq }
+    }
+
+    /// 解析 ms-seq 格式的ID，省略序号时使用default_seq
+    fn parse_with(id: &str, default_seq: u64) -> anyhow::Result<Self> {
+        let invalid = || anyhow!("Invalid stream ID specified as stream command argument");
+        let (ms, seq) = match id.split_once('-') {
+            Some((ms, seq)) => (ms, seq.parse().map_err(|_| invalid())?),
+            None => (id, default_seq),
+        };
+        Ok(StreamId {
+            ms: ms.parse().map_err(|_| invalid())?,
+            seq,
+        })
+    }
+
+    pub fn parse(id: &str) -> anyhow::Result<Self> {
+        Self::parse_with(id, 0)
+    }
+
+    /// 区间起点，- 表示最小ID
+    pub fn parse_start(id: &str) -> anyhow::Result<Self> {
+        match id {
+            "-" => Ok(StreamId::MIN),
+            _ => Self::parse_with(id, 0),
+        }
+    }
+
+    /// 区间终点，+ 表示最大ID，省略序号时包含该毫秒内的所有消息
+    pub fn parse_end(id: &str) -> anyhow::Result<Self> {
+        match id {
+            "+" => Ok(StreamId::MAX),
+            _ => Self::parse_with(id, u64::MAX),
+        }
+    }
+}
+
+impl Display for StreamId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}-{}", self.ms, self.seq)
+    }
+}
+
+/// 流消息的字段，保持写入顺序
+pub type StreamFields = Vec<(String, DBValue)>;
+
+/// 已经投递给消费者但尚未确认的消息
+#[derive(Clone, Debug, PartialEq)]
+pub struct PendingEntry {
+    pub consumer: String,
+    // 最近一次投递的时间戳(毫秒)
+    pub delivered_at: u128,
+    // 投递次数
+    pub deliveries: u64,
+}
+
+/// 消费者组，记录最后投递的消息ID和待确认的消息
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ConsumerGroup {
+    pub last_delivered: StreamId,
+    pub pending: BTreeMap<StreamId, PendingEntry>,
+}
+
+/// 只追加的流，消息按照ID升序排列
+#[derive(Clone, Default, PartialEq)]
+pub struct Stream {
+    entries: BTreeMap<StreamId, StreamFields>,
+    // 最后写入的消息ID，消息被裁剪后依然保留，保证新的ID单调递增
+    last_id: StreamId,
+    groups: BTreeMap<String, ConsumerGroup>,
+}
+
+impl Stream {
+    pub fn new() -> Self {
+        Stream::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn last_id(&self) -> StreamId {
+        self.last_id
+    }
+
+    pub fn set_last_id(&mut self, id: StreamId) {
+        self.last_id = id;
+    }
+
+    /// 根据命令时间戳和raft日志索引生成新的消息ID，保证大于最后写入的ID。
+    /// 时间戳来自提案节点，回拨时沿用最后的时间戳；序号使用raft日志索引，各副本一致。
+    /// 序号用尽时进位到下一毫秒
+    pub fn next_id(&self, ts: u128, index: u64) -> anyhow::Result<StreamId> {
+        let ms = (ts as u64).max(self.last_id.ms);
+        if ms > self.last_id.ms {
+            return Ok(StreamId::new(ms, index));
+        }
+        match self.last_id.seq.checked_add(1) {
+            Some(seq) => Ok(StreamId::new(ms, index.max(seq))),
+            None => match ms.checked_add(1) {
+                Some(ms) => Ok(StreamId::new(ms, 0)),
+                None => Err(anyhow!(
+                    "The stream has exhausted the last possible ID, unable to add more items"
+                )),
+            },
+        }
+    }
+
+    /// 追加消息，ID必须大于最后写入的ID
+    pub fn add(&mut self, id: StreamId, fields: StreamFields) -> anyhow::Result<()> {
+        if id == StreamId::MIN {
+            return Err(anyhow!("The ID specified in XADD must be greater than 0-0"));
+        }
+        if id <= self.last_id {
+            return Err(anyhow!(
+                "The ID specified in XADD is equal or smaller than the target stream top item"
+            ));
+        }
+        self.entries.insert(id, fields);
+        self.last_id = id;
+        Ok(())
+    }
+
+    pub fn get(&self, id: &StreamId) -> Option<&StreamFields> {
+        self.entries.get(id)
+    }
+
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (&StreamId, &StreamFields)> {
+        self.entries.iter()
+    }
+
+    /// 闭区间内的消息
+    pub fn range(
+        &self,
+        start: StreamId,
+        end: StreamId,
+    ) -> impl DoubleEndedIterator<Item = (&StreamId, &StreamFields)> {
+        let range = if start <= end {
+            Some(self.entries.range(start..=end))
+        } else {
+            None
+        };
+        range.into_iter().flatten()
+    }
+
+    /// ID大于after的消息
+    pub fn after(&self, after: StreamId) -> impl Iterator<Item = (&StreamId, &StreamFields)> {
+        self.entries
+            .range((Bound::Excluded(after), Bound::Unbounded))
+    }
+
+    /// 裁剪最早的消息，只保留max_len条，返回删除的数量
+    pub fn trim_len(&mut self, max_len: usize) -> usize {
+        let mut removed = 0;
+        while self.entries.len() > max_len {
+            self.entries.pop_first();
+            removed += 1;
+        }
+        removed
+    }
+
+    /// 删除ID小于min_id的消息，返回删除的数量
+    pub fn trim_before(&mut self, min_id: StreamId) -> usize {
+        let remaining = self.entries.split_off(&min_id);
+        let removed = self.entries.len();
+        self.entries = remaining;
+        removed
+    }
+
+    pub fn groups(&self) -> impl Iterator<Item = (&String, &ConsumerGroup)> {
+        self.groups.iter()
+    }
+
+    pub fn group(&self, name: &str) -> anyhow::Result<&ConsumerGroup> {
+        self.groups
+            .get(name)
+            .ok_or_else(|| anyhow!("No such consumer group {}", name))
+    }
+
+    fn group_mut(&mut self, name: &str) -> anyhow::Result<&mut ConsumerGroup> {
+        self.groups
+            .get_mut(name)
+            .ok_or_else(|| anyhow!("No such consumer group {}", name))
+    }
+
+    /// 创建消费者组，组已经存在时返回false
+    pub fn create_group(&mut self, name: &str, last_delivered: StreamId) -> bool {
+        if self.groups.contains_key(name) {
+            return false;
+        }
+        let group = ConsumerGroup {
+            last_delivered,
+            ..Default::default()
+        };
+        self.groups.insert(String::from(name), group);
+        true
+    }
+
+    pub fn insert_group(&mut self, name: String, group: ConsumerGroup) {
+        self.groups.insert(name, group);
+    }
+
+    /// 投递最后投递ID之后的新消息给消费者，noack为false时加入待确认列表
+    pub fn read_group(
+        &mut self,
+        group: &str,
+        consumer: &str,
+        count: usize,
+        now: u128,
+        noack: bool,
+    ) -> anyhow::Result<Vec<(StreamId, StreamFields)>> {
+        let last_delivered = self.group(group)?.last_delivered;
+        let entries: Vec<(StreamId, StreamFields)> = self
+            .after(last_delivered)
+            .take(count)
+            .map(|(id, fields)| (*id, fields.clone()))
+            .collect();
+        let group = self.group_mut(group)?;
+        for (id, _) in entries.iter() {
+            group.last_delivered = *id;
+            if !noack {
+                let pending = PendingEntry {
+                    consumer: String::from(consumer),
+                    delivered_at: now,
+                    deliveries: 1,
+                };
+                group.pending.insert(*id, pending);
+            }
+        }
+        Ok(entries)
+    }
+
+    /// 消费者ID大于after的待确认消息，用于消费者重启后重新处理，已被裁剪的消息跳过
+    pub fn read_pending(
+        &self,
+        group: &str,
+        consumer: &str,
+        after: StreamId,
+        count: usize,
+    ) -> anyhow::Result<Vec<(StreamId, StreamFields)>> {
+        let group = self.group(group)?;
+        Ok(group
+            .pending
+            .range((Bound::Excluded(after), Bound::Unbounded))
+            .filter(|(_, pending)| pending.consumer == consumer)
+            .filter_map(|(id, _)| self.entries.get(id).map(|fields| (*id, fields.clone())))
+            .take(count)
+            .collect())
+    }
+
+    /// 确认消息，返回确认成功的数量
+    pub fn ack(&mut self, group: &str, ids: &[StreamId]) -> anyhow::Result<usize> {
+        let group = self.group_mut(group)?;
+        Ok(ids
+            .iter()
+            .filter(|id| group.pending.remove(id).is_some())
+            .count())
+    }
+
+    /// 将空闲时间不小于min_idle_ms的待确认消息转移给消费者，返回转移的消息
+    pub fn claim(
+        &mut self,
+        group: &str,
+        consumer: &str,
+        min_idle_ms: u64,
+        ids: &[StreamId],
+        now: u128,
+    ) -> anyhow::Result<Vec<(StreamId, StreamFields)>> {
+        let mut claimed = Vec::new();
+        let Stream {
+            entries, groups, ..
+        } = self;
+        let group = groups
+            .get_mut(group)
+            .ok_or_else(|| anyhow!("No such consumer group {}", group))?;
+        for id in ids {
+            let Some(pending) = group.pending.get_mut(id) else {
+                continue;
+            };
+            if now.saturating_sub(pending.delivered_at) < min_idle_ms as u128 {
+                continue;
+            }
+            // 消息已被裁剪，从待确认列表中删除
+            let Some(fields) = entries.get(id) else {
+                group.pending.remove(id);
+                continue;
+            };
+            pending.consumer = String::from(consumer);
+            pending.delivered_at = now;
+            pending.deliveries += 1;
+            claimed.push((*id, fields.clone()));
+        }
+        Ok(claimed)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Stream, StreamId};
+    use crate::db::dbvalue::DBValue;
+
+    fn fields(value: &str) -> Vec<(String, DBValue)> {
+        vec![(String::from("event"), DBValue::String(String::from(value)))]
+    }
+
+    #[test]
+    fn next_id_test() {
+        let mut stream = Stream::new();
+        let id = stream.next_id(1_000, 5).unwrap();
+        assert_eq!(id, StreamId::new(1_000, 5));
+        stream.add(id, fields("a")).unwrap();
+        // 时间戳回拨时沿用最后的时间戳
+        assert_eq!(stream.next_id(900, 6).unwrap(), StreamId::new(1_000, 6));
+        assert_eq!(stream.next_id(1_000, 0).unwrap(), StreamId::new(1_000, 6));
+        // 序号用尽时进位，ID用尽时报错
+        stream.add(StreamId::new(1_000, u64::MAX), fields("b")).unwrap();
+        assert_eq!(stream.next_id(900, 7).unwrap(), StreamId::new(1_001, 0));
+        stream.add(StreamId::MAX, fields("c")).unwrap();
+        assert!(stream.next_id(900, 8).is_err());
+        assert!(stream.add(StreamId::new(999, 1), fields("b")).is_err());
+        assert_eq!(StreamId::parse_end("1000").unwrap().seq, u64::MAX);
+        assert!(StreamId::parse("abc").is_err());
+    }
+
+    #[test]
+    fn range_and_trim_test() {
+        let mut stream = Stream::new();
+        for ms in 1..=5 {
+            stream.add(StreamId::new(ms, 0), fields("e")).unwrap();
+        }
+        let ids: Vec<u64> = stream
+            .range(StreamId::new(2, 0), StreamId::new(4, 0))
+            .map(|(id, _)| id.ms)
+            .collect();
+        assert_eq!(ids, vec![2, 3, 4]);
+        assert_eq!(stream.range(StreamId::MAX, StreamId::MIN).count(), 0);
+        assert_eq!(stream.trim_before(StreamId::new(3, 0)), 2);
+        assert_eq!(stream.trim_len(1), 2);
+        assert_eq!(stream.last_id(), StreamId::new(5, 0));
+    }
+
+    #[test]
+    fn consumer_group_test() {
+        let mut stream = Stream::new();
+        for ms in 1..=3 {
+            stream.add(StreamId::new(ms, 0), fields("job")).unwrap();
+        }
+        assert!(stream.create_group("workers", StreamId::MIN));
+        assert!(!stream.create_group("workers", StreamId::MIN));
+        let read = stream.read_group("workers", "w1", 2, 100, false).unwrap();
+        assert_eq!(read.len(), 2);
+        let read = stream.read_group("workers", "w2", 10, 100, false).unwrap();
+        assert_eq!(read[0].0, StreamId::new(3, 0));
+
+        assert_eq!(stream.ack("workers", &[StreamId::new(1, 0)]).unwrap(), 1);
+        let pending = stream
+            .read_pending("workers", "w1", StreamId::MIN, 10)
+            .unwrap();
+        assert_eq!(pending.len(), 1);
+        // 空闲时间不足时不能转移
+        let ids = [StreamId::new(2, 0)];
+        assert!(stream
+            .claim("workers", "w2", 50, &ids, 120)
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            stream.claim("workers", "w2", 50, &ids, 200).unwrap().len(),
+            1
+        );
+        let group = stream.group("workers").unwrap();
+        assert_eq!(group.pending[&ids[0]].deliveries, 2);
+        assert!(stream.read_group("missing", "w1", 1, 0, false).is_err());
+    }
+}
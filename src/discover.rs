@@ -1,4 +1,4 @@
-use crate::postman::{AsAny, LetterMessage};
+use crate::postman::LetterMessage;
 use crate::runtime::Runtime;
 use crate::{
     config::Config,
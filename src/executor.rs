@@ -0,0 +1,493 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context as TaskContext, Poll};
+
+use ahash::AHashMap;
+use anyhow::anyhow;
+use async_trait::async_trait;
+use log::{error, info};
+use tokio::sync::{mpsc, oneshot, RwLock};
+use tokio::time::interval;
+use tokio::{select, task::JoinHandle};
+use tokio_context::context::{Context, RefContext};
+
+use crate::command::Command;
+use crate::connection::manager::ConnectionManager;
+use crate::db::dbvalue::DBValue;
+use crate::node::NodeManager;
+use crate::postman::{Channel, LetterMessage, Postman};
+use crate::proto::{ExecuteTaskCmd, TaskResultCmd};
+use crate::runtime::Runtime;
+use crate::until;
+
+/// 可以提交到节点上执行的任务，所有节点必须以相同的名称注册相同的任务
+#[async_trait]
+pub trait Task: Send + Sync {
+    async fn run(&self, args: Vec<DBValue>) -> anyhow::Result<DBValue>;
+}
+
+#[async_trait]
+impl<F> Task for F
+where
+    F: Fn(Vec<DBValue>) -> anyhow::Result<DBValue> + Send + Sync,
+{
+    async fn run(&self, args: Vec<DBValue>) -> anyhow::Result<DBValue> {
+        self(args)
+    }
+}
+
+/// 任务的执行目标
+#[derive(Clone, Debug, PartialEq)]
+pub enum TaskTarget {
+    // 本节点
+    Local,
+    // 指定ID的节点
+    Node(u64),
+    // key所属的节点
+    KeyOwner(String),
+    // 所有节点，包括本节点
+    All,
+}
+
+// 已发送到其它节点、等待结果的任务：任务ID -> (执行节点, 结果)
+type PendingTasks = Arc<Mutex<AHashMap<u64, (u64, oneshot::Sender<anyhow::Result<DBValue>>)>>>;
+
+/// 任务执行结果，执行节点离开集群时以错误完成，调用方可以设置超时，
+/// 超时或者丢弃时不再等待远程结果
+pub struct TaskFuture {
+    // 执行任务的节点
+    pub node: u64,
+    rx: oneshot::Receiver<anyhow::Result<DBValue>>,
+    // 远程任务的ID，丢弃时从等待列表中删除
+    remote: Option<(u64, PendingTasks)>,
+}
+
+impl Drop for TaskFuture {
+    fn drop(&mut self) {
+        if let Some((id, pending)) = self.remote.take() {
+            if let Ok(mut pending) = pending.lock() {
+                pending.remove(&id);
+            }
+        }
+    }
+}
+
+impl Future for TaskFuture {
+    type Output = anyhow::Result<DBValue>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.rx).poll(cx).map(|result| {
+            result.unwrap_or_else(|_| Err(anyhow!("Task was dropped before completion")))
+        })
+    }
+}
+
+/// 根据key选择所属节点，所有节点使用相同的成员列表时选择结果一致
+pub fn key_owner(key: &str, members: &[u64]) -> Option<u64> {
+    members
+        .iter()
+        .copied()
+        .max_by_key(|node| (until::stable_hash(&format!("{}:{}", key, node)), *node))
+}
+
+/// 本节点的任务执行服务：执行本地任务，并通过集群连接把任务发送到其它节点
+pub struct ExecutorService {
+    node_id: u64,
+    next_id: AtomicU64,
+    tasks: RwLock<AHashMap<String, Arc<dyn Task>>>,
+    // 已发送到其它节点、等待结果的任务
+    pending: PendingTasks,
+}
+
+impl ExecutorService {
+    pub fn new(node_id: u64) -> Self {
+        let mut tasks: AHashMap<String, Arc<dyn Task>> = AHashMap::new();
+        tasks.insert(String::from("echo"), Arc::new(echo));
+        ExecutorService {
+            node_id,
+            next_id: AtomicU64::new(1),
+            tasks: RwLock::new(tasks),
+            pending: Arc::new(Mutex::new(AHashMap::new())),
+        }
+    }
+
+    pub fn node_id(&self) -> u64 {
+        self.node_id
+    }
+
+    /// 注册任务，同名任务会被替换
+    pub async fn register(&self, name: &str, task: impl Task + 'static) {
+        self.tasks
+            .write()
+            .await
+            .insert(String::from(name), Arc::new(task));
+    }
+
+    /// 在本节点执行任务
+    pub async fn run_local(&self, name: &str, args: Vec<DBValue>) -> anyhow::Result<DBValue> {
+        let task = self.task(name).await?;
+        task.run(args).await
+    }
+
+    async fn task(&self, name: &str) -> anyhow::Result<Arc<dyn Task>> {
+        self.tasks
+            .read()
+            .await
+            .get(name)
+            .cloned()
+            .ok_or_else(|| anyhow!("No such task {}", name))
+    }
+
+    /// 在本节点后台执行任务
+    pub async fn spawn_local(&self, name: &str, args: Vec<DBValue>) -> TaskFuture {
+        let (tx, rx) = oneshot::channel();
+        match self.task(name).await {
+            Ok(task) => {
+                tokio::spawn(async move {
+                    let _ = tx.send(task.run(args).await);
+                });
+            }
+            Err(err) => {
+                let _ = tx.send(Err(err));
+            }
+        }
+        TaskFuture {
+            node: self.node_id,
+            rx,
+            remote: None,
+        }
+    }
+
+    /// 提交任务，返回每个目标节点的执行结果
+    pub async fn submit(
+        &self,
+        postman: &Postman,
+        target: TaskTarget,
+        name: &str,
+        args: Vec<DBValue>,
+    ) -> anyhow::Result<Vec<TaskFuture>> {
+        if target == TaskTarget::Local || target == TaskTarget::Node(self.node_id) {
+            return Ok(vec![self.spawn_local(name, args).await]);
+        }
+        let (tx, mut rx) = mpsc::channel(1);
+        let dispatch = DispatchTask {
+            target,
+            task: String::from(name),
+            args,
+            reply: tx,
+        };
+        if !postman.send(Box::new(dispatch)).await? {
+            return Err(anyhow!("Executor service is not started"));
+        }
+        rx.recv()
+            .await
+            .unwrap_or_else(|| Err(anyhow!("Executor service stopped")))
+    }
+
+    // 登记等待远程结果的任务
+    fn pending(&self, node: u64) -> (u64, TaskFuture) {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (tx, rx) = oneshot::channel();
+        if let Ok(mut pending) = self.pending.lock() {
+            pending.insert(id, (node, tx));
+        }
+        let remote = Some((id, self.pending.clone()));
+        (id, TaskFuture { node, rx, remote })
+    }
+
+    /// 完成等待远程结果的任务，任务不存在时返回false
+    pub async fn complete(&self, id: u64, result: anyhow::Result<DBValue>) -> bool {
+        let sender = match self.pending.lock() {
+            Ok(mut pending) => pending.remove(&id),
+            Err(_) => None,
+        };
+        match sender {
+            Some((_, tx)) => tx.send(result).is_ok(),
+            None => false,
+        }
+    }
+
+    /// 以错误完成发送到已离开集群的节点的任务，返回完成的任务数量
+    pub fn fail_departed(&self, members: &[u64]) -> usize {
+        let departed: Vec<(u64, oneshot::Sender<anyhow::Result<DBValue>>)> =
+            match self.pending.lock() {
+                Ok(mut pending) => {
+                    let ids: Vec<u64> = pending
+                        .iter()
+                        .filter(|(_, (node, _))| !members.contains(node))
+                        .map(|(id, _)| *id)
+                        .collect();
+                    ids.iter().filter_map(|id| pending.remove(id)).collect()
+                }
+                Err(_) => Vec::new(),
+            };
+        let count = departed.len();
+        for (node, tx) in departed {
+            let _ = tx.send(Err(anyhow!(
+                "Node {} left the cluster before task completion",
+                node
+            )));
+        }
+        count
+    }
+}
+
+/// 返回参数列表，用于检查节点是否可以执行任务
+fn echo(args: Vec<DBValue>) -> anyhow::Result<DBValue> {
+    Ok(DBValue::List(args.into()))
+}
+
+/// 本节点提交的任务，由执行服务选择目标节点后发送
+pub struct DispatchTask {
+    pub target: TaskTarget,
+    pub task: String,
+    pub args: Vec<DBValue>,
+    pub reply: mpsc::Sender<anyhow::Result<Vec<TaskFuture>>>,
+}
+
+impl LetterMessage for DispatchTask {
+    fn channel(&self) -> Channel {
+        Channel::Executor
+    }
+}
+
+/// 其它节点提交到本节点的任务
+pub struct RemoteTask(pub ExecuteTaskCmd);
+
+impl LetterMessage for RemoteTask {
+    fn channel(&self) -> Channel {
+        Channel::Executor
+    }
+}
+
+/// 启动任务执行服务，负责任务在节点之间的发送和结果的返回
+pub fn start_executor(
+    app: Arc<Runtime>,
+    ctx: RefContext,
+    conn_manager: ConnectionManager,
+    mut recv: mpsc::Receiver<Box<dyn LetterMessage>>,
+) -> anyhow::Result<JoinHandle<()>> {
+    let handler = tokio::spawn(async move {
+        info!("Executor thread startup");
+        let (mut done_ctx, _handler) = Context::with_parent(&ctx, None);
+        // 与节点发现清理离线节点的周期一致
+        let mut check_interval = interval(app.cfg.disc_multicast_ttl_check_interval);
+        loop {
+            select! {
+                _ = done_ctx.done() => {
+                    info!("Executor loop stop");
+                    break;
+                },
+                _ = check_interval.tick() => {
+                    let members = other_nodes(&conn_manager).await;
+                    let failed = app.executor.fail_departed(&members);
+                    if failed > 0 {
+                        info!("Fail {} tasks sent to departed nodes", failed);
+                    }
+                },
+                Some(letter) = recv.recv() => {
+                    let letter = letter.as_any();
+                    if let Some(dispatch) = letter.downcast_ref::<DispatchTask>() {
+                        let result = dispatch_task(&app, &conn_manager, dispatch).await;
+                        if dispatch.reply.send(result).await.is_err() {
+                            error!("Submitter of task {} is gone", &dispatch.task);
+                        }
+                    } else if let Some(remote) = letter.downcast_ref::<RemoteTask>() {
+                        // 任务可能执行较长时间，在独立的线程中执行
+                        let app = app.clone();
+                        let conn_manager = conn_manager.clone();
+                        let cmd = remote.0.clone();
+                        tokio::spawn(async move {
+                            run_remote_task(app.as_ref(), &conn_manager, cmd).await;
+                        });
+                    }
+                }
+            }
+        }
+    });
+    Ok(handler)
+}
+
+async fn dispatch_task(
+    app: &Arc<Runtime>,
+    conn_manager: &ConnectionManager,
+    dispatch: &DispatchTask,
+) -> anyhow::Result<Vec<TaskFuture>> {
+    let executor = &app.executor;
+    let others = other_nodes(conn_manager).await;
+    let nodes = match &dispatch.target {
+        TaskTarget::Local => vec![executor.node_id],
+        TaskTarget::Node(node) => vec![*node],
+        TaskTarget::KeyOwner(key) => {
+            let mut members = others.clone();
+            members.push(executor.node_id);
+            key_owner(key, &members).into_iter().collect()
+        }
+        TaskTarget::All => {
+            let mut nodes = vec![executor.node_id];
+            nodes.extend(others.iter().copied());
+            nodes
+        }
+    };
+    let mut futures = Vec::with_capacity(nodes.len());
+    for node in nodes {
+        if node == executor.node_id {
+            futures.push(
+                executor
+                    .spawn_local(&dispatch.task, dispatch.args.clone())
+                    .await,
+            );
+            continue;
+        }
+        let (id, future) = executor.pending(node);
+        let cmd = ExecuteTaskCmd {
+            origin: executor.node_id,
+            id,
+            task: dispatch.task.clone(),
+            args: dispatch
+                .args
+                .iter()
+                .cloned()
+                .map(|arg| arg.into())
+                .collect(),
+        };
+        // 节点写入可能较慢，在独立的任务中发送，不阻塞其它任务的提交
+        let app = app.clone();
+        let conn_manager = conn_manager.clone();
+        tokio::spawn(async move {
+            let command = Command::new(Box::new(cmd), None);
+            if let Err(err) = send_to_node(&conn_manager, node, command).await {
+                app.executor.complete(id, Err(err)).await;
+            }
+        });
+        futures.push(future);
+    }
+    Ok(futures)
+}
+
+async fn other_nodes(conn_manager: &ConnectionManager) -> Vec<u64> {
+    conn_manager
+        .get_node_manager_ref()
+        .get_other_nodes()
+        .await
+        .iter()
+        .map(|node| node.id)
+        .collect()
+}
+
+// 执行其它节点提交的任务，并把结果发回提交任务的节点
+async fn run_remote_task(app: &Runtime, conn_manager: &ConnectionManager, cmd: ExecuteTaskCmd) {
+    let args = cmd.args.into_iter().map(DBValue::from).collect();
+    let result = app.executor.run_local(&cmd.task, args).await;
+    let reply = match result {
+        Ok(value) => TaskResultCmd {
+            id: cmd.id,
+            value: Some(value.into()),
+            error: String::new(),
+        },
+        Err(err) => TaskResultCmd {
+            id: cmd.id,
+            value: None,
+            error: format!("{}", err),
+        },
+    };
+    let command = Command::new(Box::new(reply), None);
+    if let Err(err) = send_to_node(conn_manager, cmd.origin, command).await {
+        error!(
+            "Reply task {} to node {} error: {:?}",
+            &cmd.task, cmd.origin, err
+        );
+    }
+}
+
+async fn send_to_node(
+    conn_manager: &ConnectionManager,
+    node: u64,
+    command: Command,
+) -> anyhow::Result<()> {
+    let conn = conn_manager
+        .get_by_id(&node)
+        .await?
+        .ok_or_else(|| anyhow!("No such node {}", node))?;
+    let mut frames = command.encode_to_frames()?;
+    conn.write_frame(&mut frames[..]).await
+}
+
+#[cfg(test)]
+mod test {
+    use super::{key_owner, ExecutorService, TaskTarget};
+    use crate::db::dbvalue::DBValue;
+    use crate::postman::Postman;
+    use anyhow::anyhow;
+    use std::time::Duration;
+
+    #[test]
+    fn key_owner_test() {
+        let members = [3, 1, 2];
+        let owner = key_owner("user:1", &members).unwrap();
+        assert_eq!(key_owner("user:1", &[1, 2, 3]), Some(owner));
+        // 其它节点离开后，属于剩余节点的key不变
+        let rest: Vec<u64> = members.iter().copied().filter(|n| *n != owner).collect();
+        let other = key_owner("user:1", &rest).unwrap();
+        assert_ne!(other, owner);
+        assert_eq!(key_owner("user:1", &[]), None);
+    }
+
+    #[tokio::test]
+    async fn local_task_test() {
+        let executor = ExecutorService::new(7);
+        executor
+            .register("sum", |args: Vec<DBValue>| {
+                let mut sum = 0;
+                for arg in args {
+                    match arg {
+                        DBValue::Int64(v) => sum += v,
+                        v => return Err(anyhow!("Can not sum {}", v)),
+                    }
+                }
+                Ok(DBValue::Int64(sum))
+            })
+            .await;
+        let postman = Postman::new();
+        let args = vec![DBValue::Int64(1), DBValue::Int64(2)];
+        let mut futures = executor
+            .submit(&postman, TaskTarget::Node(7), "sum", args)
+            .await
+            .unwrap();
+        let future = futures.pop().unwrap();
+        assert_eq!(future.node, 7);
+        assert!(future.await.unwrap() == DBValue::Int64(3));
+        let failed = executor
+            .spawn_local("sum", vec![DBValue::Boolean(true)])
+            .await;
+        assert!(failed.await.is_err());
+        assert!(executor.spawn_local("missing", vec![]).await.await.is_err());
+        // 执行服务未启动时不能提交到其它节点
+        assert!(executor
+            .submit(&postman, TaskTarget::All, "sum", vec![])
+            .await
+            .is_err());
+
+        // 远程结果通过任务ID完成
+        let (id, future) = executor.pending(8);
+        assert!(executor.complete(id, Ok(DBValue::Int64(5))).await);
+        assert!(future.await.unwrap() == DBValue::Int64(5));
+        assert!(!executor.complete(id, Ok(DBValue::None)).await);
+
+        // 超时丢弃的任务不再等待结果
+        let (id, future) = executor.pending(8);
+        let timeout = tokio::time::timeout(Duration::from_millis(10), future).await;
+        assert!(timeout.is_err());
+        assert!(!executor.complete(id, Ok(DBValue::None)).await);
+
+        // 执行节点离开集群时任务以错误完成
+        let (_, departed) = executor.pending(8);
+        let (id, alive) = executor.pending(9);
+        assert_eq!(executor.fail_departed(&[9]), 1);
+        assert!(departed.await.is_err());
+        assert!(executor.complete(id, Ok(DBValue::Int64(1))).await);
+        assert!(alive.await.unwrap() == DBValue::Int64(1));
+    }
+}
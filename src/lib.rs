@@ -5,9 +5,12 @@ pub mod config;
 pub mod connection;
 pub mod db;
 pub mod discover;
+pub mod executor;
 pub mod node;
 pub mod postman;
 pub mod proto;
 pub mod protocol;
+pub mod pubsub;
 pub mod runtime;
 pub mod until;
+pub mod watch;
@@ -13,6 +13,10 @@ pub enum Channel {
     RaftProposal,
     /// discover
     Discover,
+    /// 发布订阅消息的集群转发
+    PubSub,
+    /// 任务在节点之间的发送
+    Executor,
 }
 
 pub trait LetterMessage: Send + Sync + Any {
@@ -20,12 +24,10 @@ pub trait LetterMessage: Send + Sync + Any {
     fn channel(&self) -> Channel;
 }
 
-pub trait AsAny {
-    fn as_any(&self) -> &dyn Any;
-}
-
-impl<T: Any> AsAny for T {
-    fn as_any(&self) -> &dyn Any {
+impl dyn LetterMessage {
+    /// 转换为信件本身的Any，用于还原具体的信件类型。
+    /// 不为任意类型实现as_any，否则对Box<dyn LetterMessage>调用得到的是Box自身
+    pub fn as_any(&self) -> &dyn Any {
         self
     }
 }
@@ -70,4 +72,33 @@ impl Postman {
         }
         Ok(false)
     }
+
+    /// 尝试发送消息，通道已满时直接返回错误，用于不能等待接收方的场景
+    pub async fn try_send(&self, message: Box<dyn LetterMessage>) -> anyhow::Result<bool> {
+        if let Some(sender) = self.channels.read().await.get(&message.channel()) {
+            sender.try_send(message)?;
+            return Ok(true);
+        }
+        Ok(false)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Channel, LetterMessage};
+
+    struct Ping;
+
+    impl LetterMessage for Ping {
+        fn channel(&self) -> Channel {
+            Channel::Discover
+        }
+    }
+
+    #[test]
+    fn boxed_letter_downcast_test() {
+        let letter: Box<dyn LetterMessage> = Box::new(Ping);
+        assert!(letter.as_any().is::<Ping>());
+        assert!(letter.as_any().downcast_ref::<Ping>().is_some());
+    }
 }
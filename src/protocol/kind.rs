@@ -6,6 +6,8 @@ pub enum Kind {
     PING,
     PONG,
     CMD,
+    // 服务端主动推送的数据，如订阅的消息
+    PUSH,
     ERROR,
 }
 
@@ -15,6 +17,7 @@ impl From<u8> for Kind {
             0 => Self::PING,
             1 => Self::PONG,
             2 => Self::CMD,
+            3 => Self::PUSH,
             _ => Self::UNKNOWN,
         }
     }
@@ -30,6 +33,7 @@ impl Segment for Kind {
             Self::PING => 0b0,
             Self::PONG => 0b0000_0001,
             Self::CMD => 0b0000_0010,
+            Self::PUSH => 0b0000_0011,
             Self::ERROR => 0b0000_1110,
             Self::UNKNOWN => 0b0000_1111,
         }
@@ -40,6 +44,7 @@ impl Segment for Kind {
             0b0 => Self::PING,
             0b0000_0001 => Self::PONG,
             0b0000_0010 => Self::CMD,
+            0b0000_0011 => Self::PUSH,
             _ => Self::UNKNOWN,
         }
     }
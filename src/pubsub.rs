@@ -0,0 +1,283 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use ahash::{AHashMap, AHashSet};
+use log::{error, info, warn};
+use prost::Message;
+use tokio::sync::mpsc::{self, error::TrySendError};
+use tokio::sync::RwLock;
+use tokio::{select, task::JoinHandle};
+use tokio_context::context::{Context, RefContext};
+
+use crate::cluster;
+use crate::command::Command;
+use crate::connection::manager::ConnectionManager;
+use crate::db::dbvalue::DBValue;
+use crate::postman::{Channel, LetterMessage};
+use crate::proto::push_message::Push;
+use crate::proto::{PubSubMessage, PublishCmd, PushMessage};
+use crate::protocol::frame::{self, Frame};
+use crate::protocol::kind::Kind;
+use crate::until;
+
+// 订阅者：一个订阅了频道的客户端连接
+struct Subscriber {
+    // 推送数据帧到连接的发送器
+    sender: mpsc::Sender<Vec<Frame>>,
+    channels: AHashSet<String>,
+    patterns: AHashSet<String>,
+}
+
+/// 本节点的发布订阅注册表，跨节点的消息通过集群连接转发后在各节点本地投递
+pub struct PubSub {
+    next_id: AtomicU64,
+    subscribers: RwLock<AHashMap<u64, Subscriber>>,
+}
+
+impl PubSub {
+    pub fn new() -> Self {
+        PubSub {
+            next_id: AtomicU64::new(1),
+            subscribers: RwLock::new(AHashMap::new()),
+        }
+    }
+
+    /// 注册订阅者，返回订阅者ID
+    pub async fn register(&self, sender: mpsc::Sender<Vec<Frame>>) -> u64 {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let subscriber = Subscriber {
+            sender,
+            channels: AHashSet::new(),
+            patterns: AHashSet::new(),
+        };
+        self.subscribers.write().await.insert(id, subscriber);
+        id
+    }
+
+    /// 移除订阅者，连接断开时调用
+    pub async fn remove(&self, id: u64) {
+        self.subscribers.write().await.remove(&id);
+    }
+
+    pub async fn subscribe(&self, id: u64, channels: &[String], pattern: bool) {
+        if let Some(subscriber) = self.subscribers.write().await.get_mut(&id) {
+            let target = if pattern {
+                &mut subscriber.patterns
+            } else {
+                &mut subscriber.channels
+            };
+            target.extend(channels.iter().cloned());
+        }
+    }
+
+    /// 取消订阅，channels为空表示取消全部
+    pub async fn unsubscribe(&self, id: u64, channels: &[String], pattern: bool) {
+        if let Some(subscriber) = self.subscribers.write().await.get_mut(&id) {
+            let target = if pattern {
+                &mut subscriber.patterns
+            } else {
+                &mut subscriber.channels
+            };
+            if channels.is_empty() {
+                target.clear();
+            } else {
+                for channel in channels {
+                    target.remove(channel);
+                }
+            }
+        }
+    }
+
+    /// 投递消息给本节点的订阅者，返回接收到消息的订阅者数量
+    pub async fn publish(&self, channel: &str, message: &DBValue) -> usize {
+        let subscribers = self.subscribers.read().await;
+        let mut receivers = 0;
+        for subscriber in subscribers.values() {
+            let mut matched: Vec<&str> = Vec::new();
+            if subscriber.channels.contains(channel) {
+                matched.push("");
+            }
+            for pattern in subscriber.patterns.iter() {
+                if until::glob_match(pattern, channel) {
+                    matched.push(pattern);
+                }
+            }
+            for pattern in matched {
+                let push = Push::Message(PubSubMessage {
+                    channel: String::from(channel),
+                    pattern: String::from(pattern),
+                    message: Some(message.clone().into()),
+                });
+                if send_push(&subscriber.sender, push) {
+                    receivers += 1;
+                }
+            }
+        }
+        receivers
+    }
+}
+
+impl Default for PubSub {
+    fn default() -> Self {
+        PubSub::new()
+    }
+}
+
+/// 构造推送数据帧
+pub fn build_push(push: Push) -> anyhow::Result<Vec<Frame>> {
+    let message = PushMessage { push: Some(push) };
+    frame::build_frames(Kind::PUSH, &message.encode_to_vec())
+}
+
+/// 推送数据到连接，连接处理不过来时丢弃，避免慢订阅者阻塞发布者
+pub fn send_push(sender: &mpsc::Sender<Vec<Frame>>, push: Push) -> bool {
+    let frames = match build_push(push) {
+        Ok(frames) => frames,
+        Err(err) => {
+            error!("Build push frames error: {:?}", err);
+            return false;
+        }
+    };
+    match sender.try_send(frames) {
+        Ok(_) => true,
+        Err(TrySendError::Full(_)) => {
+            warn!("Subscriber is too slow, push message dropped");
+            false
+        }
+        Err(TrySendError::Closed(_)) => false,
+    }
+}
+
+/// 需要转发到其它节点的发布消息
+pub struct ClusterPublish(pub PublishCmd);
+
+impl LetterMessage for ClusterPublish {
+    fn channel(&self) -> Channel {
+        Channel::PubSub
+    }
+}
+
+/// 启动发布消息的集群转发任务
+pub fn start_pubsub(
+    ctx: RefContext,
+    conn_manager: ConnectionManager,
+    mut recv: mpsc::Receiver<Box<dyn LetterMessage>>,
+) -> anyhow::Result<JoinHandle<()>> {
+    let handler = tokio::spawn(async move {
+        info!("PubSub forward thread startup");
+        let (mut done_ctx, _handler) = Context::with_parent(&ctx, None);
+        loop {
+            select! {
+                _ = done_ctx.done() => {
+                    info!("PubSub forward loop stop");
+                    break;
+                },
+                Some(letter) = recv.recv() => {
+                    if let Some(publish) = letter.as_any().downcast_ref::<ClusterPublish>() {
+                        let mut cmd = publish.0.clone();
+                        // 标记为转发的消息，其它节点只在本地投递，不再继续转发
+                        cmd.forwarded = true;
+                        let command = Command::new(Box::new(cmd), None);
+                        if let Err(err) = cluster::broadcast(&conn_manager, &command).await {
+                            error!("Forward publish to cluster error: {:?}", err);
+                        }
+                    }
+                }
+            }
+        }
+    });
+    Ok(handler)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{start_pubsub, ClusterPublish, PubSub};
+    use crate::command::Command;
+    use crate::config::Config;
+    use crate::connection::{connection::Connection, manager::ConnectionManager};
+    use crate::db::dbvalue::DBValue;
+    use crate::node::{Node, NodeManager, NodeTable, ShareNodeTable};
+    use crate::postman::LetterMessage;
+    use crate::proto::push_message::Push;
+    use crate::proto::{PublishCmd, PushMessage};
+    use crate::protocol::head::Head;
+    use prost::Message;
+    use std::sync::Arc;
+    use tokio::net::TcpListener;
+    use tokio::sync::mpsc;
+    use tokio_context::context::RefContext;
+
+    #[tokio::test]
+    async fn publish_test() {
+        let pubsub = PubSub::new();
+        let (tx, mut rx) = mpsc::channel(8);
+        let id = pubsub.register(tx).await;
+        pubsub
+            .subscribe(id, &[String::from("presence")], false)
+            .await;
+        pubsub
+            .subscribe(id, &[String::from("presence*")], true)
+            .await;
+
+        let online = DBValue::String(String::from("user:1 online"));
+        // 频道订阅和模式订阅各收到一次
+        assert_eq!(pubsub.publish("presence", &online).await, 2);
+        assert_eq!(pubsub.publish("presence:eu", &online).await, 1);
+        assert_eq!(pubsub.publish("orders", &online).await, 0);
+
+        let frames = rx.recv().await.unwrap();
+        let push = PushMessage::decode(&frames[0].payload[..]).unwrap();
+        match push.push {
+            Some(Push::Message(message)) => {
+                assert_eq!(message.channel, "presence");
+                assert!(DBValue::from(message.message.unwrap()) == online);
+            }
+            _ => panic!("push should be pubsub message"),
+        }
+
+        pubsub.unsubscribe(id, &[], true).await;
+        assert_eq!(pubsub.publish("presence:eu", &online).await, 0);
+        pubsub.remove(id).await;
+        assert_eq!(pubsub.publish("presence", &online).await, 0);
+    }
+
+    #[tokio::test]
+    async fn forward_publish_test() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port() as usize;
+        let mut node_table = ShareNodeTable::new(NodeTable::new(Arc::new(Config::default())));
+        node_table
+            .ping(Node::new("127.0.0.1", 2, port, false, true))
+            .await
+            .unwrap();
+        let (ctx, _handle) = RefContext::new();
+        let (tx, rx) = mpsc::channel::<Box<dyn LetterMessage>>(8);
+        start_pubsub(ctx, ConnectionManager::new(node_table), rx).unwrap();
+
+        let publish = PublishCmd {
+            channel: String::from("presence"),
+            message: Some(DBValue::String(String::from("online")).into()),
+            forwarded: false,
+        };
+        tx.send(Box::new(ClusterPublish(publish))).await.unwrap();
+
+        // 其它节点收到标记为转发的发布命令
+        let (stream, _) = listener.accept().await.unwrap();
+        let conn = Connection::new(stream);
+        let mut payload = Vec::new();
+        loop {
+            let frame = conn.read_frame().await.unwrap().unwrap();
+            payload.extend_from_slice(&frame.payload);
+            if frame.header.head == Head::FIN {
+                break;
+            }
+        }
+        let command = Command::from(&payload[..]);
+        let forwarded = command
+            .inner_ref()
+            .as_any()
+            .downcast_ref::<PublishCmd>()
+            .unwrap();
+        assert_eq!(forwarded.channel, "presence");
+        assert!(forwarded.forwarded);
+    }
+}
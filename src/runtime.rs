@@ -3,10 +3,15 @@ use crate::cmd_server::start_cmd_server;
 use crate::config::Config;
 use crate::connection::manager::ConnectionManager;
 use crate::db::database::{start_db_cmd_channel, Database};
+use crate::db::script::ScriptLimits;
 use crate::discover::start_discover;
+use crate::executor::{start_executor, ExecutorService};
 use crate::node::{NodeTable, ShareNodeTable};
 use crate::postman::{Channel, Postman};
+use crate::pubsub::{start_pubsub, PubSub};
+use crate::watch::WatchHub;
 use anyhow::anyhow;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use tokio::task::JoinHandle;
 use tokio_context::context::RefContext;
@@ -16,6 +21,14 @@ pub struct Runtime {
     // 应用级别的消息邮差
     pub postman: Postman,
     pub cfg: Arc<Config>,
+    // 本节点的发布订阅注册表
+    pub pubsub: PubSub,
+    // 本节点的key变更监听注册表
+    pub watch: WatchHub,
+    // 本节点的任务执行服务
+    pub executor: ExecutorService,
+    // 本节点当前是否为raft leader，由集群线程更新
+    leader: AtomicBool,
 }
 
 impl Runtime {
@@ -23,9 +36,22 @@ impl Runtime {
         Runtime {
             postman: Postman::new(),
             cfg: cfg.clone(),
+            pubsub: PubSub::new(),
+            watch: WatchHub::new(cfg.watch_history),
+            executor: ExecutorService::new(cfg.node_id),
+            leader: AtomicBool::new(false),
         }
     }
 
+    /// 本节点是否为raft leader，定时任务的提案只由leader发起
+    pub fn is_leader(&self) -> bool {
+        self.leader.load(Ordering::Acquire)
+    }
+
+    pub fn set_leader(&self, leader: bool) {
+        self.leader.store(leader, Ordering::Release);
+    }
+
     pub fn new_with_default_config() -> Self {
         Self::new(Arc::new(Config::default()))
     }
@@ -63,7 +89,17 @@ impl Runtime {
             return Err(anyhow!("数据库通道已被打开，无法启动"));
         }
         let db_recv = recv.unwrap();
-        let db = Database::new();
+        let mut db = Database::new();
+        db.remove_empty_hash = app.cfg.remove_empty_hash;
+        db.max_memory = app.cfg.max_memory;
+        db.eviction_policy = app.cfg.eviction_policy;
+        db.watch_enabled = app.cfg.watch_enabled;
+        db.lock_lease_ms = app.cfg.lock_lease.as_millis() as u64;
+        db.session_timeout_ms = app.cfg.session_timeout.as_millis() as u64;
+        db.scripts.limits = ScriptLimits {
+            max_operations: app.cfg.script_max_operations,
+            max_value_size: app.cfg.script_max_value_size,
+        };
         // 启动db_cmd_channel, 用于处理来自本地或者cmd_server的db命令
         let db_cmd_channel_handler = start_db_cmd_channel(app.clone(), ctx.clone(), db, db_recv)?;
 
@@ -85,11 +121,32 @@ impl Runtime {
             proposal_mailbox.unwrap(),
         )?;
 
+        // 启动发布订阅消息的集群转发
+        let pubsub_recv = app.postman.new_channel(Channel::PubSub, 64).await;
+        if pubsub_recv.is_none() {
+            return Err(anyhow!("发布订阅通道已被打开，无法启动"));
+        }
+        let pubsub_handler = start_pubsub(ctx.clone(), conn_manager.clone(), pubsub_recv.unwrap())?;
+
+        // 启动任务执行服务
+        let executor_recv = app.postman.new_channel(Channel::Executor, 64).await;
+        if executor_recv.is_none() {
+            return Err(anyhow!("任务执行通道已被打开，无法启动"));
+        }
+        let executor_handler = start_executor(
+            app.clone(),
+            ctx.clone(),
+            conn_manager.clone(),
+            executor_recv.unwrap(),
+        )?;
+
         Ok(vec![
             discover_handler,
             cmd_server_handler,
             db_cmd_channel_handler,
             cluster_handler,
+            pubsub_handler,
+            executor_handler,
         ])
     }
 }
@@ -22,12 +22,15 @@ mod config;
 mod connection;
 mod db;
 mod discover;
+mod executor;
 mod node;
 mod postman;
 mod proto;
 mod protocol;
+mod pubsub;
 mod runtime;
 mod until;
+mod watch;
 
 fn main() {
     env_logger::init();
@@ -87,6 +90,7 @@ pub fn execute_cmd(
                     )))
                     .into(),
                 ),
+                ephemeral: false,
             });
             let command = Box::new(Command::new(cmd, Some(dbvalue_tx.clone())));
             if let Err(err) = app_ref.postman.send(command).await {
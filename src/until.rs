@@ -4,3 +4,201 @@ pub fn now_ts() -> anyhow::Result<u128> {
     let now = SystemTime::now().duration_since(UNIX_EPOCH)?;
     Ok(now.as_millis())
 }
+
+/// 按照 Redis 的下标语义规范化闭区间 [start, end]，支持负数下标（-1 表示最后一个元素）
+/// 返回 None 表示区间为空
+pub fn normalize_range(start: i64, end: i64, len: usize) -> Option<(usize, usize)> {
+    let len = len as i64;
+    if len == 0 {
+        return None;
+    }
+    let start = if start < 0 { (len + start).max(0) } else { start };
+    let end = if end < 0 { len + end } else { end.min(len - 1) };
+    if start > end || start >= len || end < 0 {
+        return None;
+    }
+    Some((start as usize, end as usize))
+}
+
+/// SplitMix64伪随机数生成器，相同的种子在所有副本上产生相同的序列，
+/// 用于需要随机选择但又必须经raft确定性执行的命令
+pub struct SplitMix64(u64);
+
+impl SplitMix64 {
+    pub fn new(seed: u64) -> Self {
+        SplitMix64(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// 返回[0, n)范围内的随机数
+    pub fn next_below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+}
+
+/// 与进程和Rust版本无关的稳定哈希，用于生成确定性的随机种子和跨节点一致的选择
+pub fn stable_hash(value: &str) -> u64 {
+    murmur64a(value.as_bytes(), 0)
+}
+
+/// MurmurHash64A，结果与平台和Rust版本无关，用于需要持久化或者跨节点一致的哈希
+pub fn murmur64a(data: &[u8], seed: u64) -> u64 {
+    const M: u64 = 0xc6a4a7935bd1e995;
+    const R: u32 = 47;
+    let mut h = seed ^ (data.len() as u64).wrapping_mul(M);
+    let mut chunks = data.chunks_exact(8);
+    for chunk in chunks.by_ref() {
+        let mut k = u64::from_le_bytes(chunk.try_into().unwrap());
+        k = k.wrapping_mul(M);
+        k ^= k >> R;
+        k = k.wrapping_mul(M);
+        h ^= k;
+        h = h.wrapping_mul(M);
+    }
+    let rest = chunks.remainder();
+    if !rest.is_empty() {
+        for (i, b) in rest.iter().enumerate() {
+            h ^= (*b as u64) << (8 * i);
+        }
+        h = h.wrapping_mul(M);
+    }
+    h ^= h >> R;
+    h = h.wrapping_mul(M);
+    h ^= h >> R;
+    h
+}
+
+/// glob风格匹配，支持 * ? [abc] [^a] [a-z] 以及 \ 转义
+pub fn glob_match(pattern: &str, value: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let value: Vec<char> = value.chars().collect();
+    glob_match_chars(&pattern, &value)
+}
+
+fn glob_match_chars(pattern: &[char], value: &[char]) -> bool {
+    let (mut p, mut v) = (0, 0);
+    // 最近一个*的位置以及其匹配到的value位置，用于回溯
+    let mut star: Option<(usize, usize)> = None;
+    while v < value.len() {
+        if p < pattern.len() {
+            match pattern[p] {
+                '*' => {
+                    star = Some((p, v));
+                    p += 1;
+                    continue;
+                }
+                '?' => {
+                    p += 1;
+                    v += 1;
+                    continue;
+                }
+                '[' => {
+                    if let Some((matched, next)) = match_class(pattern, p, value[v]) {
+                        if matched {
+                            p = next;
+                            v += 1;
+                            continue;
+                        }
+                    }
+                }
+                '\\' if p + 1 < pattern.len() => {
+                    if pattern[p + 1] == value[v] {
+                        p += 2;
+                        v += 1;
+                        continue;
+                    }
+                }
+                c => {
+                    if c == value[v] {
+                        p += 1;
+                        v += 1;
+                        continue;
+                    }
+                }
+            }
+        }
+        // 不匹配时回溯到上一个*，让*多匹配一个字符
+        match star {
+            Some((star_p, star_v)) => {
+                star = Some((star_p, star_v + 1));
+                p = star_p + 1;
+                v = star_v + 1;
+            }
+            None => return false,
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+// 匹配字符集合，返回是否匹配以及集合结束后的位置，集合没有闭合时返回None
+fn match_class(pattern: &[char], start: usize, c: char) -> Option<(bool, usize)> {
+    let mut i = start + 1;
+    let negate = i < pattern.len() && pattern[i] == '^';
+    if negate {
+        i += 1;
+    }
+    let mut matched = false;
+    let mut first = true;
+    while i < pattern.len() && (first || pattern[i] != ']') {
+        first = false;
+        let mut low = pattern[i];
+        if low == '\\' && i + 1 < pattern.len() {
+            i += 1;
+            low = pattern[i];
+        }
+        if i + 2 < pattern.len() && pattern[i + 1] == '-' && pattern[i + 2] != ']' {
+            let high = pattern[i + 2];
+            if low <= c && c <= high {
+                matched = true;
+            }
+            i += 3;
+        } else {
+            if low == c {
+                matched = true;
+            }
+            i += 1;
+        }
+    }
+    if i >= pattern.len() {
+        return None;
+    }
+    Some((matched != negate, i + 1))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{glob_match, normalize_range};
+
+    #[test]
+    fn glob_match_test() {
+        assert!(glob_match("*", ""));
+        assert!(glob_match("user:*", "user:42"));
+        assert!(!glob_match("user:*", "session:42"));
+        assert!(glob_match("h?llo", "hallo"));
+        assert!(glob_match("h[ae]llo", "hello"));
+        assert!(!glob_match("h[^e]llo", "hello"));
+        assert!(glob_match("h[a-c]llo", "hbllo"));
+        assert!(glob_match("*:*:end", "a:b:c:end"));
+        assert!(glob_match("a\\*", "a*"));
+        assert!(!glob_match("a\\*", "ab"));
+        assert!(!glob_match("abc", "abcd"));
+    }
+
+    #[test]
+    fn normalize_range_test() {
+        assert_eq!(normalize_range(0, -1, 5), Some((0, 4)));
+        assert_eq!(normalize_range(-3, -2, 5), Some((2, 3)));
+        assert_eq!(normalize_range(1, 100, 5), Some((1, 4)));
+        assert_eq!(normalize_range(-100, 1, 5), Some((0, 1)));
+        assert_eq!(normalize_range(3, 1, 5), None);
+        assert_eq!(normalize_range(5, 10, 5), None);
+        assert_eq!(normalize_range(0, -1, 0), None);
+    }
+}
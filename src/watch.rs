@@ -0,0 +1,305 @@
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use ahash::AHashMap;
+use anyhow::anyhow;
+use tokio::sync::{mpsc, Mutex};
+
+use crate::db::namespace::normalize_namespace;
+use crate::proto::push_message::Push;
+use crate::proto::{WatchCmd, WatchEvent, WatchOp};
+use crate::protocol::frame::Frame;
+use crate::pubsub::{build_push, send_push};
+use crate::until;
+
+// 重放期间暂存新事件的数量，超出时与连接的推送通道一样丢弃
+const REPLAY_BUFFER: usize = 64;
+
+/// 监听的key范围
+#[derive(Clone, Debug, PartialEq)]
+pub enum WatchFilter {
+    Key(String),
+    Prefix(String),
+    Pattern(String),
+}
+
+impl WatchFilter {
+    pub fn from_cmd(cmd: &WatchCmd) -> anyhow::Result<Self> {
+        let filters = [
+            (!cmd.key.is_empty()).then(|| WatchFilter::Key(cmd.key.clone())),
+            (!cmd.prefix.is_empty()).then(|| WatchFilter::Prefix(cmd.prefix.clone())),
+            (!cmd.pattern.is_empty()).then(|| WatchFilter::Pattern(cmd.pattern.clone())),
+        ];
+        let mut filters = filters.into_iter().flatten();
+        match (filters.next(), filters.next()) {
+            (Some(filter), None) => Ok(filter),
+            _ => Err(anyhow!(
+                "Watch requires exactly one of key, prefix or pattern"
+            )),
+        }
+    }
+
+    pub fn matches(&self, key: &str) -> bool {
+        match self {
+            WatchFilter::Key(watched) => watched == key,
+            WatchFilter::Prefix(prefix) => key.starts_with(prefix.as_str()),
+            WatchFilter::Pattern(pattern) => until::glob_match(pattern, key),
+        }
+    }
+}
+
+// 监听者：一个连接上的一次监听
+struct Watcher {
+    namespace: String,
+    filter: WatchFilter,
+    sender: mpsc::Sender<Vec<Frame>>,
+}
+
+impl Watcher {
+    fn accept(&self, event: &WatchEvent) -> bool {
+        if self.namespace != event.namespace {
+            return false;
+        }
+        // 清空和交换命名空间影响命名空间中所有的key
+        match WatchOp::try_from(event.op) {
+            Ok(WatchOp::Flush) | Ok(WatchOp::Swap) => true,
+            _ => self.filter.matches(&event.key),
+        }
+    }
+}
+
+struct WatchState {
+    watchers: AHashMap<u64, Watcher>,
+    // 最近的变更事件，用于断线重连后从指定版本恢复监听
+    history: VecDeque<WatchEvent>,
+    capacity: usize,
+    // 最近一次投递的事件版本号
+    revision: u64,
+}
+
+/// 本节点的监听注册表，变更事件由数据库在应用命令后投递
+pub struct WatchHub {
+    next_id: AtomicU64,
+    state: Mutex<WatchState>,
+}
+
+impl WatchHub {
+    pub fn new(capacity: usize) -> Self {
+        WatchHub {
+            next_id: AtomicU64::new(1),
+            state: Mutex::new(WatchState {
+                watchers: AHashMap::new(),
+                history: VecDeque::with_capacity(capacity),
+                capacity,
+                revision: 0,
+            }),
+        }
+    }
+
+    /// 注册监听，返回监听ID。start_revision大于0时先重放该版本及之后的历史事件，
+    /// 重放的事件与注册后投递的新事件经同一个通道按顺序转发，不重复也不遗漏
+    pub async fn watch(
+        &self,
+        namespace: &str,
+        filter: WatchFilter,
+        start_revision: u64,
+        sender: mpsc::Sender<Vec<Frame>>,
+    ) -> anyhow::Result<u64> {
+        let mut state = self.state.lock().await;
+        let mut watcher = Watcher {
+            namespace: String::from(normalize_namespace(namespace)),
+            filter,
+            sender,
+        };
+        if start_revision > 0 {
+            let oldest = state
+                .history
+                .front()
+                .map_or(state.revision + 1, |event| event.revision);
+            if start_revision < oldest {
+                return Err(anyhow!(
+                    "Revision {} has been compacted, oldest available revision is {}",
+                    start_revision,
+                    oldest
+                ));
+            }
+            let mut replay = Vec::new();
+            for event in state.history.iter() {
+                if event.revision >= start_revision && watcher.accept(event) {
+                    replay.push(build_push(Push::Event(event.clone()))?);
+                }
+            }
+            if !replay.is_empty() {
+                // 持有锁时不能等待连接发送，重放交给单独的任务，新事件暂存在转发通道中
+                let (tx, rx) = mpsc::channel(REPLAY_BUFFER);
+                let sender = std::mem::replace(&mut watcher.sender, tx);
+                tokio::spawn(forward_replay(replay, rx, sender));
+            }
+        }
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        state.watchers.insert(id, watcher);
+        Ok(id)
+    }
+
+    pub async fn unwatch(&self, id: u64) {
+        self.state.lock().await.watchers.remove(&id);
+    }
+
+    /// 投递变更事件给匹配的监听者，并保存到历史中
+    pub async fn dispatch(&self, events: Vec<WatchEvent>) {
+        let mut state = self.state.lock().await;
+        state
+            .watchers
+            .retain(|_, watcher| !watcher.sender.is_closed());
+        for event in events {
+            for watcher in state.watchers.values() {
+                if watcher.accept(&event) {
+                    send_push(&watcher.sender, Push::Event(event.clone()));
+                }
+            }
+            state.revision = event.revision;
+            if state.capacity > 0 {
+                if state.history.len() == state.capacity {
+                    state.history.pop_front();
+                }
+                state.history.push_back(event);
+            }
+        }
+    }
+}
+
+// 先发送重放的事件再转发新事件，连接关闭或者取消监听时结束
+async fn forward_replay(
+    replay: Vec<Vec<Frame>>,
+    mut rx: mpsc::Receiver<Vec<Frame>>,
+    sender: mpsc::Sender<Vec<Frame>>,
+) {
+    for frames in replay {
+        if sender.send(frames).await.is_err() {
+            return;
+        }
+    }
+    while let Some(frames) = rx.recv().await {
+        if sender.send(frames).await.is_err() {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{WatchFilter, WatchHub};
+    use crate::db::database::Database;
+    use crate::db::dbvalue::DBValue;
+    use crate::proto::push_message::Push;
+    use crate::proto::{PushMessage, WatchCmd, WatchEvent, WatchOp};
+    use prost::Message;
+    use tokio::sync::mpsc;
+
+    fn decode(frames: Vec<crate::protocol::frame::Frame>) -> WatchEvent {
+        let push = PushMessage::decode(&frames[0].payload[..]).unwrap();
+        match push.push {
+            Some(Push::Event(event)) => event,
+            _ => panic!("push should be watch event"),
+        }
+    }
+
+    #[test]
+    fn filter_test() {
+        let cmd = WatchCmd {
+            prefix: String::from("user:"),
+            ..Default::default()
+        };
+        let filter = WatchFilter::from_cmd(&cmd).unwrap();
+        assert!(filter.matches("user:1"));
+        assert!(!filter.matches("order:1"));
+        let cmd = WatchCmd {
+            key: String::from("a"),
+            pattern: String::from("a*"),
+            ..Default::default()
+        };
+        assert!(WatchFilter::from_cmd(&cmd).is_err());
+    }
+
+    #[test]
+    fn record_events_test() {
+        let mut db = Database::new();
+        db.watch_enabled = true;
+        db.set(
+            String::from("user:1"),
+            DBValue::String(String::from("alice")),
+        );
+        if let Some(DBValue::String(name)) = db.get_mut("user:1") {
+            name.push_str(" smith");
+        }
+        let events = db.take_events();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].op, WatchOp::Put as i32);
+        assert!(events[0].old_value.is_none());
+        assert_eq!(events[1].op, WatchOp::Put as i32);
+        let old = DBValue::from(events[1].old_value.clone().unwrap());
+        assert!(old == DBValue::String(String::from("alice")));
+
+        // 只读取不修改不产生事件
+        db.get_mut("user:1");
+        assert!(db.take_events().is_empty());
+        db.remove("user:1");
+        let events = db.take_events();
+        assert_eq!(events[0].op, WatchOp::Delete as i32);
+        assert!(events[0].new_value.is_none());
+        assert_eq!(events[0].revision, 3);
+        assert_eq!(db.revision(), 3);
+    }
+
+    #[tokio::test]
+    async fn resume_watch_test() {
+        let hub = WatchHub::new(2);
+        let mut db = Database::new();
+        db.watch_enabled = true;
+        for key in ["user:1", "user:2", "order:1"] {
+            db.set(String::from(key), DBValue::String(String::from("v")));
+        }
+        hub.dispatch(db.take_events()).await;
+
+        let (tx, mut rx) = mpsc::channel(8);
+        let filter = WatchFilter::Prefix(String::from("user:"));
+        // 版本1已经不在历史中
+        assert!(hub.watch("", filter.clone(), 1, tx.clone()).await.is_err());
+        let id = hub.watch("", filter, 2, tx).await.unwrap();
+        assert_eq!(decode(rx.recv().await.unwrap()).key, "user:2");
+        assert!(rx.try_recv().is_err());
+
+        db.remove("user:1");
+        hub.dispatch(db.take_events()).await;
+        let event = decode(rx.recv().await.unwrap());
+        assert_eq!(event.revision, 4);
+        assert_eq!(event.namespace, "default");
+
+        hub.unwatch(id).await;
+        db.remove("user:2");
+        hub.dispatch(db.take_events()).await;
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn replay_without_blocking_test() {
+        let hub = WatchHub::new(16);
+        let mut db = Database::new();
+        db.watch_enabled = true;
+        for i in 0..8 {
+            db.set(format!("user:{}", i), DBValue::Int64(i));
+        }
+        hub.dispatch(db.take_events()).await;
+
+        // 推送通道容量小于重放的事件数量，注册和投递都不等待连接发送
+        let (tx, mut rx) = mpsc::channel(2);
+        hub.watch("", WatchFilter::Prefix(String::from("user:")), 1, tx)
+            .await
+            .unwrap();
+        db.set(String::from("user:8"), DBValue::Int64(8));
+        hub.dispatch(db.take_events()).await;
+        for revision in 1..=9 {
+            assert_eq!(decode(rx.recv().await.unwrap()).revision, revision);
+        }
+    }
+}